@@ -1,7 +1,7 @@
 use crate::{BOARD_SIZE_MAX, Location};
 use super::Board;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ChessType {
     None,
     Black,
@@ -35,9 +35,11 @@ impl ChessChange {
     }
 }
 
+#[derive(Debug)]
 pub enum MoveError {
     Exist(Location),
     NoLiberty(Location),
+    Ko(Location),
     NoMove,
 }
 
@@ -52,53 +54,171 @@ impl GoBoard {
     }
 
     pub fn make_move(&mut self, chess_type: ChessType, location: Location) -> Result<ChessChange, MoveError> {
-        let board_chess = self.get(&location);
+        if self.get(&location) != ChessType::None {
+            return Err(MoveError::Exist(location));
+        }
 
-        match board_chess {
-            ChessType::None => {
-                self.set(&location, chess_type);
-                let deads = GoBoardLiberty::get_deads(self);
-
-                let mut chess_change = ChessChange::new();
-
-                match chess_type {
-                    ChessType::None => {
-                        return Err(MoveError::NoMove);
-                    },
-                    ChessType::Black => {
-                        if deads.0.len() > 0 && deads.1.len() == 0 {
-                            self.set(&location, ChessType::None);
-                            return Err(MoveError::NoLiberty(location));
-                        }
-                        self.set(&location, chess_type);
-
-                        chess_change.at.chess_type = chess_type;
-                        chess_change.at.location = location;
-                        chess_change.remove = deads.1;
-                    },
-                    ChessType::White => {
-                        if deads.1.len() > 0 && deads.0.len() == 0 {
-                            self.set(&location, ChessType::None);
-                            return Err(MoveError::NoLiberty(location));
-                        }
-                        self.set(&location, chess_type);
-
-                        chess_change.at.chess_type = chess_type;
-                        chess_change.at.location = location;
-                        chess_change.remove = deads.0;
-                    },
-                };
+        if chess_type == ChessType::None {
+            return Err(MoveError::NoMove);
+        }
+
+        self.set(&location, chess_type);
+
+        /* Only the chains next to the move can lose the last liberty */
+        let mut remove: Vec<Location> = Vec::new();
+        for neighbor in self.neighbors(&location) {
+            let neighbor_type = self.get(&neighbor);
+
+            if neighbor_type == ChessType::None || neighbor_type == chess_type || remove.contains(&neighbor) {
+                continue;
+            }
+
+            if !self.has_liberty(&neighbor) {
+                remove.extend(self.chain(&neighbor));
+            }
+        }
+
+        if remove.is_empty() && !self.has_liberty(&location) {
+            self.set(&location, ChessType::None);
+            return Err(MoveError::NoLiberty(location));
+        }
 
-                for location in chess_change.remove.iter() {
-                    self.set(location, ChessType::None);
+        remove.sort_by_key(|location| (location.alphabet, location.digit));
+
+        for location in remove.iter() {
+            self.set(location, ChessType::None);
+        }
+
+        let mut chess_change = ChessChange::new();
+        chess_change.at.chess_type = chess_type;
+        chess_change.at.location = location;
+        chess_change.remove = remove;
+
+        Ok(chess_change)
+    }
+
+    /// The stones connected to the location.
+    pub fn chain(&self, location: &Location) -> Vec<Location> {
+        let chess_type = self.get(location);
+        let mut visited = GoBoardVisited::new(self.size);
+        let mut chain: Vec<Location> = vec![*location];
+        let mut queue: Vec<Location> = vec![*location];
+
+        visited.set(location, true);
+
+        while let Some(location) = queue.pop() {
+            for neighbor in self.neighbors(&location) {
+                if self.get(&neighbor) == chess_type && !visited.get(&neighbor) {
+                    visited.set(&neighbor, true);
+                    chain.push(neighbor);
+                    queue.push(neighbor);
                 }
+            }
+        }
 
-                return Ok(chess_change);
-            },
-            _ => {
-                Err(MoveError::Exist(location))
+        chain
+    }
+
+    fn has_liberty(&self, location: &Location) -> bool {
+        self.count_liberties(location, 1) > 0
+    }
+
+    /// The liberties of the chain at the location.
+    pub fn liberties(&self, location: &Location) -> Vec<Location> {
+        let mut liberties: Vec<Location> = Vec::new();
+
+        for stone in self.chain(location) {
+            for neighbor in self.neighbors(&stone) {
+                if self.get(&neighbor) == ChessType::None && !liberties.contains(&neighbor) {
+                    liberties.push(neighbor);
+                }
+            }
+        }
+
+        liberties
+    }
+
+    /// Whether the move is not on a stone and not a suicide, the ko is not checked.
+    pub fn is_legal(&self, chess_type: ChessType, location: &Location) -> bool {
+        if chess_type == ChessType::None || self.get(location) != ChessType::None {
+            return false;
+        }
+
+        let neighbors = self.neighbors(location);
+
+        if neighbors.iter().any(|neighbor| self.get(neighbor) == ChessType::None) {
+            return true;
+        }
+
+        neighbors.iter().any(|neighbor| {
+            match self.get(neighbor) == chess_type {
+                /* Connects to a chain with other liberties */
+                true => self.count_liberties(neighbor, 2) > 1,
+                /* Captures the chain */
+                false => self.count_liberties(neighbor, 2) == 1,
+            }
+        })
+    }
+
+    /// The liberties of the chain at the location, counting stops at the limit.
+    fn count_liberties(&self, location: &Location, limit: usize) -> usize {
+        let mut liberties: Vec<Location> = Vec::new();
+
+        for stone in self.chain(location) {
+            for neighbor in self.neighbors(&stone) {
+                if self.get(&neighbor) == ChessType::None && !liberties.contains(&neighbor) {
+                    liberties.push(neighbor);
+
+                    if liberties.len() >= limit {
+                        return liberties.len();
+                    }
+                }
             }
         }
+
+        liberties.len()
+    }
+
+    /// Whether the empty location is surrounded by the chess type and not a false eye.
+    pub fn is_eye(&self, chess_type: ChessType, location: &Location) -> bool {
+        if self.get(location) != ChessType::None {
+            return false;
+        }
+
+        if self.neighbors(location).iter().any(|neighbor| self.get(neighbor) != chess_type) {
+            return false;
+        }
+
+        let diagonals = self.diagonals(location);
+        let opponents = diagonals.iter()
+            .filter(|diagonal| {
+                let diagonal_type = self.get(diagonal);
+                diagonal_type != chess_type && diagonal_type != ChessType::None
+            })
+            .count();
+
+        match diagonals.len() {
+            4 => opponents < 2,
+            _ => opponents == 0,
+        }
+    }
+
+    /// The point where the immediate recapture of the change is forbidden.
+    pub fn ko(&self, chess_change: &ChessChange) -> Option<Location> {
+        if chess_change.remove.len() != 1 {
+            return None;
+        }
+
+        let location = &chess_change.at.location;
+
+        if self.chain(location).len() != 1 {
+            return None;
+        }
+
+        match self.liberties(location).as_slice() {
+            [liberty] if *liberty == chess_change.remove[0] => Some(*liberty),
+            _ => None,
+        }
     }
 
     pub fn reverse_change(&mut self, chess_change: &ChessChange) {
@@ -120,6 +240,19 @@ impl GoBoard {
             self.board[location.alphabet as usize][location.digit as usize] = back_chess_type;
         }
     }
+
+    /// Plays the change again after it is reversed.
+    pub fn apply_change(&mut self, chess_change: &ChessChange) {
+        if chess_change.at.chess_type == ChessType::None {
+            return;
+        }
+
+        self.set(&chess_change.at.location, chess_change.at.chess_type);
+
+        for location in chess_change.remove.iter() {
+            self.set(location, ChessType::None);
+        }
+    }
 }
 
 impl std::fmt::Display for GoBoard {
@@ -150,102 +283,95 @@ impl std::fmt::Display for GoBoard {
     }
 }
 
-type GoBoardLiberty = Board<bool>;
+pub(crate) type GoBoardVisited = Board<bool>;
 
-impl GoBoardLiberty {
-    fn new(size: u8) -> GoBoardLiberty {
-        GoBoardLiberty {
+impl GoBoardVisited {
+    pub(crate) fn new(size: u8) -> GoBoardVisited {
+        GoBoardVisited {
             size,
             board: [[false; BOARD_SIZE_MAX]; BOARD_SIZE_MAX],
         }
     }
+}
 
-    fn get_deads(board: &GoBoard) -> (Vec<Location>, Vec<Location>) {
-        let mut deads: (Vec<Location>, Vec<Location>) = (Vec::new(), Vec::new());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let board_liberty = GoBoardLiberty::make(board);
+    fn location(alphabet: u8, digit: u8) -> Location {
+        Location {
+            alphabet,
+            digit,
+        }
+    }
 
-        for idx1 in 0..board.size {
-            for idx2 in 0..board.size {
-                let location = Location {
-                    alphabet: idx1,
-                    digit: idx2,
-                };
-                match board.get(&location) {
-                    ChessType::Black => {
-                        match board_liberty.get(&location) {
-                            true => continue,
-                            false => {
-                                deads.0.push(location);
-                            }
-                        }
-                    },
-                    ChessType::White => {
-                        match board_liberty.get(&location) {
-                            true => continue,
-                            false => {
-                                deads.1.push(location);
-                            }
-                        }
-                    },
-                    ChessType::None => continue,
-                }
-            }
+    fn board(black: &[(u8, u8)], white: &[(u8, u8)]) -> GoBoard {
+        let mut board = GoBoard::new(5);
+
+        for (alphabet, digit) in black.iter() {
+            board.set(&location(*alphabet, *digit), ChessType::Black);
+        }
+        for (alphabet, digit) in white.iter() {
+            board.set(&location(*alphabet, *digit), ChessType::White);
         }
 
-        deads
+        board
     }
 
-    fn make(board: &GoBoard) -> GoBoardLiberty {
-        let mut board_liberty = GoBoardLiberty::new(board.size);
+    #[test]
+    fn test_capture() {
+        let mut board = board(&[(1, 0)], &[(0, 0)]);
 
-        for idx1 in 0..board.size {
-            for idx2 in 0..board.size {
-                let location = Location {
-                    alphabet: idx1,
-                    digit: idx2,
-                };
-                if board.get(&location) == ChessType::None {
-                    let mut spread_start: Vec<Location> = Vec::new();
-
-                    for location in board.neighbors(&location).iter() {
-                        match board_liberty.get(location) {
-                            false => {
-                                let has_liberty = match board.get(&location) {
-                                    ChessType::None => continue,
-                                    ChessType::Black => {
-                                        true
-                                    },
-                                    ChessType::White => {
-                                        true
-                                    },
-                                };
-                                if !board_liberty.get(location) {
-                                    board_liberty.set(location, has_liberty);
-                                    spread_start.push(*location);
-                                }
-                            },
-                            true => continue,
-                        };
-                    }
+        assert!(board.is_legal(ChessType::Black, &location(0, 1)));
 
-                    while let Some(spread_location) = spread_start.pop() {
-                        for next_location in board.neighbors(&spread_location).iter() {
-                            if board.get(&next_location) != board.get(&spread_location) {
-                                continue;
-                            }
-
-                            if !board_liberty.get(next_location) {
-                                board_liberty.set(next_location, true);
-                                spread_start.push(*next_location);
-                            }
-                        }
-                    }
-                }
-            }
+        let chess_change = board.make_move(ChessType::Black, location(0, 1)).unwrap();
+
+        assert_eq!(chess_change.remove, [location(0, 0)]);
+        assert_eq!(board.get(&location(0, 0)), ChessType::None);
+        assert_eq!(board.get(&location(0, 1)), ChessType::Black);
+    }
+
+    #[test]
+    fn test_capture_chains() {
+        /* A single stone and a chain of two, both in atari at A2 */
+        let mut board = board(&[(1, 0), (1, 2), (1, 3), (0, 4)], &[(0, 0), (0, 2), (0, 3)]);
+
+        let chess_change = board.make_move(ChessType::Black, location(0, 1)).unwrap();
+
+        assert_eq!(chess_change.remove, [location(0, 0), location(0, 2), location(0, 3)]);
+        for digit in [0, 2, 3].iter() {
+            assert_eq!(board.get(&location(0, *digit)), ChessType::None);
         }
+    }
+
+    #[test]
+    fn test_suicide() {
+        let mut board = board(&[(1, 0), (0, 1)], &[]);
+
+        assert!(!board.is_legal(ChessType::White, &location(0, 0)));
+        assert!(matches!(board.make_move(ChessType::White, location(0, 0)), Err(MoveError::NoLiberty(_))));
+        assert_eq!(board.get(&location(0, 0)), ChessType::None);
+
+        /* Filling the last liberty of the own chain */
+        let mut board = self::board(&[(1, 0), (1, 1), (0, 2)], &[(0, 0)]);
 
-        board_liberty
+        assert!(!board.is_legal(ChessType::White, &location(0, 1)));
+        assert!(matches!(board.make_move(ChessType::White, location(0, 1)), Err(MoveError::NoLiberty(_))));
+        assert_eq!(board.get(&location(0, 1)), ChessType::None);
+        assert_eq!(board.get(&location(0, 0)), ChessType::White);
     }
-}
 
+    #[test]
+    fn test_capture_instead_of_suicide() {
+        /* The white stone at A1 has no liberty, but takes the last liberties of B1 and A2 */
+        let mut board = board(&[(1, 0), (0, 1)], &[(2, 0), (1, 1), (0, 2)]);
+
+        assert!(board.is_legal(ChessType::White, &location(0, 0)));
+
+        let chess_change = board.make_move(ChessType::White, location(0, 0)).unwrap();
+
+        assert_eq!(chess_change.remove, [location(0, 1), location(1, 0)]);
+        assert_eq!(board.get(&location(0, 0)), ChessType::White);
+        assert_eq!(board.get(&location(1, 0)), ChessType::None);
+    }
+}
@@ -4,6 +4,7 @@ pub(crate) mod scoring_board;
 use crate::Location;
 use crate::BOARD_SIZE_MAX;
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Board<T: Copy> {
     pub size: u8,
     pub board: [[T; BOARD_SIZE_MAX]; BOARD_SIZE_MAX],
@@ -55,4 +56,22 @@ impl<T: Copy> Board<T> {
 
         return neighbors;
     }
+
+    pub fn diagonals(&self, location: &Location) -> Vec<Location> {
+        let mut diagonals: Vec<Location> = Vec::new();
+
+        for (alphabet, digit) in [(-1, -1), (-1, 1), (1, -1), (1, 1)].iter() {
+            let alphabet = location.alphabet as i32 + alphabet;
+            let digit = location.digit as i32 + digit;
+
+            if alphabet >= 0 && digit >= 0 && alphabet < self.size as i32 && digit < self.size as i32 {
+                diagonals.push(Location {
+                    alphabet: alphabet as u8,
+                    digit: digit as u8,
+                });
+            }
+        }
+
+        diagonals
+    }
 }
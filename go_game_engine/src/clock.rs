@@ -0,0 +1,390 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Player, PLAYER_NUM};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimeSystem {
+    Absolute {
+        main: Duration,
+    },
+    /// Japanese byo-yomi, a period is not used up if the move is made within it.
+    ByoYomi {
+        main: Duration,
+        period: Duration,
+        periods: u32,
+    },
+    /// Canadian overtime, `stones` moves have to be made in every period.
+    Canadian {
+        main: Duration,
+        period: Duration,
+        stones: u32,
+    },
+    Fischer {
+        main: Duration,
+        increment: Duration,
+        max: Option<Duration>,
+    },
+    /// The time used by a player is given to the opponent.
+    Hourglass {
+        main: Duration,
+    },
+}
+
+impl TimeSystem {
+    pub fn main(&self) -> Duration {
+        match self {
+            TimeSystem::Absolute { main } => *main,
+            TimeSystem::ByoYomi { main, .. } => *main,
+            TimeSystem::Canadian { main, .. } => *main,
+            TimeSystem::Fischer { main, .. } => *main,
+            TimeSystem::Hourglass { main } => *main,
+        }
+    }
+
+    /// The SGF OT property, None if no overtime.
+    pub fn overtime(&self) -> Option<String> {
+        match self {
+            TimeSystem::Absolute { .. } => None,
+            TimeSystem::ByoYomi { period, periods, .. } => Some(format!("{}x{} byo-yomi", periods, period.as_secs_f64())),
+            TimeSystem::Canadian { period, stones, .. } => Some(format!("{}/{} Canadian", stones, period.as_secs_f64())),
+            TimeSystem::Fischer { increment, .. } => Some(format!("Fischer {}", increment.as_secs_f64())),
+            TimeSystem::Hourglass { .. } => Some("hourglass".to_string()),
+        }
+    }
+}
+
+/// The remaining time as the SGF BL/WL and OB/OW properties.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeLeft {
+    /// The main time, or the time of the current period in overtime.
+    pub seconds: f64,
+    /// The byo-yomi periods or the Canadian stones left in overtime.
+    pub periods: Option<u32>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClockError {
+    NotRunning,
+    Timeout(Player),
+}
+
+pub trait TimeSource {
+    /// The time elapsed since an arbitrary fixed point.
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> SystemTimeSource {
+        SystemTimeSource {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> SystemTimeSource {
+        SystemTimeSource::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// The time source only moves when it is advanced, the clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualTimeSource {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> ManualTimeSource {
+        ManualTimeSource::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PlayerClock {
+    main: Duration,
+    /// The time left of the current overtime period.
+    period: Duration,
+    /// The byo-yomi periods or the Canadian stones left.
+    periods: u32,
+}
+
+pub struct Clock<S: TimeSource> {
+    system: TimeSystem,
+    source: S,
+    players: [PlayerClock; PLAYER_NUM],
+    running: Option<(Player, Duration)>,
+}
+
+impl<S: TimeSource> Clock<S> {
+    pub fn new(system: TimeSystem, source: S) -> Clock<S> {
+        let player_clock = match system {
+            TimeSystem::ByoYomi { main, period, periods } => PlayerClock { main, period, periods },
+            TimeSystem::Canadian { main, period, stones } => PlayerClock { main, period, periods: stones },
+            _ => PlayerClock { main: system.main(), period: Duration::from_secs(0), periods: 0 },
+        };
+
+        Clock {
+            system,
+            source,
+            players: [player_clock; PLAYER_NUM],
+            running: None,
+        }
+    }
+
+    pub fn system(&self) -> TimeSystem {
+        self.system
+    }
+
+    pub fn running(&self) -> Option<Player> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// Starts the clock of the player, the running clock is stopped without finishing a move.
+    pub fn start(&mut self, player: Player) {
+        if let Some((running, start)) = self.running {
+            let elapsed = self.source.now() - start;
+            if let Some(player_clock) = self.consume(running, elapsed) {
+                self.players[running as usize] = player_clock;
+            }
+        }
+
+        self.running = Some((player, self.source.now()));
+    }
+
+    /// Stops the running clock as the player finished the move.
+    pub fn stop(&mut self) -> Result<TimeLeft, ClockError> {
+        let (player, start) = match self.running.take() {
+            Some(running) => running,
+            None => return Err(ClockError::NotRunning),
+        };
+
+        let elapsed = self.source.now() - start;
+
+        let player_clock = match self.consume(player, elapsed) {
+            Some(player_clock) => player_clock,
+            None => return Err(ClockError::Timeout(player)),
+        };
+
+        self.players[player as usize] = self.finish_move(player_clock);
+
+        if let TimeSystem::Hourglass { .. } = self.system {
+            self.players[player.switch() as usize].main += elapsed;
+        }
+
+        Ok(self.time_left(player))
+    }
+
+    /// The player whose time is up on the running clock.
+    pub fn flagged(&self) -> Option<Player> {
+        match self.running {
+            Some((player, start)) => match self.consume(player, self.source.now() - start) {
+                Some(_) => None,
+                None => Some(player),
+            },
+            None => None,
+        }
+    }
+
+    pub fn time_left(&self, player: Player) -> TimeLeft {
+        let player_clock = match self.running {
+            Some((running, start)) if running == player => {
+                match self.consume(player, self.source.now() - start) {
+                    Some(player_clock) => player_clock,
+                    None => return TimeLeft {
+                        seconds: 0.0,
+                        periods: match self.system {
+                            TimeSystem::ByoYomi { .. } | TimeSystem::Canadian { .. } => Some(0),
+                            _ => None,
+                        },
+                    },
+                }
+            },
+            _ => self.players[player as usize],
+        };
+
+        let is_overtime = player_clock.main == Duration::from_secs(0);
+
+        match (self.system, is_overtime) {
+            (TimeSystem::ByoYomi { .. }, true) | (TimeSystem::Canadian { .. }, true) => TimeLeft {
+                seconds: player_clock.period.as_secs_f64(),
+                periods: Some(player_clock.periods),
+            },
+            _ => TimeLeft {
+                seconds: player_clock.main.as_secs_f64(),
+                periods: None,
+            },
+        }
+    }
+
+    /// Sets the remaining time as GTP time_left does, `periods` is 0 in the main time.
+    pub fn set_time_left(&mut self, player: Player, seconds: f64, periods: u32) {
+        let player_clock = &mut self.players[player as usize];
+        let time = Duration::from_secs_f64(seconds.max(0.0));
+
+        match (self.system, periods) {
+            (TimeSystem::ByoYomi { .. }, 0) | (TimeSystem::Canadian { .. }, 0) => {
+                player_clock.main = time;
+            },
+            (TimeSystem::ByoYomi { .. }, _) | (TimeSystem::Canadian { .. }, _) => {
+                player_clock.main = Duration::from_secs(0);
+                player_clock.period = time;
+                player_clock.periods = periods;
+            },
+            _ => {
+                player_clock.main = time;
+            },
+        };
+    }
+
+    /// The clock after the elapsed time, None if the time is up.
+    fn consume(&self, player: Player, elapsed: Duration) -> Option<PlayerClock> {
+        let mut player_clock = self.players[player as usize];
+
+        if elapsed <= player_clock.main {
+            player_clock.main -= elapsed;
+            return Some(player_clock);
+        }
+
+        let mut overtime = elapsed - player_clock.main;
+        player_clock.main = Duration::from_secs(0);
+
+        match self.system {
+            TimeSystem::ByoYomi { period, .. } => {
+                while overtime > player_clock.period {
+                    if player_clock.periods <= 1 {
+                        return None;
+                    }
+
+                    overtime -= player_clock.period;
+                    player_clock.periods -= 1;
+                    player_clock.period = period;
+                }
+
+                player_clock.period -= overtime;
+                Some(player_clock)
+            },
+            TimeSystem::Canadian { .. } => {
+                if overtime > player_clock.period {
+                    return None;
+                }
+
+                player_clock.period -= overtime;
+                Some(player_clock)
+            },
+            _ => None,
+        }
+    }
+
+    fn finish_move(&self, mut player_clock: PlayerClock) -> PlayerClock {
+        let is_overtime = player_clock.main == Duration::from_secs(0);
+
+        match self.system {
+            TimeSystem::ByoYomi { period, .. } if is_overtime => {
+                player_clock.period = period;
+            },
+            TimeSystem::Canadian { period, stones, .. } if is_overtime => {
+                player_clock.periods -= 1;
+
+                if player_clock.periods == 0 {
+                    player_clock.period = period;
+                    player_clock.periods = stones;
+                }
+            },
+            TimeSystem::Fischer { increment, max, .. } => {
+                player_clock.main += increment;
+
+                if let Some(max) = max {
+                    player_clock.main = std::cmp::min(player_clock.main, max);
+                }
+            },
+            _ => {},
+        };
+
+        player_clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_byo_yomi() {
+        let source = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeSystem::ByoYomi { main: secs(10), period: secs(30), periods: 3 }, source.clone());
+
+        clock.start(Player::Black);
+        source.advance(secs(15));
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 30.0, periods: Some(3) }));
+
+        clock.start(Player::Black);
+        source.advance(secs(65));
+        assert_eq!(clock.time_left(Player::Black), TimeLeft { seconds: 25.0, periods: Some(1) });
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 30.0, periods: Some(1) }));
+
+        clock.start(Player::Black);
+        source.advance(secs(31));
+        assert_eq!(clock.flagged(), Some(Player::Black));
+        assert_eq!(clock.stop(), Err(ClockError::Timeout(Player::Black)));
+    }
+
+    #[test]
+    fn test_canadian() {
+        let source = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeSystem::Canadian { main: secs(0), period: secs(60), stones: 2 }, source.clone());
+
+        clock.start(Player::White);
+        source.advance(secs(40));
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 20.0, periods: Some(1) }));
+
+        clock.start(Player::White);
+        source.advance(secs(20));
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 60.0, periods: Some(2) }));
+
+        clock.start(Player::White);
+        source.advance(secs(61));
+        assert_eq!(clock.stop(), Err(ClockError::Timeout(Player::White)));
+    }
+
+    #[test]
+    fn test_fischer_and_hourglass() {
+        let source = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeSystem::Fischer { main: secs(60), increment: secs(10), max: Some(secs(65)) }, source.clone());
+
+        clock.start(Player::Black);
+        source.advance(secs(2));
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 65.0, periods: None }));
+
+        let mut clock = Clock::new(TimeSystem::Hourglass { main: secs(60) }, source.clone());
+
+        clock.start(Player::Black);
+        source.advance(secs(20));
+        assert_eq!(clock.stop(), Ok(TimeLeft { seconds: 40.0, periods: None }));
+        assert_eq!(clock.time_left(Player::White), TimeLeft { seconds: 80.0, periods: None });
+    }
+}
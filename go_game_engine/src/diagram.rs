@@ -0,0 +1,469 @@
+use crate::{Location, Player, ChessType, BOARD_SIZE_MAX};
+
+const DIAGRAM_PREFIX: &str = "$$";
+const DIAGRAM_NUMBER_MAX: u8 = 10;
+const DIAGRAM_SIZE_DEFAULT: u8 = 19;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Markup {
+    None,
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+    Label(char),
+    /// The numbered move, 1 to 10 as Sensei's Library allows.
+    Number(u8),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DiagramPoint {
+    pub stone: ChessType,
+    pub markup: Markup,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum DiagramError {
+    MissingHeader,
+    InvalidHeader(String),
+    InvalidSymbol(char),
+    RaggedRows,
+    TooLarge,
+    IllegalMove(u8),
+}
+
+impl std::fmt::Display for DiagramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagramError::MissingHeader => write!(f, "The diagram does not start with $$"),
+            DiagramError::InvalidHeader(header) => write!(f, "Invalid diagram header: {}", header),
+            DiagramError::InvalidSymbol(symbol) => write!(f, "Invalid diagram symbol: {}", symbol),
+            DiagramError::RaggedRows => write!(f, "The diagram rows have different width"),
+            DiagramError::TooLarge => write!(f, "The diagram is larger than the board"),
+            DiagramError::IllegalMove(number) => write!(f, "The move {} is illegal", number),
+        }
+    }
+}
+
+/// A position in the Sensei's Library diagram format.
+///
+/// The first line is the header `$$[B|W][c][size][m<number>] [title]`, and
+/// every board line starts with `$$`, e.g.
+///
+/// ```text
+/// $$Bc9 Capture
+/// $$ +-------------------+
+/// $$ | . . . . . . . . . |
+/// $$ | . . X O 1 . . . . |
+/// ```
+pub struct Diagram {
+    pub title: String,
+    /// The player of the move numbered 1.
+    pub first_player: Player,
+    /// The move number of the move numbered 1.
+    pub first_number: i32,
+    pub coordinates: bool,
+    size: u8,
+    points: Vec<Vec<DiagramPoint>>,
+}
+
+impl Diagram {
+    pub fn new(size: u8) -> Diagram {
+        Diagram {
+            title: "".to_string(),
+            first_player: Player::Black,
+            first_number: 1,
+            coordinates: true,
+            size,
+            points: vec![vec![DiagramPoint { stone: ChessType::None, markup: Markup::None }; size as usize]; size as usize],
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn get(&self, location: Location) -> DiagramPoint {
+        self.points[location.alphabet as usize][location.digit as usize]
+    }
+
+    pub fn set(&mut self, location: Location, point: DiagramPoint) {
+        self.points[location.alphabet as usize][location.digit as usize] = point;
+    }
+
+    pub fn set_markup(&mut self, location: Location, markup: Markup) {
+        self.points[location.alphabet as usize][location.digit as usize].markup = markup;
+    }
+
+    /// The player of the given numbered move.
+    pub fn number_player(&self, number: u8) -> Player {
+        match number % 2 {
+            1 => self.first_player,
+            _ => self.first_player.switch(),
+        }
+    }
+
+    fn parse_header(&mut self, header: &str) -> Result<Option<u8>, DiagramError> {
+        let mut size: Option<u8> = None;
+        let mut chars = header.chars().peekable();
+
+        if let Some(&player) = chars.peek() {
+            match player {
+                'B' => {
+                    chars.next();
+                },
+                'W' => {
+                    self.first_player = Player::White;
+                    chars.next();
+                },
+                _ => {},
+            };
+        }
+
+        if let Some('c') = chars.peek() {
+            self.coordinates = true;
+            chars.next();
+        } else {
+            self.coordinates = false;
+        }
+
+        let mut digits = "".to_string();
+        while let Some(&digit) = chars.peek() {
+            if !digit.is_ascii_digit() {
+                break;
+            }
+            digits.push(digit);
+            chars.next();
+        }
+
+        if !digits.is_empty() {
+            size = match digits.parse::<u8>() {
+                Ok(size) if size > 0 && size as usize <= BOARD_SIZE_MAX => Some(size),
+                _ => return Err(DiagramError::InvalidHeader(header.to_string())),
+            };
+        }
+
+        if let Some('m') = chars.peek() {
+            chars.next();
+
+            let number: String = chars.by_ref().take_while(|c| c.is_ascii_digit()).collect();
+            self.first_number = match number.parse::<i32>() {
+                Ok(number) => number,
+                Err(_) => return Err(DiagramError::InvalidHeader(header.to_string())),
+            };
+        }
+
+        self.title = chars.collect::<String>().trim().to_string();
+
+        Ok(size)
+    }
+}
+
+fn is_hoshi(size: u8, alphabet: u8, digit: u8) -> bool {
+    let edge = match size {
+        0..=6 => return false,
+        7..=12 => 2,
+        _ => 3,
+    };
+
+    let hoshi_line = |index: u8| {
+        index == edge || index == size - 1 - edge || (size % 2 == 1 && size >= 13 && index == size / 2)
+    };
+
+    if size % 2 == 1 && alphabet == size / 2 && digit == size / 2 {
+        return true;
+    }
+
+    hoshi_line(alphabet) && hoshi_line(digit)
+}
+
+fn to_symbol(point: &DiagramPoint, hoshi: bool) -> char {
+    match (point.stone, point.markup) {
+        (_, Markup::Number(number)) => match number {
+            DIAGRAM_NUMBER_MAX => '0',
+            _ => (b'0' + number) as char,
+        },
+        (ChessType::Black, Markup::Circle) => 'B',
+        (ChessType::White, Markup::Circle) => 'W',
+        (ChessType::Black, Markup::Square) => '#',
+        (ChessType::White, Markup::Square) => '@',
+        (ChessType::Black, Markup::Triangle) => 'Y',
+        (ChessType::White, Markup::Triangle) => 'Q',
+        (ChessType::Black, Markup::Cross) => 'Z',
+        (ChessType::White, Markup::Cross) => 'P',
+        (ChessType::Black, _) => 'X',
+        (ChessType::White, _) => 'O',
+        (ChessType::None, Markup::Circle) => 'C',
+        (ChessType::None, Markup::Square) => 'S',
+        (ChessType::None, Markup::Triangle) => 'T',
+        (ChessType::None, Markup::Cross) => 'M',
+        (ChessType::None, Markup::Label(label)) => label,
+        (ChessType::None, Markup::None) => match hoshi {
+            true => ',',
+            false => '.',
+        },
+    }
+}
+
+fn from_symbol(symbol: char, diagram: &Diagram) -> Result<DiagramPoint, DiagramError> {
+    let point = |stone, markup| {
+        Ok(DiagramPoint {
+            stone,
+            markup,
+        })
+    };
+
+    match symbol {
+        '.' | ',' => point(ChessType::None, Markup::None),
+        'X' => point(ChessType::Black, Markup::None),
+        'O' => point(ChessType::White, Markup::None),
+        'B' => point(ChessType::Black, Markup::Circle),
+        'W' => point(ChessType::White, Markup::Circle),
+        '#' => point(ChessType::Black, Markup::Square),
+        '@' => point(ChessType::White, Markup::Square),
+        'Y' => point(ChessType::Black, Markup::Triangle),
+        'Q' => point(ChessType::White, Markup::Triangle),
+        'Z' => point(ChessType::Black, Markup::Cross),
+        'P' => point(ChessType::White, Markup::Cross),
+        'C' => point(ChessType::None, Markup::Circle),
+        'S' => point(ChessType::None, Markup::Square),
+        'T' => point(ChessType::None, Markup::Triangle),
+        'M' => point(ChessType::None, Markup::Cross),
+        'a'..='z' => point(ChessType::None, Markup::Label(symbol)),
+        '0'..='9' => {
+            let number = match symbol {
+                '0' => DIAGRAM_NUMBER_MAX,
+                _ => symbol as u8 - b'0',
+            };
+
+            let stone = match diagram.number_player(number) {
+                Player::Black => ChessType::Black,
+                Player::White => ChessType::White,
+            };
+
+            point(stone, Markup::Number(number))
+        },
+        _ => Err(DiagramError::InvalidSymbol(symbol)),
+    }
+}
+
+impl std::str::FromStr for Diagram {
+    type Err = DiagramError;
+
+    fn from_str(text: &str) -> Result<Diagram, DiagramError> {
+        let mut lines = text.lines()
+            .map(|line| line.trim())
+            .filter(|line| line.starts_with(DIAGRAM_PREFIX))
+            .map(|line| &line[DIAGRAM_PREFIX.len()..]);
+
+        let mut diagram = Diagram::new(0);
+
+        let size = match lines.next() {
+            Some(header) => diagram.parse_header(header)?,
+            None => return Err(DiagramError::MissingHeader),
+        };
+
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut edges = (false, false, false, false); /* top, bottom, left, right */
+
+        for line in lines {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+
+            if line.contains('-') && line.chars().all(|c| c == '-' || c == '+' || c.is_whitespace()) {
+                match rows.len() {
+                    0 => edges.0 = true,
+                    _ => edges.1 = true,
+                };
+                continue;
+            }
+
+            let mut row: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+
+            if row.first() == Some(&'|') {
+                edges.2 = true;
+                row.remove(0);
+            }
+
+            if row.last() == Some(&'|') {
+                edges.3 = true;
+                row.pop();
+            }
+
+            if let Some(width) = rows.first().map(|first| first.len()) {
+                if width != row.len() {
+                    return Err(DiagramError::RaggedRows);
+                }
+            }
+
+            rows.push(row);
+        }
+
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+
+        let size = match size {
+            Some(size) => size,
+            None => match edges {
+                (true, true, true, true) if width == height => width as u8,
+                _ => DIAGRAM_SIZE_DEFAULT,
+            },
+        };
+
+        if width > size as usize || height > size as usize || size as usize > BOARD_SIZE_MAX {
+            return Err(DiagramError::TooLarge);
+        }
+
+        diagram.size = size;
+        diagram.points = Diagram::new(size).points;
+
+        let alphabet_offset = match (edges.2, edges.3) {
+            (false, true) => size as usize - width,
+            _ => 0,
+        };
+
+        let top_digit = match (edges.0, edges.1) {
+            (false, true) => height - 1,
+            _ => size as usize - 1,
+        };
+
+        for (row_idx, row) in rows.iter().enumerate() {
+            for (column_idx, symbol) in row.iter().enumerate() {
+                let point = from_symbol(*symbol, &diagram)?;
+
+                diagram.set(Location {
+                    alphabet: (alphabet_offset + column_idx) as u8,
+                    digit: (top_digit - row_idx) as u8,
+                }, point);
+            }
+        }
+
+        Ok(diagram)
+    }
+}
+
+impl std::fmt::Display for Diagram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}{}",
+            DIAGRAM_PREFIX,
+            match self.first_player {
+                Player::Black => 'B',
+                Player::White => 'W',
+            },
+            match self.coordinates {
+                true => "c",
+                false => "",
+            },
+            self.size,
+        )?;
+
+        if self.first_number != 1 {
+            write!(f, "m{}", self.first_number)?;
+        }
+
+        if !self.title.is_empty() {
+            write!(f, " {}", self.title)?;
+        }
+
+        let edge = format!("{} +{}+\n", DIAGRAM_PREFIX, "-".repeat(self.size as usize * 2 + 1));
+
+        write!(f, "\n{}", edge)?;
+
+        for digit in (0..self.size).rev() {
+            write!(f, "{} |", DIAGRAM_PREFIX)?;
+
+            for alphabet in 0..self.size {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+
+                write!(f, " {}", to_symbol(&self.get(location), is_hoshi(self.size, alphabet, digit)))?;
+            }
+
+            writeln!(f, " |")?;
+        }
+
+        write!(f, "{}", edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GoGameEngine;
+
+    #[test]
+    fn test_parse_numbered_capture() {
+        let diagram: Diagram = "
+            $$Bc9 Capture
+            $$ +-------------------+
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . X . . . . . |
+            $$ | . . X O 1 . . . . |
+            $$ | . . . X . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ +-------------------+
+        ".parse().unwrap();
+
+        assert_eq!(diagram.size(), 9);
+        assert_eq!(diagram.title, "Capture");
+
+        let engine = GoGameEngine::from_diagram(&diagram, 6.5).unwrap();
+
+        assert_eq!(engine.get_chess(Location { alphabet: 3, digit: 4 }), ChessType::None);
+        assert_eq!(engine.get_chess(Location { alphabet: 4, digit: 4 }), ChessType::Black);
+        assert_eq!(engine.get_capture(&Player::Black), 1);
+        assert!(engine.player() == Player::White);
+    }
+
+    #[test]
+    fn test_parse_corner() {
+        let diagram: Diagram = "
+            $$W
+            $$ | . . . .
+            $$ | . O X .
+            $$ | . . . .
+            $$ +--------
+        ".parse().unwrap();
+
+        assert_eq!(diagram.size(), 19);
+        assert!(diagram.first_player == Player::White);
+        assert_eq!(diagram.get(Location { alphabet: 1, digit: 1 }).stone, ChessType::White);
+        assert_eq!(diagram.get(Location { alphabet: 2, digit: 1 }).stone, ChessType::Black);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut engine = GoGameEngine::new(9, 6.5);
+
+        for (alphabet, digit) in [(2, 2), (6, 6), (2, 6), (6, 2)].iter() {
+            assert!(engine.make_move(Location { alphabet: *alphabet, digit: *digit }).is_ok());
+        }
+
+        let diagram = engine.to_diagram(2);
+        let text = diagram.to_string();
+
+        assert!(text.starts_with("$$Bc9m3\n"));
+
+        let parsed: Diagram = text.parse().unwrap();
+        let replayed = GoGameEngine::from_diagram(&parsed, 6.5).unwrap();
+
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(replayed.get_chess(Location { alphabet: 6, digit: 2 }), ChessType::White);
+        assert!(replayed.player() == Player::Black);
+
+        let last_move = replayed.last_move().unwrap();
+        assert_eq!(last_move.number, 4);
+        assert_eq!(last_move.location, Some(Location { alphabet: 6, digit: 2 }));
+        assert_eq!(replayed.move_numbers()[2][6], Some(3));
+        assert_eq!(replayed.move_numbers()[2][2], None);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::Location;
+
+/// The handicap points of the GTP `fixed_handicap`, None if the stones can't be placed on the board.
+///
+/// The stones are on the 4-4 points from 13x13 and on the 3-3 points below, the points on the
+/// middle lines are used for more than 4 stones on the odd boards larger than 7x7.
+pub fn fixed_handicap(size: u8, stones: usize) -> Option<Vec<Location>> {
+    if !(2..=9).contains(&stones) || size < 7 {
+        return None;
+    }
+
+    if stones > 4 && !(size % 2 == 1 && size > 7) {
+        return None;
+    }
+
+    let low = match size >= 13 {
+        true => 3,
+        false => 2,
+    };
+    let high = size - 1 - low;
+    let middle = size / 2;
+
+    let point = |alphabet: u8, digit: u8| Location {
+        alphabet,
+        digit,
+    };
+
+    let corners = [point(low, low), point(high, high), point(low, high), point(high, low)];
+    let sides = [point(low, middle), point(high, middle), point(middle, low), point(middle, high)];
+
+    let mut locations: Vec<Location> = corners.iter().take(stones).copied().collect();
+
+    if stones >= 6 {
+        locations.extend(sides.iter().take((stones - 4) / 2 * 2));
+    }
+
+    /* The center for the odd stones */
+    if stones >= 5 && stones % 2 == 1 {
+        locations.push(point(middle, middle));
+    }
+
+    Some(locations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_handicap() {
+        let locations = fixed_handicap(19, 9).unwrap();
+
+        assert_eq!(locations.len(), 9);
+        assert_eq!(locations[0], Location { alphabet: 3, digit: 3 });
+        assert_eq!(locations[1], Location { alphabet: 15, digit: 15 });
+        assert_eq!(locations[8], Location { alphabet: 9, digit: 9 });
+
+        assert_eq!(fixed_handicap(9, 2).unwrap(), vec![Location { alphabet: 2, digit: 2 }, Location { alphabet: 6, digit: 6 }]);
+        assert_eq!(fixed_handicap(19, 6).unwrap().len(), 6);
+        assert!(fixed_handicap(19, 10).is_none());
+        assert!(fixed_handicap(10, 5).is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{BOARD_SIZE_MAX, Location, Player};
+use crate::board::go_board::{GoBoard, GoBoardVisited, ChessType};
+
+const STONE_INFLUENCE: i32 = 128;
+const DILATIONS: usize = 5;
+const EROSIONS: usize = 21;
+
+type InfluenceBoard = crate::board::Board<i32>;
+
+impl InfluenceBoard {
+    fn new(size: u8) -> InfluenceBoard {
+        InfluenceBoard {
+            size,
+            board: [[0; BOARD_SIZE_MAX]; BOARD_SIZE_MAX],
+        }
+    }
+}
+
+/// The ownership of the position by the influence.
+#[derive(Clone, Debug)]
+pub struct Influence {
+    /// Positive for black and negative for white, indexed by alphabet then digit.
+    pub influence: Vec<Vec<i32>>,
+    pub ownership: Vec<Vec<Option<Player>>>,
+    /// The stones in the area of the opponent.
+    pub dead: Vec<Location>,
+}
+
+impl Influence {
+    /// The points owned by the player, the dead stones of the opponent included but not the stones of the player.
+    pub fn territory(&self, board: &GoBoard, player: Player) -> i32 {
+        let chess_type = match player {
+            Player::Black => ChessType::Black,
+            Player::White => ChessType::White,
+        };
+        let mut territory = 0;
+
+        for (alphabet, column) in self.ownership.iter().enumerate() {
+            for (digit, owner) in column.iter().enumerate() {
+                let location = Location {
+                    alphabet: alphabet as u8,
+                    digit: digit as u8,
+                };
+
+                if *owner == Some(player) && board.get(&location) != chess_type {
+                    territory += 1;
+                }
+            }
+        }
+
+        territory
+    }
+
+    /// The dead stones of the player.
+    pub fn dead_stones(&self, board: &GoBoard, player: Player) -> i32 {
+        let chess_type = match player {
+            Player::Black => ChessType::Black,
+            Player::White => ChessType::White,
+        };
+
+        self.dead.iter().filter(|location| board.get(location) == chess_type).count() as i32
+    }
+}
+
+/// Estimates the ownership by Bouzy's 5/21 dilation and erosion.
+///
+/// A chain is dead if none of the empty points next to it is owned by its color and the
+/// influence computed without it owns all its points for the opponent, that is the chain is
+/// inside the area of the opponent. The influence is computed again without the dead stones.
+pub fn estimate(board: &GoBoard) -> Influence {
+    let influence = bouzy(board);
+
+    let mut checked = GoBoardVisited::new(board.size());
+    let mut dead: Vec<Location> = Vec::new();
+
+    for_each_location(board.size(), |location| {
+        let chess_type = board.get(&location);
+
+        if chess_type == ChessType::None || checked.get(&location) {
+            return;
+        }
+
+        let chain = board.chain(&location);
+        for stone in chain.iter() {
+            checked.set(stone, true);
+        }
+
+        let sign = match chess_type {
+            ChessType::Black => 1,
+            _ => -1,
+        };
+
+        let has_area = board.liberties(&location).iter()
+            .any(|liberty| influence.get(liberty) * sign > 0);
+
+        if has_area {
+            return;
+        }
+
+        let mut without = board.clone();
+        for stone in chain.iter() {
+            without.set(stone, ChessType::None);
+        }
+
+        let surrounding = bouzy(&without);
+        if chain.iter().all(|stone| surrounding.get(stone) * sign < 0) {
+            dead.extend(chain);
+        }
+    });
+
+    let influence = match dead.is_empty() {
+        true => influence,
+        false => {
+            let mut board = board.clone();
+
+            for location in dead.iter() {
+                board.set(location, ChessType::None);
+            }
+
+            bouzy(&board)
+        },
+    };
+
+    let size = board.size() as usize;
+    let mut values: Vec<Vec<i32>> = vec![vec![0; size]; size];
+    let mut ownership: Vec<Vec<Option<Player>>> = vec![vec![None; size]; size];
+
+    for_each_location(board.size(), |location| {
+        let value = influence.get(&location);
+
+        values[location.alphabet as usize][location.digit as usize] = value;
+        ownership[location.alphabet as usize][location.digit as usize] = match value {
+            value if value > 0 => Some(Player::Black),
+            value if value < 0 => Some(Player::White),
+            _ => None,
+        };
+    });
+
+    Influence {
+        influence: values,
+        ownership,
+        dead,
+    }
+}
+
+fn bouzy(board: &GoBoard) -> InfluenceBoard {
+    let mut influence = InfluenceBoard::new(board.size());
+
+    for_each_location(board.size(), |location| {
+        influence.set(&location, match board.get(&location) {
+            ChessType::Black => STONE_INFLUENCE,
+            ChessType::White => -STONE_INFLUENCE,
+            ChessType::None => 0,
+        });
+    });
+
+    for _ in 0..DILATIONS {
+        influence = dilate(&influence);
+    }
+
+    for _ in 0..EROSIONS {
+        influence = erode(&influence);
+    }
+
+    influence
+}
+
+/// A point not next to the opposite sign gains the number of the neighbors of its sign.
+fn dilate(influence: &InfluenceBoard) -> InfluenceBoard {
+    let mut dilated = influence.clone();
+
+    for_each_location(influence.size(), |location| {
+        let value = influence.get(&location);
+        let neighbors: Vec<i32> = influence.neighbors(&location).iter()
+            .map(|neighbor| influence.get(neighbor))
+            .collect();
+
+        let positives = neighbors.iter().filter(|neighbor| **neighbor > 0).count() as i32;
+        let negatives = neighbors.iter().filter(|neighbor| **neighbor < 0).count() as i32;
+
+        if value >= 0 && negatives == 0 {
+            dilated.set(&location, value + positives);
+        } else if value <= 0 && positives == 0 {
+            dilated.set(&location, value - negatives);
+        }
+    });
+
+    dilated
+}
+
+/// A point loses the number of the neighbors not of its sign, without crossing zero.
+fn erode(influence: &InfluenceBoard) -> InfluenceBoard {
+    let mut eroded = influence.clone();
+
+    for_each_location(influence.size(), |location| {
+        let value = influence.get(&location);
+        let neighbors = influence.neighbors(&location);
+
+        if value > 0 {
+            let others = neighbors.iter().filter(|neighbor| influence.get(neighbor) <= 0).count() as i32;
+            eroded.set(&location, std::cmp::max(value - others, 0));
+        } else if value < 0 {
+            let others = neighbors.iter().filter(|neighbor| influence.get(neighbor) >= 0).count() as i32;
+            eroded.set(&location, std::cmp::min(value + others, 0));
+        }
+    });
+
+    eroded
+}
+
+fn for_each_location<F: FnMut(Location)>(size: u8, mut f: F) {
+    for alphabet in 0..size {
+        for digit in 0..size {
+            f(Location {
+                alphabet,
+                digit,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Diagram, GoGameEngine, Location, Player};
+
+    #[test]
+    fn test_estimate() {
+        let diagram: Diagram = "
+            $$B
+            $$ +-------------------+
+            $$ | . . . X O . . . . |
+            $$ | . . . X O . . . . |
+            $$ | . . X X O . . . . |
+            $$ | . . X O O . . . . |
+            $$ | . . X O . . . . . |
+            $$ | . . X O . . O . . |
+            $$ | . O X O . . . . . |
+            $$ | . . X O . . . . . |
+            $$ | . . X O . . . . . |
+            $$ +-------------------+
+        ".parse().unwrap();
+        let engine = GoGameEngine::from_diagram(&diagram, 6.5).unwrap();
+        let estimate = engine.estimate_score();
+
+        assert_eq!(estimate.influence.ownership[0][0], Some(Player::Black));
+        assert_eq!(estimate.influence.ownership[8][8], Some(Player::White));
+        assert_eq!(estimate.influence.dead, vec![Location { alphabet: 1, digit: 2 }]);
+        assert!(estimate.score.1 > estimate.score.0);
+
+        /* The score is not changed by the estimation */
+        assert_eq!(engine.get_score(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_estimate_opening() {
+        let mut engine = GoGameEngine::new(19, 6.5);
+
+        /* The star points, the colors are swapped by the mirror so the position is even */
+        for (alphabet, digit) in [(3, 3), (3, 15), (15, 15), (15, 3)].iter() {
+            assert!(engine.make_move(Location { alphabet: *alphabet, digit: *digit }).is_ok());
+        }
+
+        let estimate = engine.estimate_score();
+
+        assert!(estimate.influence.dead.is_empty());
+        assert_eq!(estimate.score.1 - estimate.score.0, 6.5);
+    }
+}
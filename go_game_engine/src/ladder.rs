@@ -0,0 +1,319 @@
+use crate::Location;
+use crate::board::go_board::{GoBoard, Chess, ChessType};
+
+/// The reading gives up and lets the prey escape after so many moves.
+const MAX_NODES: usize = 10000;
+
+#[derive(Clone)]
+pub struct Ladder {
+    /// Whether the chain is captured whatever the prey does.
+    pub captured: bool,
+    /// The main line of the reading, ends with the capture if captured.
+    pub sequence: Vec<Chess>,
+    /// The stones of the prey's color next to the path of the broken ladder.
+    pub breakers: Vec<Location>,
+    /// The empty points where a stone of the prey's color breaks the working ladder.
+    pub breaker_points: Vec<Location>,
+}
+
+struct Reader {
+    prey: ChessType,
+    attacker: ChessType,
+    nodes: usize,
+}
+
+/// Reads whether the chain at the location, in atari or with two liberties, is captured by a ladder.
+///
+/// A chain in atari has to escape if the prey is to move, a chain with two liberties is read
+/// if the attacker is to move, otherwise there is no ladder and None is returned.
+pub fn read_ladder(board: &GoBoard, location: &Location, to_move: ChessType, ko: Option<Location>) -> Option<Ladder> {
+    let mut ladder = read(board, location, to_move, ko)?;
+
+    if ladder.captured {
+        ladder.breaker_points = breaker_points(board, location, to_move, ko, &ladder.sequence);
+    }
+
+    Some(ladder)
+}
+
+fn read(board: &GoBoard, location: &Location, to_move: ChessType, ko: Option<Location>) -> Option<Ladder> {
+    let prey = board.get(location);
+    let attacker = match prey {
+        ChessType::None => return None,
+        ChessType::Black => ChessType::White,
+        ChessType::White => ChessType::Black,
+    };
+
+    let mut reader = Reader {
+        prey,
+        attacker,
+        nodes: 0,
+    };
+
+    let liberties = board.liberties(location);
+
+    let (captured, sequence) = match (liberties.len(), to_move == prey) {
+        (1, true) => reader.defend(board, location, ko),
+        (1, false) => (true, vec![Chess { chess_type: attacker, location: liberties[0] }]),
+        (2, false) => reader.attack(board, location, ko),
+        _ => return None,
+    };
+
+    let breakers = match captured {
+        true => Vec::new(),
+        false => {
+            let chain = board.chain(location);
+            let mut breakers: Vec<Location> = Vec::new();
+
+            for chess in sequence.iter() {
+                for neighbor in board.neighbors(&chess.location) {
+                    if board.get(&neighbor) == prey && !chain.contains(&neighbor) && !breakers.contains(&neighbor) {
+                        breakers.push(neighbor);
+                    }
+                }
+            }
+
+            breakers
+        },
+    };
+
+    Some(Ladder {
+        captured,
+        sequence,
+        breakers,
+        breaker_points: Vec::new(),
+    })
+}
+
+/// Tries the points next to the ladder path which are not next to the chain.
+fn breaker_points(board: &GoBoard, location: &Location, to_move: ChessType, ko: Option<Location>, sequence: &[Chess]) -> Vec<Location> {
+    let prey = board.get(location);
+    let chain = board.chain(location);
+
+    let mut candidates: Vec<Location> = Vec::new();
+    for chess in sequence.iter() {
+        for neighbor in board.neighbors(&chess.location) {
+            if board.get(&neighbor) != ChessType::None
+                || candidates.contains(&neighbor)
+                || sequence.iter().any(|chess| chess.location == neighbor)
+                || chain.iter().any(|stone| board.neighbors(stone).contains(&neighbor)) {
+                continue;
+            }
+
+            candidates.push(neighbor);
+        }
+    }
+
+    candidates.sort_by_key(|location| (location.alphabet, location.digit));
+
+    candidates.into_iter()
+        .filter(|candidate| {
+            let mut board = board.clone();
+
+            if board.make_move(prey, *candidate).is_err() {
+                return false;
+            }
+
+            match read(&board, location, to_move, ko) {
+                Some(ladder) => !ladder.captured,
+                None => true,
+            }
+        })
+        .collect()
+}
+
+impl Reader {
+    /// The attacker puts the prey with two liberties in atari.
+    fn attack(&mut self, board: &GoBoard, prey: &Location, ko: Option<Location>) -> (bool, Vec<Chess>) {
+        let mut escape: Option<Vec<Chess>> = None;
+
+        let mut liberties = board.liberties(prey);
+        liberties.sort_by_key(|location| (location.alphabet, location.digit));
+
+        for liberty in liberties {
+            if ko == Some(liberty) {
+                continue;
+            }
+
+            let mut board = board.clone();
+            let chess_change = match board.make_move(self.attacker, liberty) {
+                Ok(chess_change) => chess_change,
+                Err(_) => continue,
+            };
+
+            if board.liberties(prey).len() != 1 {
+                continue;
+            }
+
+            let ko = board.ko(&chess_change);
+            let (captured, line) = self.defend(&board, prey, ko);
+            let line = prepend(Chess { chess_type: self.attacker, location: liberty }, line);
+
+            if captured {
+                return (true, line);
+            }
+
+            /* The longest escape is the main line of a broken ladder */
+            escape = match escape {
+                Some(escape) if escape.len() >= line.len() => Some(escape),
+                _ => Some(line),
+            };
+        }
+
+        (false, escape.unwrap_or_default())
+    }
+
+    /// The prey in atari extends or captures an attacker chain in atari.
+    fn defend(&mut self, board: &GoBoard, prey: &Location, ko: Option<Location>) -> (bool, Vec<Chess>) {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES {
+            return (false, Vec::new());
+        }
+
+        let liberty = board.liberties(prey)[0];
+
+        let mut moves: Vec<Location> = Vec::new();
+        for stone in board.chain(prey) {
+            for neighbor in board.neighbors(&stone) {
+                if board.get(&neighbor) != self.attacker {
+                    continue;
+                }
+
+                if let [capture] = board.liberties(&neighbor).as_slice() {
+                    if !moves.contains(capture) {
+                        moves.push(*capture);
+                    }
+                }
+            }
+        }
+
+        if !moves.contains(&liberty) {
+            moves.push(liberty);
+        }
+
+        let mut captured_line: Option<Vec<Chess>> = None;
+
+        for mov in moves {
+            if ko == Some(mov) {
+                continue;
+            }
+
+            let mut board = board.clone();
+            let chess_change = match board.make_move(self.prey, mov) {
+                Ok(chess_change) => chess_change,
+                Err(_) => continue,
+            };
+
+            let chess = Chess { chess_type: self.prey, location: mov };
+            let liberties = board.liberties(prey);
+
+            match liberties.len() {
+                1 => {
+                    if captured_line.is_none() {
+                        captured_line = Some(vec![chess, Chess { chess_type: self.attacker, location: liberties[0] }]);
+                    }
+                },
+                2 => {
+                    let ko = board.ko(&chess_change);
+                    let (captured, line) = self.attack(&board, prey, ko);
+                    let line = prepend(chess, line);
+
+                    if !captured {
+                        return (false, line);
+                    }
+
+                    if captured_line.is_none() {
+                        captured_line = Some(line);
+                    }
+                },
+                _ => return (false, vec![chess]),
+            };
+        }
+
+        match captured_line {
+            Some(line) => (true, line),
+            None => (true, vec![Chess { chess_type: self.attacker, location: liberty }]),
+        }
+    }
+}
+
+fn prepend(chess: Chess, mut line: Vec<Chess>) -> Vec<Chess> {
+    line.insert(0, chess);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Diagram, GoGameEngine, Location};
+
+    fn engine(diagram: &str) -> GoGameEngine {
+        let diagram: Diagram = diagram.parse().unwrap();
+        GoGameEngine::from_diagram(&diagram, 6.5).unwrap()
+    }
+
+    #[test]
+    fn test_ladder_works() {
+        let engine = engine("
+            $$W
+            $$ +-------------------+
+            $$ | . . . . . . . . . |
+            $$ | . X . . . . . . . |
+            $$ | X O X . . . . . . |
+            $$ | X . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ +-------------------+
+        ");
+
+        let ladder = engine.ladder(Location { alphabet: 1, digit: 6 }).unwrap();
+
+        assert!(ladder.captured);
+        assert!(ladder.sequence.len() > 10);
+        assert!(ladder.breakers.is_empty());
+        assert!(ladder.breaker_points.contains(&Location { alphabet: 6, digit: 3 }));
+    }
+
+    #[test]
+    fn test_ladder_breaker() {
+        let engine = engine("
+            $$W
+            $$ +-------------------+
+            $$ | . . . . . . . . . |
+            $$ | . X . . . . . . . |
+            $$ | X O X . . . . . . |
+            $$ | X . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . O . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ +-------------------+
+        ");
+
+        let ladder = engine.ladder(Location { alphabet: 1, digit: 6 }).unwrap();
+
+        assert!(!ladder.captured);
+        assert_eq!(ladder.breakers, vec![Location { alphabet: 6, digit: 3 }]);
+        assert!(ladder.breaker_points.is_empty());
+    }
+
+    #[test]
+    fn test_no_ladder() {
+        let engine = engine("
+            $$W
+            $$ +-----------+
+            $$ | . . . . . |
+            $$ | . X . . . |
+            $$ | X O . . . |
+            $$ | . . . . . |
+            $$ | . . . . . |
+            $$ +-----------+
+        ");
+
+        assert!(engine.ladder(Location { alphabet: 1, digit: 2 }).is_none());
+        assert!(engine.ladder(Location { alphabet: 3, digit: 3 }).is_none());
+    }
+}
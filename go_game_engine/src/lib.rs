@@ -1,14 +1,28 @@
 mod tree;
 mod board;
-
-pub use crate::board::go_board::{GoBoard, ChessChange, MoveError, ChessType};
+mod diagram;
+mod clock;
+mod ladder;
+mod tsumego;
+mod influence;
+mod sgf;
+mod handicap;
+
+pub use crate::board::go_board::{GoBoard, Chess, ChessChange, MoveError, ChessType};
+pub use crate::diagram::{Diagram, DiagramPoint, DiagramError, Markup};
+pub use crate::clock::{Clock, ClockError, TimeSystem, TimeLeft, TimeSource, SystemTimeSource, ManualTimeSource};
+pub use crate::ladder::{Ladder, read_ladder};
+pub use crate::influence::{Influence, estimate};
+pub use crate::sgf::SgfError;
+pub use crate::handicap::fixed_handicap;
+pub use crate::tsumego::{Tsumego, TsumegoError, TsumegoMove, TsumegoSolution, TsumegoStatus, Variation};
 use crate::board::scoring_board::ScoreBoard;
-use crate::tree::{Tree};
+use crate::tree::{Tree, Visit};
 
 pub const BOARD_SIZE_MAX: usize = 19;
 pub const PLAYER_NUM: usize = 2;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Player {
     Black = 0,
     White = 1,
@@ -23,7 +37,7 @@ impl Player {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Location {
     pub alphabet: u8,
     pub digit: u8,
@@ -35,11 +49,136 @@ pub enum GameStatus {
     Scoring,
 }
 
+/// The game result, the player is the winner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameResult {
+    Score(Player, f64),
+    Resign(Player),
+    Time(Player),
+    Forfeit(Player),
+    Draw,
+    Void,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Score(player, _) => Some(*player),
+            GameResult::Resign(player) => Some(*player),
+            GameResult::Time(player) => Some(*player),
+            GameResult::Forfeit(player) => Some(*player),
+            GameResult::Draw | GameResult::Void => None,
+        }
+    }
+}
+
+/// The SGF RE property, e.g. `B+3.5`, `W+R`, `B+T`.
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let winner = |player: &Player| match player {
+            Player::Black => 'B',
+            Player::White => 'W',
+        };
+
+        match self {
+            GameResult::Score(player, score) => write!(f, "{}+{}", winner(player), score),
+            GameResult::Resign(player) => write!(f, "{}+R", winner(player)),
+            GameResult::Time(player) => write!(f, "{}+T", winner(player)),
+            GameResult::Forfeit(player) => write!(f, "{}+F", winner(player)),
+            GameResult::Draw => write!(f, "0"),
+            GameResult::Void => write!(f, "Void"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseGameResultError {
+}
+
+impl std::str::FromStr for GameResult {
+    type Err = ParseGameResultError;
+
+    fn from_str(result: &str) -> Result<GameResult, ParseGameResultError> {
+        let result = result.trim();
+
+        match result {
+            "0" | "Draw" | "Jigo" => return Ok(GameResult::Draw),
+            "Void" | "?" => return Ok(GameResult::Void),
+            _ => {},
+        };
+
+        let mut parts = result.splitn(2, '+');
+
+        let player = match parts.next() {
+            Some("B") | Some("b") => Player::Black,
+            Some("W") | Some("w") => Player::White,
+            _ => return Err(ParseGameResultError {}),
+        };
+
+        match parts.next() {
+            Some("R") | Some("Resign") => Ok(GameResult::Resign(player)),
+            Some("T") | Some("Time") => Ok(GameResult::Time(player)),
+            Some("F") | Some("Forfeit") => Ok(GameResult::Forfeit(player)),
+            Some(score) => match score.parse::<f64>() {
+                Ok(score) => Ok(GameResult::Score(player, score)),
+                Err(_) => Err(ParseGameResultError {}),
+            },
+            None => Err(ParseGameResultError {}),
+        }
+    }
+}
+
 pub struct GoNode {
     changes: Option<ChessChange>,
     steps: i32,
     capture: [i32; PLAYER_NUM],
     player: Option<Player>,
+    /// The move number given by the SGF MN property.
+    move_number: Option<i32>,
+    /// The time left of the player after the move.
+    time_left: Option<TimeLeft>,
+    comment: Option<String>,
+    annotation: Option<Annotation>,
+    /// The evaluation of the position, the SGF V property.
+    value: Option<f64>,
+}
+
+/// The SGF move annotation properties.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Annotation {
+    Tesuji,
+    Bad,
+    VeryBad,
+    Doubtful,
+    Interesting,
+}
+
+impl Annotation {
+    fn to_sgf(self) -> &'static str {
+        match self {
+            Annotation::Tesuji => "TE[1]",
+            Annotation::Bad => "BM[1]",
+            Annotation::VeryBad => "BM[2]",
+            Annotation::Doubtful => "DO[]",
+            Annotation::Interesting => "IT[]",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Estimate {
+    pub influence: Influence,
+    /// The score counted as `get_score`, (black, white).
+    pub score: (f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveRecord {
+    pub number: i32,
+    pub player: Player,
+    /// None for the pass.
+    pub location: Option<Location>,
+    pub captures: Vec<Location>,
 }
 
 pub struct GoGameEngine {
@@ -49,15 +188,32 @@ pub struct GoGameEngine {
     status: GameStatus,
     score_board: Option<ScoreBoard>,
     playAs: Option<Player>,
+    setup: Vec<Chess>,
+    first_player: Player,
+    time_system: Option<TimeSystem>,
+    result: Option<GameResult>,
+    player_names: [Option<String>; PLAYER_NUM],
 }
 
 impl GoGameEngine {
     pub fn new(size: u8, komi: f64) -> GoGameEngine {
+        GoGameEngine::with_first_player(size, komi, Player::Black)
+    }
+
+    fn with_first_player(size: u8, komi: f64, first_player: Player) -> GoGameEngine {
         let root_node = GoNode {
             changes: None,
             steps: 0,
             capture: [0; PLAYER_NUM],
-            player: None,
+            player: match first_player {
+                Player::Black => None,
+                Player::White => Some(Player::Black),
+            },
+            move_number: None,
+            time_left: None,
+            comment: None,
+            annotation: None,
+            value: None,
         };
         GoGameEngine {
             tree: Tree::new(root_node),
@@ -66,7 +222,263 @@ impl GoGameEngine {
             status: GameStatus::Playing,
             score_board: None,
             playAs: None,
+            setup: Vec::new(),
+            first_player,
+            time_system: None,
+            result: None,
+            player_names: [None, None],
+        }
+    }
+
+    /// Builds the position of the diagram, the stones are set up and the numbered moves are played in order.
+    pub fn from_diagram(diagram: &Diagram, komi: f64) -> Result<GoGameEngine, DiagramError> {
+        let mut engine = GoGameEngine::with_first_player(diagram.size(), komi, diagram.first_player);
+        let mut numbered: Vec<(u8, Location)> = Vec::new();
+
+        for alphabet in 0..diagram.size() {
+            for digit in 0..diagram.size() {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+                let point = diagram.get(location);
+
+                if let Markup::Number(number) = point.markup {
+                    numbered.push((number, location));
+                    continue;
+                }
+
+                if point.stone != ChessType::None {
+                    engine.board.set(&location, point.stone);
+                    engine.setup.push(Chess {
+                        chess_type: point.stone,
+                        location,
+                    });
+                }
+            }
+        }
+
+        numbered.sort_by_key(|(number, _)| *number);
+
+        let mut is_first = true;
+        for (number, location) in numbered {
+            if engine.player() != diagram.number_player(number) {
+                engine.pass();
+            }
+
+            if engine.make_move(location).is_err() {
+                return Err(DiagramError::IllegalMove(number));
+            }
+
+            if is_first && diagram.first_number != 1 {
+                engine.set_move_number(diagram.first_number + number as i32 - 1);
+            }
+            is_first = false;
+        }
+
+        Ok(engine)
+    }
+
+    /// Places the black handicap stones before the first move, white moves first.
+    pub fn set_handicap(&mut self, locations: &[Location]) -> Result<(), MoveError> {
+        if self.steps() > 0 || !self.setup.is_empty() {
+            return Err(MoveError::NoMove);
+        }
+
+        for (index, location) in locations.iter().enumerate() {
+            if self.board.get(location) != ChessType::None || locations[..index].contains(location) {
+                return Err(MoveError::Exist(*location));
+            }
+        }
+
+        for location in locations.iter() {
+            self.board.set(location, ChessType::Black);
+            self.setup.push(Chess {
+                chess_type: ChessType::Black,
+                location: *location,
+            });
+        }
+
+        self.first_player = Player::White;
+        self.tree.access_head_mut(|head| {
+            head.player = Some(Player::Black);
+        });
+
+        Ok(())
+    }
+
+    /// The diagram of the current position, the last `numbered` moves (10 at most) are numbered
+    /// as long as their stones are still on the board.
+    pub fn to_diagram(&self, numbered: usize) -> Diagram {
+        let moves = self.moves();
+
+        let mut used: Vec<Location> = Vec::new();
+        for record in moves.iter().rev() {
+            if used.len() >= std::cmp::min(numbered, 10) {
+                break;
+            }
+
+            match record.location {
+                Some(location) if !used.contains(&location)
+                    && self.board.get(&location) == player_chess_type(record.player) => {
+                    used.push(location);
+                },
+                _ => break,
+            };
+        }
+
+        let start = moves.len() - used.len();
+        let mut diagram = Diagram::new(self.size());
+
+        match moves.get(start) {
+            Some(record) => {
+                diagram.first_number = record.number;
+                diagram.first_player = record.player;
+            },
+            None => {
+                diagram.first_number = moves.last().map_or(1, |record| record.number + 1);
+                diagram.first_player = self.player();
+            },
+        };
+
+        for alphabet in 0..self.size() {
+            for digit in 0..self.size() {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+
+                diagram.set(location, DiagramPoint {
+                    stone: self.board.get(&location),
+                    markup: Markup::None,
+                });
+            }
         }
+
+        for (idx, location) in used.iter().rev().enumerate() {
+            diagram.set_markup(*location, Markup::Number(idx as u8 + 1));
+        }
+
+        diagram
+    }
+
+    /// The moves from the root to the head.
+    pub fn moves(&self) -> Vec<MoveRecord> {
+        let mut moves: Vec<MoveRecord> = Vec::new();
+        let mut number = 0;
+        let mut is_root = true;
+
+        self.tree.path(|node| {
+            if is_root {
+                number = node.move_number.unwrap_or(0);
+                is_root = false;
+                return;
+            }
+
+            number = match node.move_number {
+                Some(move_number) => move_number,
+                None => number + 1,
+            };
+
+            moves.push(MoveRecord {
+                number,
+                player: node.player.unwrap(),
+                location: node.changes.as_ref().map(|chess_change| chess_change.at.location),
+                captures: match &node.changes {
+                    Some(chess_change) => chess_change.remove.clone(),
+                    None => Vec::new(),
+                },
+            });
+        });
+
+        moves
+    }
+
+    pub fn last_move(&self) -> Option<MoveRecord> {
+        self.moves().pop()
+    }
+
+    /// The stones set up before the first move, such as the handicap stones.
+    pub fn setup(&self) -> &[Chess] {
+        &self.setup
+    }
+
+    /// The player of the first move, white after the handicap stones.
+    pub fn first_player(&self) -> Player {
+        self.first_player
+    }
+
+    /// The move number that placed each stone on the board, indexed by `[alphabet][digit]`.
+    /// The setup stones have no move number.
+    pub fn move_numbers(&self) -> Vec<Vec<Option<i32>>> {
+        let mut move_numbers = vec![vec![None; self.size() as usize]; self.size() as usize];
+
+        for record in self.moves() {
+            if let Some(location) = record.location {
+                move_numbers[location.alphabet as usize][location.digit as usize] = Some(record.number);
+            }
+
+            for location in record.captures.iter() {
+                move_numbers[location.alphabet as usize][location.digit as usize] = None;
+            }
+        }
+
+        move_numbers
+    }
+
+    pub fn set_time_system(&mut self, time_system: TimeSystem) {
+        self.time_system = Some(time_system);
+    }
+
+    pub fn time_system(&self) -> Option<TimeSystem> {
+        self.time_system
+    }
+
+    /// Records the time left of the player who made the head move.
+    pub fn record_time_left(&mut self, time_left: TimeLeft) {
+        self.tree.access_head_mut(|head| {
+            head.time_left = Some(time_left);
+        });
+    }
+
+    /// The time left recorded on the head move.
+    pub fn time_left(&self) -> Option<TimeLeft> {
+        let mut time_left: Option<TimeLeft> = None;
+
+        self.tree.access_head(|head| {
+            time_left = head.time_left;
+        });
+
+        time_left
+    }
+
+    pub fn set_result(&mut self, result: GameResult) {
+        self.result = Some(result);
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// The name of the player, the SGF PB or PW property.
+    pub fn set_player_name(&mut self, player: Player, name: &str) {
+        self.player_names[player as usize] = Some(name.to_string());
+    }
+
+    pub fn player_name(&self, player: Player) -> Option<&str> {
+        self.player_names[player as usize].as_deref()
+    }
+
+    /// The player runs out of time and loses the game.
+    pub fn flag(&mut self, player: Player) {
+        self.set_result(GameResult::Time(player.switch()));
+    }
+
+    /// Sets the move number of the head as the SGF MN property, the following moves are numbered from it.
+    pub fn set_move_number(&mut self, number: i32) {
+        self.tree.access_head_mut(|head| {
+            head.move_number = Some(number);
+        });
     }
 
     pub fn size(&self) -> u8 {
@@ -77,6 +489,75 @@ impl GoGameEngine {
         return self.komi;
     }
 
+    pub fn set_komi(&mut self, komi: f64) {
+        self.komi = komi;
+    }
+
+    pub fn board(&self) -> &GoBoard {
+        &self.board
+    }
+
+    /// The point forbidden by the ko rule for the player to move.
+    pub fn ko(&self) -> Option<Location> {
+        let mut ko: Option<Location> = None;
+
+        self.tree.access_head(|head| {
+            if let Some(chess_change) = &head.changes {
+                ko = self.board.ko(chess_change);
+            }
+        });
+
+        ko
+    }
+
+    pub fn is_legal(&self, location: Location) -> bool {
+        self.status == GameStatus::Playing
+            && self.ko() != Some(location)
+            && self.board.is_legal(player_chess_type(self.player()), &location)
+    }
+
+    /// Whether the player may play at the location, a pass of the other player is implied if it is not the turn of the player.
+    pub fn is_legal_for(&self, player: Player, location: Location) -> bool {
+        match player == self.player() {
+            true => self.is_legal(location),
+            /* The implied pass clears the ko, but it must not be the second pass in a row */
+            false => self.status == GameStatus::Playing
+                && !self.is_head_pass()
+                && self.board.is_legal(player_chess_type(player), &location),
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<Location> {
+        let mut moves: Vec<Location> = Vec::new();
+
+        for alphabet in 0..self.size() {
+            for digit in 0..self.size() {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+
+                if self.is_legal(location) {
+                    moves.push(location);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Reads the ladder of the chain at the location with the player to move.
+    pub fn ladder(&self, location: Location) -> Option<Ladder> {
+        read_ladder(&self.board, &location, player_chess_type(self.player()), self.ko())
+    }
+
+    /// The life-and-death problem in the region with the player to move.
+    pub fn tsumego(&self, region: Vec<Location>, attacker: Player) -> Tsumego {
+        let mut tsumego = Tsumego::new(&self.board, region, player_chess_type(attacker), player_chess_type(self.player()));
+        tsumego.set_ko(self.ko());
+        tsumego
+    }
+
     pub fn setPlayAs(&mut self, player: Player) {
         self.playAs = Some(player);
     }
@@ -106,6 +587,10 @@ impl GoGameEngine {
             };
         });
 
+        if self.ko() == Some(location) {
+            return Err(MoveError::Ko(location));
+        }
+
         match self.board.make_move(chess_type, location) {
             Ok(chess_change) => {
                 self.tree.grow(|head| {
@@ -117,6 +602,11 @@ impl GoGameEngine {
                             None => Some(Player::Black),
                             Some(player) => Some(player.switch()),
                         },
+                        move_number: None,
+                        time_left: None,
+                        comment: None,
+                        annotation: None,
+                        value: None,
                     };
 
                     node.capture[node.player.unwrap() as usize] += node.changes.as_ref().unwrap().remove.len() as i32;
@@ -151,23 +641,28 @@ impl GoGameEngine {
             _ => return,
         };
 
-        let mut status: Option<GameStatus> = None;
+        if self.is_head_pass() {
+            self.status = GameStatus::Scoring;
+            self.score_board = Some(ScoreBoard::new(&self.board));
+            self.score_board.as_mut().unwrap().refresh_belong(&self.board);
+            return;
+        }
+
+        self.grow_pass();
+    }
+
+    /// Whether the last move is a pass.
+    pub fn is_head_pass(&self) -> bool {
+        let mut is_pass = false;
         self.tree.access_head(|head| {
-            if let None = head.changes {
-                status = Some(GameStatus::Scoring);
-            }
+            /* The root has no changes but is not a pass */
+            is_pass = head.steps > 0 && head.changes.is_none();
         });
 
-        match status {
-            None => {},
-            Some(game_status) => {
-                self.status = game_status;
-                self.score_board = Some(ScoreBoard::new(&self.board));
-                self.score_board.as_mut().unwrap().refresh_belong(&self.board);
-                return;
-            }
-        };
+        is_pass
+    }
 
+    fn grow_pass(&mut self) {
         self.tree.grow(|head_data| {
             GoNode {
                 changes: None,
@@ -176,7 +671,12 @@ impl GoGameEngine {
                 player: match head_data.player {
                     None => Some(Player::Black),
                     Some(player) => Some(player.switch()),
-                }
+                },
+                move_number: None,
+                time_left: None,
+                comment: None,
+                annotation: None,
+                value: None,
             }
         });
     }
@@ -231,6 +731,103 @@ impl GoGameEngine {
         }
     }
 
+    /// Moves back to the previous node, the moves after it are kept as the variations.
+    pub fn back(&mut self) -> bool {
+        let mut chess_change: Option<ChessChange> = None;
+
+        self.tree.access_head(|head| {
+            chess_change = head.changes.clone();
+        });
+
+        if !self.tree.up() {
+            return false;
+        }
+
+        self.status = GameStatus::Playing;
+        self.score_board = None;
+
+        if let Some(chess_change) = chess_change {
+            self.board.reverse_change(&chess_change);
+        }
+
+        true
+    }
+
+    /// Moves forward to the variation of the current node, 0 is the main line.
+    pub fn forward(&mut self, variation: usize) -> bool {
+        if !self.tree.down(variation) {
+            return false;
+        }
+
+        self.status = GameStatus::Playing;
+        self.score_board = None;
+
+        let mut chess_change: Option<ChessChange> = None;
+
+        self.tree.access_head(|head| {
+            chess_change = head.changes.clone();
+        });
+
+        if let Some(chess_change) = chess_change {
+            self.board.apply_change(&chess_change);
+        }
+
+        true
+    }
+
+    /// The number of the moves after the current node.
+    pub fn variations(&self) -> usize {
+        self.tree.children_count()
+    }
+
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.tree.access_head_mut(|head| {
+            head.comment = comment;
+        });
+    }
+
+    pub fn comment(&self) -> Option<String> {
+        let mut comment: Option<String> = None;
+
+        self.tree.access_head(|head| {
+            comment = head.comment.clone();
+        });
+
+        comment
+    }
+
+    pub fn set_annotation(&mut self, annotation: Option<Annotation>) {
+        self.tree.access_head_mut(|head| {
+            head.annotation = annotation;
+        });
+    }
+
+    pub fn annotation(&self) -> Option<Annotation> {
+        let mut annotation: Option<Annotation> = None;
+
+        self.tree.access_head(|head| {
+            annotation = head.annotation;
+        });
+
+        annotation
+    }
+
+    pub fn set_value(&mut self, value: Option<f64>) {
+        self.tree.access_head_mut(|head| {
+            head.value = value;
+        });
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        let mut value: Option<f64> = None;
+
+        self.tree.access_head(|head| {
+            value = head.value;
+        });
+
+        value
+    }
+
     pub fn get_status(&self) -> GameStatus {
         return self.status;
     }
@@ -293,36 +890,146 @@ impl GoGameEngine {
         return score;
     }
 
+    /// Estimates the score by the influence, available during the play.
+    pub fn estimate_score(&self) -> Estimate {
+        let influence = estimate(&self.board);
+
+        let mut score: (f64, f64) = (
+            (influence.territory(&self.board, Player::Black) - influence.dead_stones(&self.board, Player::Black)) as f64,
+            (influence.territory(&self.board, Player::White) - influence.dead_stones(&self.board, Player::White)) as f64,
+        );
+
+        {
+            let mut capture = [0; PLAYER_NUM];
+            self.tree.access_head(|head| {
+                capture = head.capture;
+            });
+
+            score.0 -= capture[Player::White as usize] as f64;
+            score.1 -= capture[Player::Black as usize] as f64;
+        }
+
+        score.1 += self.komi;
+
+        Estimate {
+            influence,
+            score,
+        }
+    }
+
     pub fn to_sgf(&self) -> String {
         let mut sgf = "".to_string();
 
         sgf.push_str("(;");
         sgf.push_str("GM[1]FF[4]CA[UTF-8]AP[TGoBan:0.0.1]RU[Japanese]");
-        sgf.push_str(&format!("KM[{}]", self.komi));
+        sgf.push_str(&format!("SZ[{}]KM[{}]", self.size(), self.komi));
+
+        if let Some(time_system) = self.time_system {
+            sgf.push_str(&format!("TM[{}]", time_system.main().as_secs_f64()));
+
+            if let Some(overtime) = time_system.overtime() {
+                sgf.push_str(&format!("OT[{}]", overtime));
+            }
+        }
+
+        if let Some(result) = self.result {
+            sgf.push_str(&format!("RE[{}]", result));
+        }
+
+        for (property, player) in [("PB", Player::Black), ("PW", Player::White)].iter() {
+            if let Some(name) = self.player_name(*player) {
+                sgf.push_str(&format!("{}[{}]", property, name.replace('\\', "\\\\").replace(']', "\\]")));
+            }
+        }
 
         let size = self.size();
+        let sgf_point = |location: &Location| {
+            format!("{}{}",
+                (location.alphabet + b'a') as char,
+                (size - 1 - location.digit + b'a') as char
+            )
+        };
+
+        for (property, chess_type) in [("AB", ChessType::Black), ("AW", ChessType::White)].iter() {
+            let points: Vec<String> = self.setup.iter()
+                .filter(|chess| chess.chess_type == *chess_type)
+                .map(|chess| format!("[{}]", sgf_point(&chess.location)))
+                .collect();
+
+            if points.len() > 0 {
+                sgf.push_str(&format!("{}{}", property, points.concat()));
+            }
+        }
+
+        if self.setup.len() > 0 || self.first_player != Player::Black {
+            sgf.push_str(match self.first_player {
+                Player::Black => "PL[B]",
+                Player::White => "PL[W]",
+            });
+        }
         let mut is_root = true;
 
-        self.tree.preorder(|data: &GoNode| {
+        self.tree.visit(|visit| {
+            let data = match visit {
+                Visit::Begin => {
+                    sgf.push('(');
+                    return;
+                },
+                Visit::End => {
+                    sgf.push(')');
+                    return;
+                },
+                Visit::Node(data) => data,
+            };
+
             if !is_root {
                 sgf.push_str(
-                    &format!(";{}[{}]\n",
+                    &format!(";{}[{}]{}{}",
                         match data.player.unwrap() {
                             Player::Black => 'B',
                             Player::White => 'W',
                         },
                         match &data.changes {
                             None => "".to_string(),
-                            Some(chess_change) => {
-                                format!("{}{}",
-                                    (chess_change.at.location.alphabet as u8 + 'a' as u8) as char,
-                                    ((size - 1 - chess_change.at.location.digit) as u8 + 'a' as u8) as char
-                                )
-                            }
+                            Some(chess_change) => sgf_point(&chess_change.at.location),
+                        },
+                        match data.move_number {
+                            None => "".to_string(),
+                            Some(move_number) => format!("MN[{}]", move_number),
+                        },
+                        match data.time_left {
+                            None => "".to_string(),
+                            Some(time_left) => {
+                                let player = match data.player.unwrap() {
+                                    Player::Black => 'B',
+                                    Player::White => 'W',
+                                };
+
+                                match time_left.periods {
+                                    None => format!("{}L[{}]", player, time_left.seconds),
+                                    Some(periods) => format!("{}L[{}]O{}[{}]", player, time_left.seconds, player, periods),
+                                }
+                            },
                         },
                     )
                 );
             }
+
+            if let Some(annotation) = data.annotation {
+                sgf.push_str(annotation.to_sgf());
+            }
+
+            if let Some(value) = data.value {
+                sgf.push_str(&format!("V[{}]", value));
+            }
+
+            if let Some(comment) = &data.comment {
+                sgf.push_str(&format!("C[{}]", comment.replace('\\', "\\\\").replace(']', "\\]")));
+            }
+
+            if !is_root {
+                sgf.push('\n');
+            }
             is_root = false;
         });
 
@@ -332,6 +1039,13 @@ impl GoGameEngine {
     }
 }
 
+fn player_chess_type(player: Player) -> ChessType {
+    match player {
+        Player::Black => ChessType::Black,
+        Player::White => ChessType::White,
+    }
+}
+
 impl std::fmt::Display for GoGameEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut res: std::fmt::Result = Ok(());
@@ -417,3 +1131,39 @@ impl std::str::FromStr for Location {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(engine: &mut GoGameEngine, moves: &[(u8, u8)]) {
+        for (alphabet, digit) in moves.iter() {
+            assert!(engine.make_move(Location { alphabet: *alphabet, digit: *digit }).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ko() {
+        let mut engine = GoGameEngine::new(9, 6.5);
+        let ko = Location { alphabet: 4, digit: 4 };
+
+        /* Black takes the white stone at E5 by F5 */
+        play(&mut engine, &[(4, 3), (5, 3), (3, 4), (6, 4), (4, 5), (5, 5), (0, 0), (4, 4), (5, 4)]);
+        assert_eq!(engine.get_chess(ko), ChessType::None);
+        assert_eq!(engine.ko(), Some(ko));
+        assert!(!engine.is_legal(ko));
+        assert!(!engine.legal_moves().contains(&ko));
+
+        match engine.make_move(ko) {
+            Err(MoveError::Ko(location)) => assert_eq!(location, ko),
+            _ => panic!("The ko is retaken at once"),
+        };
+        assert_eq!(engine.steps(), 9);
+
+        /* White may retake after a move elsewhere */
+        play(&mut engine, &[(8, 8), (0, 8)]);
+        assert_eq!(engine.ko(), None);
+        play(&mut engine, &[(4, 4)]);
+        assert_eq!(engine.get_chess(Location { alphabet: 5, digit: 4 }), ChessType::None);
+    }
+}
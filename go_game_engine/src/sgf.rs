@@ -0,0 +1,505 @@
+use crate::{GameResult, GoGameEngine, Location, Player, TimeLeft, Annotation, BOARD_SIZE_MAX};
+use crate::board::go_board::{Chess, ChessType};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SgfError {
+    /// The syntax error at the byte offset.
+    Syntax(usize),
+    InvalidValue(String),
+    /// The property is valid SGF but not supported by the engine.
+    Unsupported(String),
+    /// The illegal move at the node, counted from the root as 0.
+    IllegalMove(usize),
+}
+
+impl std::fmt::Display for SgfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SgfError::Syntax(offset) => write!(f, "Syntax error at {}", offset),
+            SgfError::InvalidValue(property) => write!(f, "Invalid value of {}", property),
+            SgfError::Unsupported(property) => write!(f, "Unsupported property {}", property),
+            SgfError::IllegalMove(node) => write!(f, "Illegal move at node {}", node),
+        }
+    }
+}
+
+const SETUP_PROPERTIES: [&str; 3] = ["AB", "AW", "AE"];
+
+struct SgfNode {
+    properties: Vec<(String, Vec<String>)>,
+}
+
+impl SgfNode {
+    fn get(&self, identifier: &str) -> Option<&Vec<String>> {
+        self.properties.iter()
+            .find(|(property, _)| property == identifier)
+            .map(|(_, values)| values)
+    }
+
+    fn first(&self, identifier: &str) -> Option<&str> {
+        self.get(identifier).and_then(|values| values.first()).map(|value| value.as_str())
+    }
+}
+
+struct SgfTree {
+    nodes: Vec<SgfNode>,
+    variations: Vec<SgfTree>,
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.offset < self.text.len() && self.text[self.offset].is_ascii_whitespace() {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.offset).cloned()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), SgfError> {
+        match self.peek() {
+            Some(next) if next == byte => {
+                self.offset += 1;
+                Ok(())
+            },
+            _ => Err(SgfError::Syntax(self.offset)),
+        }
+    }
+
+    fn tree(&mut self) -> Result<SgfTree, SgfError> {
+        self.expect(b'(')?;
+
+        let mut tree = SgfTree {
+            nodes: Vec::new(),
+            variations: Vec::new(),
+        };
+
+        while self.peek() == Some(b';') {
+            self.offset += 1;
+            tree.nodes.push(self.node()?);
+        }
+
+        if tree.nodes.is_empty() {
+            return Err(SgfError::Syntax(self.offset));
+        }
+
+        while self.peek() == Some(b'(') {
+            tree.variations.push(self.tree()?);
+        }
+
+        self.expect(b')')?;
+
+        Ok(tree)
+    }
+
+    fn node(&mut self) -> Result<SgfNode, SgfError> {
+        let mut node = SgfNode {
+            properties: Vec::new(),
+        };
+
+        while let Some(byte) = self.peek() {
+            if !byte.is_ascii_alphabetic() {
+                break;
+            }
+
+            let start = self.offset;
+            while self.offset < self.text.len() && self.text[self.offset].is_ascii_alphabetic() {
+                self.offset += 1;
+            }
+
+            /* The lower case letters are allowed in the old FF[3] identifiers */
+            let identifier: String = String::from_utf8_lossy(&self.text[start..self.offset])
+                .chars()
+                .filter(|c| c.is_ascii_uppercase())
+                .collect();
+
+            let mut values: Vec<String> = Vec::new();
+            while self.peek() == Some(b'[') {
+                values.push(self.value()?);
+            }
+
+            if values.is_empty() {
+                return Err(SgfError::Syntax(self.offset));
+            }
+
+            node.properties.push((identifier, values));
+        }
+
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<String, SgfError> {
+        self.expect(b'[')?;
+
+        let mut value: Vec<u8> = Vec::new();
+
+        loop {
+            match self.text.get(self.offset) {
+                None => return Err(SgfError::Syntax(self.offset)),
+                Some(b']') => {
+                    self.offset += 1;
+                    break;
+                },
+                Some(b'\\') => {
+                    self.offset += 1;
+
+                    match self.text.get(self.offset) {
+                        None => return Err(SgfError::Syntax(self.offset)),
+                        /* The soft line break is removed */
+                        Some(b'\n') => {},
+                        Some(byte) => value.push(*byte),
+                    };
+                    self.offset += 1;
+                },
+                Some(byte) => {
+                    value.push(*byte);
+                    self.offset += 1;
+                },
+            };
+        }
+
+        Ok(String::from_utf8_lossy(&value).into_owned())
+    }
+}
+
+impl GoGameEngine {
+    /// Loads the first game of the SGF collection with its variations, the head is at the end of the main line.
+    pub fn from_sgf(sgf: &str) -> Result<GoGameEngine, SgfError> {
+        GoGameEngine::from_sgf_with_warnings(sgf).map(|(engine, _)| engine)
+    }
+
+    /// Loads the game as `from_sgf`, with the warnings of the skipped properties.
+    ///
+    /// The setup properties before the first move of the main line are a part of the setup, the
+    /// ones after it are skipped since the moves cannot be mixed with the setup.
+    pub fn from_sgf_with_warnings(sgf: &str) -> Result<(GoGameEngine, Vec<String>), SgfError> {
+        let mut parser = Parser {
+            text: sgf.as_bytes(),
+            offset: 0,
+        };
+
+        let tree = parser.tree()?;
+        let root = &tree.nodes[0];
+
+        let size = match root.first("SZ") {
+            None => 19,
+            Some(size) => match size.parse::<u8>() {
+                Ok(size) if size >= 1 && size as usize <= BOARD_SIZE_MAX => size,
+                _ => return Err(SgfError::InvalidValue("SZ".to_string())),
+            },
+        };
+
+        let komi = match root.first("KM") {
+            None => 0.0,
+            Some(komi) => match komi.trim().parse::<f64>() {
+                Ok(komi) => komi,
+                Err(_) => return Err(SgfError::InvalidValue("KM".to_string())),
+            },
+        };
+
+        let first_player = match root.first("PL") {
+            None => first_move_player(&tree).unwrap_or(Player::Black),
+            Some(player) => parse_player(player).ok_or_else(|| SgfError::InvalidValue("PL".to_string()))?,
+        };
+
+        let mut engine = GoGameEngine::with_first_player(size, komi, first_player);
+
+        setup(&mut engine, root)?;
+
+        if let Some(result) = root.first("RE") {
+            if let Ok(result) = result.parse::<GameResult>() {
+                engine.result = Some(result);
+            }
+        }
+
+        for (property, player) in [("PB", Player::Black), ("PW", Player::White)].iter() {
+            if let Some(name) = root.first(property) {
+                engine.set_player_name(*player, name);
+            }
+        }
+
+        let mut index = 0;
+        let mut warnings: Vec<String> = Vec::new();
+        build(&mut engine, &tree, &mut index, true, &mut warnings)?;
+
+        while engine.forward(0) {}
+
+        Ok((engine, warnings))
+    }
+}
+
+/// Plays the nodes and the variations of the tree, the head is moved back to where it starts.
+fn build(engine: &mut GoGameEngine, tree: &SgfTree, index: &mut usize, is_root: bool, warnings: &mut Vec<String>) -> Result<(), SgfError> {
+    let mut grown = 0;
+
+    for (idx, node) in tree.nodes.iter().enumerate() {
+        let has_setup = SETUP_PROPERTIES.iter().any(|property| node.get(property).is_some());
+
+        if has_setup && !(is_root && idx == 0) {
+            match is_root && engine.steps() == 0 {
+                true => setup(engine, node)?,
+                false => warnings.push(format!("Setup at node {} is skipped", *index)),
+            };
+        }
+
+        grown += play_node(engine, node, *index)?;
+        *index += 1;
+    }
+
+    for variation in tree.variations.iter() {
+        build(engine, variation, index, false, warnings)?;
+    }
+
+    for _ in 0..grown {
+        engine.back();
+    }
+
+    Ok(())
+}
+
+/// Adds and removes the setup stones of the node.
+fn setup(engine: &mut GoGameEngine, node: &SgfNode) -> Result<(), SgfError> {
+    let size = engine.size();
+
+    for property in SETUP_PROPERTIES.iter() {
+        let chess_type = match *property {
+            "AB" => ChessType::Black,
+            "AW" => ChessType::White,
+            _ => ChessType::None,
+        };
+
+        for value in node.get(property).into_iter().flatten() {
+            for location in parse_points(value, size).ok_or_else(|| SgfError::InvalidValue(property.to_string()))? {
+                engine.board.set(&location, chess_type);
+                engine.setup.retain(|chess| chess.location != location);
+
+                if chess_type != ChessType::None {
+                    engine.setup.push(Chess {
+                        chess_type,
+                        location,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Plays the move of the node and sets its properties, returns the number of the grown nodes.
+fn play_node(engine: &mut GoGameEngine, node: &SgfNode, index: usize) -> Result<usize, SgfError> {
+    let size = engine.size();
+    let mut grown = 0;
+
+    let mov = match (node.first("B"), node.first("W")) {
+        (Some(point), None) => Some((Player::Black, point)),
+        (None, Some(point)) => Some((Player::White, point)),
+        (None, None) => None,
+        (Some(_), Some(_)) => return Err(SgfError::IllegalMove(index)),
+    };
+
+    if let Some((player, point)) = mov {
+        if engine.player() != player {
+            engine.grow_pass();
+            grown += 1;
+        }
+
+        let is_pass = point.is_empty() || (point == "tt" && size <= 19);
+
+        match is_pass {
+            true => engine.grow_pass(),
+            false => {
+                let location = match parse_points(point, size).as_deref() {
+                    Some([location]) => *location,
+                    _ => return Err(SgfError::InvalidValue(player_property(player).to_string())),
+                };
+
+                if engine.make_move(location).is_err() {
+                    return Err(SgfError::IllegalMove(index));
+                }
+            },
+        };
+        grown += 1;
+    }
+
+    if let Some(number) = node.first("MN") {
+        match number.parse::<i32>() {
+            Ok(number) => engine.set_move_number(number),
+            Err(_) => return Err(SgfError::InvalidValue("MN".to_string())),
+        };
+    }
+
+    for player in [Player::Black, Player::White].iter() {
+        let (time, periods) = match player {
+            Player::Black => ("BL", "OB"),
+            Player::White => ("WL", "OW"),
+        };
+
+        if let Some(seconds) = node.first(time).and_then(|seconds| seconds.parse::<f64>().ok()) {
+            engine.record_time_left(TimeLeft {
+                seconds,
+                periods: node.first(periods).and_then(|periods| periods.parse::<u32>().ok()),
+            });
+        }
+    }
+
+    let annotation = match (node.first("BM"), node.get("DO"), node.get("IT"), node.get("TE")) {
+        (Some("2"), _, _, _) => Some(Annotation::VeryBad),
+        (Some(_), _, _, _) => Some(Annotation::Bad),
+        (None, Some(_), _, _) => Some(Annotation::Doubtful),
+        (None, None, Some(_), _) => Some(Annotation::Interesting),
+        (None, None, None, Some(_)) => Some(Annotation::Tesuji),
+        _ => None,
+    };
+    if annotation.is_some() {
+        engine.set_annotation(annotation);
+    }
+
+    if let Some(value) = node.first("V").and_then(|value| value.parse::<f64>().ok()) {
+        engine.set_value(Some(value));
+    }
+
+    if let Some(comment) = node.first("C") {
+        engine.set_comment(Some(comment.to_string()));
+    }
+
+    Ok(grown)
+}
+
+/// The player of the first move in the main line.
+fn first_move_player(tree: &SgfTree) -> Option<Player> {
+    for node in tree.nodes.iter() {
+        match (node.get("B"), node.get("W")) {
+            (Some(_), None) => return Some(Player::Black),
+            (None, Some(_)) => return Some(Player::White),
+            _ => {},
+        };
+    }
+
+    tree.variations.first().and_then(first_move_player)
+}
+
+fn player_property(player: Player) -> &'static str {
+    match player {
+        Player::Black => "B",
+        Player::White => "W",
+    }
+}
+
+fn parse_player(player: &str) -> Option<Player> {
+    match player {
+        "B" | "b" => Some(Player::Black),
+        "W" | "w" => Some(Player::White),
+        _ => None,
+    }
+}
+
+/// The point or the compressed rectangle of points.
+fn parse_points(value: &str, size: u8) -> Option<Vec<Location>> {
+    let parse_point = |point: &str| -> Option<(u8, u8)> {
+        let bytes = point.as_bytes();
+
+        if bytes.len() != 2 || !bytes[0].is_ascii_lowercase() || !bytes[1].is_ascii_lowercase() {
+            return None;
+        }
+
+        let (alphabet, row) = (bytes[0] - b'a', bytes[1] - b'a');
+
+        match alphabet < size && row < size {
+            true => Some((alphabet, size - 1 - row)),
+            false => None,
+        }
+    };
+
+    let mut fields = value.split(':');
+    let from = parse_point(fields.next()?)?;
+    let to = match fields.next() {
+        Some(to) => parse_point(to)?,
+        None => from,
+    };
+
+    let mut points: Vec<Location> = Vec::new();
+    for alphabet in std::cmp::min(from.0, to.0)..=std::cmp::max(from.0, to.0) {
+        for digit in std::cmp::min(from.1, to.1)..=std::cmp::max(from.1, to.1) {
+            points.push(Location {
+                alphabet,
+                digit,
+            });
+        }
+    }
+
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variations() {
+        let engine = GoGameEngine::from_sgf("
+            (;GM[1]FF[4]SZ[9]KM[6.5]AB[cc:cd]C[Setup [1\\] ]
+            ;W[ee]C[Center]
+            (;B[gg];W[gc]BM[1])
+            (;B[gc]DO[];W[]))
+        ").unwrap();
+
+        assert_eq!(engine.size(), 9);
+        assert_eq!(engine.komi(), 6.5);
+        assert_eq!(engine.get_chess(Location { alphabet: 2, digit: 5 }), ChessType::Black);
+        assert_eq!(engine.get_chess(Location { alphabet: 6, digit: 2 }), ChessType::Black);
+        assert_eq!(engine.annotation(), Some(Annotation::Bad));
+        assert_eq!(engine.steps(), 3);
+
+        assert!(engine.to_sgf().contains("SZ[9]KM[6.5]AB[cd][cc]PL[W]C[Setup [1\\] ];W[ee]"));
+        assert!(engine.to_sgf().contains(";W[ee]C[Center]\n(;B[gg]\n;W[gc]BM[1]\n)(;B[gc]DO[]\n;W[]\n))"));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut engine = GoGameEngine::from_sgf("(;SZ[9];B[ee](;W[ge])(;W[cc]))").unwrap();
+
+        assert!(engine.back());
+        assert_eq!(engine.variations(), 2);
+        assert!(engine.forward(1));
+        assert_eq!(engine.get_chess(Location { alphabet: 2, digit: 6 }), ChessType::White);
+        assert_eq!(engine.get_chess(Location { alphabet: 6, digit: 4 }), ChessType::None);
+
+        assert!(engine.back() && engine.back());
+        assert!(!engine.back());
+        assert_eq!(engine.get_chess(Location { alphabet: 4, digit: 4 }), ChessType::None);
+    }
+
+    #[test]
+    fn test_player_names() {
+        let engine = GoGameEngine::from_sgf("(;SZ[9]PB[GNU Go 3.8]PW[Leela \\[0.11\\]];B[ee])").unwrap();
+
+        assert_eq!(engine.player_name(Player::Black), Some("GNU Go 3.8"));
+        assert_eq!(engine.player_name(Player::White), Some("Leela [0.11]"));
+        assert!(engine.to_sgf().contains("PB[GNU Go 3.8]PW[Leela [0.11\\]]"));
+    }
+
+    #[test]
+    fn test_setup_nodes() {
+        let (engine, warnings) = GoGameEngine::from_sgf_with_warnings("(;SZ[9]AB[cc];AB[gg]AE[cc];W[ee];AW[aa];B[ge])").unwrap();
+
+        assert_eq!(engine.get_chess(Location { alphabet: 6, digit: 2 }), ChessType::Black);
+        assert_eq!(engine.get_chess(Location { alphabet: 2, digit: 6 }), ChessType::None);
+        assert_eq!(engine.get_chess(Location { alphabet: 0, digit: 8 }), ChessType::None);
+        assert_eq!(engine.steps(), 2);
+        assert_eq!(warnings, ["Setup at node 3 is skipped"]);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert_eq!(GoGameEngine::from_sgf("(;SZ[9];B[ee]").err(), Some(SgfError::Syntax(13)));
+        assert_eq!(GoGameEngine::from_sgf("(;SZ[9];B[ee];W[ee])").err(), Some(SgfError::IllegalMove(2)));
+    }
+}
@@ -12,6 +12,13 @@ pub(crate) struct Node<T> {
     prev: Option<Weak<RefCell<Node<T>>>>,
 }
 
+/// The event of visiting the tree, the variations are enclosed by the begin and the end.
+pub(crate) enum Visit<'a, T> {
+    Node(&'a T),
+    Begin,
+    End,
+}
+
 pub(crate) struct Tree<T> {
     root: Rc<RefCell<Node<T>>>,
 
@@ -48,6 +55,12 @@ impl<T> Tree<T> {
         f(&self.head.borrow().data);
     }
 
+    pub fn access_head_mut<F>(&mut self, f:F) where
+        F: FnOnce(&mut T) {
+
+        f(&mut self.head.borrow_mut().data);
+    }
+
     pub fn grow<F>(&mut self, f: F) where
         F: FnOnce(&T) -> T {
 
@@ -155,77 +168,97 @@ impl<T> Tree<T> {
         f(&removed_head.data);
     }
 
-    pub fn preorder<F>(&self, mut f: F) where F: FnMut(&T) {
-        let iterator: PreIterator<T> = PreIterator::new(&self.root);
+    /// Visits the nodes from the root down to the head.
+    pub fn path<F>(&self, mut f: F) where F: FnMut(&T) {
+        let mut nodes: Vec<Rc<RefCell<Node<T>>>> = Vec::new();
+        let mut node = Some(self.head.clone());
+
+        while let Some(current_node) = node {
+            node = current_node.borrow().parent.as_ref()
+                .map(|parent| parent.upgrade().unwrap() /* Always success since the tree holds */);
+            nodes.push(current_node);
+        }
 
-        for node in iterator {
+        for node in nodes.iter().rev() {
             f(&node.borrow().data);
         }
     }
-}
 
-struct PreIterator<T> {
-    node: Rc<RefCell<Node<T>>>,
-    has_next: bool,
-}
+    /// Moves the head to the parent without removing it, false at the root.
+    pub fn up(&mut self) -> bool {
+        let parent = match self.head.borrow().parent.as_ref() {
+            Some(parent) => parent.upgrade().unwrap() /* Always success since the tree holds */,
+            None => return false,
+        };
+
+        self.head = parent;
+        true
+    }
+
+    /// Moves the head to the child at the index, the first child is the main line.
+    pub fn down(&mut self, index: usize) -> bool {
+        let mut child = self.head.borrow().first_child.clone();
 
-impl<T> PreIterator<T> {
-    fn new(node: &Rc<RefCell<Node<T>>>) -> PreIterator::<T> {
-        PreIterator::<T> {
-            node: node.clone(),
-            has_next: true,
+        for _ in 0..index {
+            child = match child {
+                Some(node) => node.borrow().next.clone(),
+                None => None,
+            };
+        }
+
+        match child {
+            Some(node) => {
+                self.head = node;
+                true
+            },
+            None => false,
         }
     }
-}
 
-impl<T> Iterator for PreIterator<T> {
-    type Item = Rc<RefCell<Node<T>>>;
+    pub fn children_count(&self) -> usize {
+        let mut count = 0;
+        let mut child = self.head.borrow().first_child.clone();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.has_next {
-            return None;
+        while let Some(node) = child {
+            count += 1;
+            child = node.borrow().next.clone();
         }
 
-        let current_node = self.node.clone();
+        count
+    }
+
+    /// Visits the nodes as the SGF game tree, a node with several children encloses each of them.
+    pub fn visit<F>(&self, mut f: F) where F: FnMut(Visit<T>) {
+        fn visit_node<T, F>(node: &Rc<RefCell<Node<T>>>, f: &mut F) where F: FnMut(Visit<T>) {
+            let mut node = node.clone();
 
-        { /* Move to next*/
-            let mut current_node = current_node.clone();
+            loop {
+                f(Visit::Node(&node.borrow().data));
 
-            let has_first_child = {
-                match current_node.borrow().first_child {
-                    None => false,
-                    _ => true,
+                let first_child = node.borrow().first_child.clone();
+                let first_child = match first_child {
+                    Some(first_child) => first_child,
+                    None => return,
+                };
+
+                if first_child.borrow().next.is_none() {
+                    node = first_child;
+                    continue;
                 }
-            };
 
-            if has_first_child {
-                self.node = current_node.borrow().first_child.as_ref().unwrap().clone();
-            } else {
-                loop {
-                    let find_node = {
-                        let current_node_ref = current_node.borrow();
-
-                        if let Some(next) = current_node_ref.next.as_ref() {
-                            self.node = next.clone();
-                            break;
-                        }
-
-                        match current_node_ref.parent.as_ref() {
-                            Some(parent) => {
-                                parent.upgrade().unwrap()
-                            },
-                            None => {
-                                self.has_next = false;
-                                break;
-                            }
-                        }
-                    };
-
-                    current_node = find_node;
+                let mut child = Some(first_child);
+                while let Some(current_child) = child {
+                    f(Visit::Begin);
+                    visit_node(&current_child, f);
+                    f(Visit::End);
+
+                    child = current_child.borrow().next.clone();
                 }
+
+                return;
             }
         }
 
-        return Some(current_node);
+        visit_node(&self.root, &mut f);
     }
 }
@@ -0,0 +1,682 @@
+use std::collections::HashMap;
+
+use crate::{BOARD_SIZE_MAX, Location};
+use crate::board::go_board::{GoBoard, GoBoardVisited, ChessType};
+
+const MAX_NODES_DEFAULT: usize = 200000;
+
+/// The result of the problem, ordered from the best of the defender to the best of the attacker.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum TsumegoStatus {
+    Live,
+    Seki,
+    /// The result depends on who wins the ko.
+    Ko,
+    Kill,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TsumegoError {
+    /// No stone of the defender in the region.
+    NoDefender,
+    NodeLimit,
+}
+
+impl std::fmt::Display for TsumegoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TsumegoError::NoDefender => write!(f, "No stone of the defender in the region"),
+            TsumegoError::NodeLimit => write!(f, "The search exceeds the node limit"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TsumegoMove {
+    pub chess_type: ChessType,
+    /// None is pass.
+    pub location: Option<Location>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Variation {
+    pub mov: TsumegoMove,
+    /// The result with the best play after the move.
+    pub status: TsumegoStatus,
+    pub children: Vec<Variation>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TsumegoSolution {
+    pub status: TsumegoStatus,
+    pub main_line: Vec<TsumegoMove>,
+    /// Every first move with its result, the correct moves have every answer refuted,
+    /// the wrong moves have the refutation line.
+    pub variations: Vec<Variation>,
+    pub nodes: usize,
+}
+
+/// The local life-and-death problem, only the empty points in the region are played.
+pub struct Tsumego {
+    board: GoBoard,
+    region: Vec<Location>,
+    attacker: ChessType,
+    to_move: ChessType,
+    ko: Option<Location>,
+    max_nodes: usize,
+}
+
+impl Tsumego {
+    pub fn new(board: &GoBoard, region: Vec<Location>, attacker: ChessType, to_move: ChessType) -> Tsumego {
+        Tsumego {
+            board: board.clone(),
+            region,
+            attacker,
+            to_move,
+            ko: None,
+            max_nodes: MAX_NODES_DEFAULT,
+        }
+    }
+
+    /// The point forbidden by the ko rule for the player to move.
+    pub fn set_ko(&mut self, ko: Option<Location>) {
+        self.ko = ko;
+    }
+
+    pub fn set_max_nodes(&mut self, max_nodes: usize) {
+        self.max_nodes = max_nodes;
+    }
+
+    pub fn solve(&self) -> Result<TsumegoSolution, TsumegoError> {
+        let defender = opponent(self.attacker);
+
+        if !self.region.iter().any(|location| self.board.get(location) == defender) {
+            return Err(TsumegoError::NoDefender);
+        }
+
+        let mut solver = Solver {
+            tsumego: self,
+            defender,
+            nodes: 0,
+            table: HashMap::new(),
+            path: Vec::new(),
+            max_depth: self.region.len() * 2 + 2,
+        };
+
+        let root = State {
+            board: self.board.clone(),
+            to_move: self.to_move,
+            ko: self.ko,
+            passes: 0,
+            retakes: [true; 2],
+        };
+
+        let status = solver.value(&root, 0)?;
+        let main_line = solver.line(&root, 0)?;
+
+        let mut variations: Vec<Variation> = Vec::new();
+        for (mov, child, retake) in solver.children(&root) {
+            if mov.location.is_none() {
+                continue;
+            }
+
+            let child_status = solver.value(&child, 1)?;
+            let child_status = solver.capped(child_status, mov.chess_type, retake);
+
+            let children = match child_status == status {
+                true => {
+                    let mut answers: Vec<Variation> = Vec::new();
+
+                    for (answer, grandchild, retake) in solver.children(&child) {
+                        if answer.location.is_none() {
+                            continue;
+                        }
+
+                        let answer_status = solver.value(&grandchild, 2)?;
+
+                        answers.push(Variation {
+                            mov: answer,
+                            status: solver.capped(answer_status, answer.chess_type, retake),
+                            children: solver.chain(&grandchild, 2)?,
+                        });
+                    }
+
+                    answers
+                },
+                false => solver.chain(&child, 1)?,
+            };
+
+            variations.push(Variation {
+                mov,
+                status: child_status,
+                children,
+            });
+        }
+
+        Ok(TsumegoSolution {
+            status,
+            main_line,
+            variations,
+            nodes: solver.nodes,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct State {
+    board: GoBoard,
+    to_move: ChessType,
+    ko: Option<Location>,
+    passes: u8,
+    /// Whether the attacker and the defender may still retake a ko, as if a ko threat is answered.
+    retakes: [bool; 2],
+}
+
+struct Solver<'a> {
+    tsumego: &'a Tsumego,
+    defender: ChessType,
+    nodes: usize,
+    /// The results of the positions, only the ones not cut by the depth limit or the repetition in
+    /// the line are kept since the others depend on the path to the position.
+    table: HashMap<State, TsumegoStatus>,
+    /// The boards of the current line, a move repeating one is forbidden except the ko retake.
+    path: Vec<GoBoard>,
+    max_depth: usize,
+}
+
+impl<'a> Solver<'a> {
+    fn value(&mut self, state: &State, depth: usize) -> Result<TsumegoStatus, TsumegoError> {
+        self.search(state, depth).map(|(status, _)| status)
+    }
+
+    /// The result of the state and whether it is exact, that is not cut by the depth limit or the
+    /// repetition in the line.
+    fn search(&mut self, state: &State, depth: usize) -> Result<(TsumegoStatus, bool), TsumegoError> {
+        if let Some(status) = self.table.get(state) {
+            return Ok((*status, true));
+        }
+
+        self.nodes += 1;
+        if self.nodes > self.tsumego.max_nodes {
+            return Err(TsumegoError::NodeLimit);
+        }
+
+        if let Some(status) = self.settled(&state.board) {
+            return Ok((status, true));
+        }
+
+        if state.passes >= 2 {
+            return Ok((self.terminal(&state.board), true));
+        }
+
+        if depth >= self.max_depth {
+            return Ok((self.terminal(&state.board), false));
+        }
+
+        let (mut best, goal) = match state.to_move == self.tsumego.attacker {
+            true => (TsumegoStatus::Live, TsumegoStatus::Kill),
+            false => (TsumegoStatus::Kill, TsumegoStatus::Live),
+        };
+        let mut exact = true;
+
+        self.path.push(state.board.clone());
+
+        for (mov, child, retake) in self.children(state) {
+            if !retake && mov.location.is_some() && self.path.contains(&child.board) {
+                exact = false;
+                continue;
+            }
+
+            let (status, child_exact) = match self.search(&child, depth + 1) {
+                Ok((status, child_exact)) => (self.capped(status, mov.chess_type, retake), child_exact),
+                Err(error) => {
+                    self.path.pop();
+                    return Err(error);
+                },
+            };
+
+            best = match mov.chess_type == self.tsumego.attacker {
+                true => std::cmp::max(best, status),
+                false => std::cmp::min(best, status),
+            };
+
+            /* The best result found exactly cannot be bettered by the other moves */
+            if status == goal && child_exact {
+                exact = true;
+                break;
+            }
+
+            exact = exact && child_exact;
+
+            if best == goal {
+                break;
+            }
+        }
+
+        self.path.pop();
+
+        if exact {
+            self.table.insert(state.clone(), best);
+        }
+
+        Ok((best, exact))
+    }
+
+    /// The best play from the state, a move is preferred to pass.
+    fn line(&mut self, state: &State, depth: usize) -> Result<Vec<TsumegoMove>, TsumegoError> {
+        let mut line: Vec<TsumegoMove> = Vec::new();
+        let mut state = state.clone();
+        let mut depth = depth;
+
+        while self.settled(&state.board).is_none() && state.passes < 2 && depth < self.max_depth {
+            let status = self.value(&state, depth)?;
+
+            let mut next: Option<(TsumegoMove, State)> = None;
+            for (mov, child, retake) in self.children(&state) {
+                let child_status = self.value(&child, depth + 1)?;
+
+                if self.capped(child_status, mov.chess_type, retake) == status {
+                    next = Some((mov, child));
+                    break;
+                }
+            }
+
+            match next {
+                Some((mov, child)) => {
+                    line.push(mov);
+                    state = child;
+                    depth += 1;
+                },
+                None => break,
+            };
+        }
+
+        /* The passes at the end are not a part of the solution */
+        while let Some(TsumegoMove { location: None, .. }) = line.last() {
+            line.pop();
+        }
+
+        Ok(line)
+    }
+
+    /// The best play as nested variations.
+    fn chain(&mut self, state: &State, depth: usize) -> Result<Vec<Variation>, TsumegoError> {
+        let line = self.line(state, depth)?;
+        let mut state = state.clone();
+        let mut statuses: Vec<TsumegoStatus> = Vec::new();
+
+        for mov in line.iter() {
+            for (child_mov, child, retake) in self.children(&state) {
+                if child_mov == *mov {
+                    let status = self.value(&child, depth + statuses.len() + 1)?;
+                    statuses.push(self.capped(status, mov.chess_type, retake));
+                    state = child;
+                    break;
+                }
+            }
+        }
+
+        let mut children: Vec<Variation> = Vec::new();
+        for (mov, status) in line.into_iter().zip(statuses).rev() {
+            children = vec![Variation {
+                mov,
+                status,
+                children,
+            }];
+        }
+
+        Ok(children)
+    }
+
+    /// The moves on the empty points of the region and pass, the moves next to stones first.
+    fn children(&self, state: &State) -> Vec<(TsumegoMove, State, bool)> {
+        let board = &state.board;
+        let player = match state.to_move == self.tsumego.attacker {
+            true => 0,
+            false => 1,
+        };
+
+        let mut locations: Vec<Location> = self.tsumego.region.iter()
+            .filter(|location| board.get(location) == ChessType::None)
+            .cloned()
+            .collect();
+
+        locations.sort_by_key(|location| {
+            let stones = board.neighbors(location).iter()
+                .filter(|neighbor| board.get(neighbor) != ChessType::None)
+                .count();
+            (std::cmp::Reverse(stones), location.alphabet, location.digit)
+        });
+
+        let mut children: Vec<(TsumegoMove, State, bool)> = Vec::new();
+
+        for location in locations {
+            let retake = state.ko == Some(location);
+
+            if retake && !state.retakes[player] {
+                continue;
+            }
+
+            let mut child = state.clone();
+
+            let chess_change = match child.board.make_move(state.to_move, location) {
+                Ok(chess_change) => chess_change,
+                Err(_) => continue,
+            };
+
+            child.to_move = opponent(state.to_move);
+            child.ko = child.board.ko(&chess_change);
+            child.passes = 0;
+
+            if retake {
+                child.retakes[player] = false;
+            }
+
+            children.push((
+                TsumegoMove {
+                    chess_type: state.to_move,
+                    location: Some(location),
+                },
+                child,
+                retake,
+            ));
+        }
+
+        let mut child = state.clone();
+        child.to_move = opponent(state.to_move);
+        child.ko = None;
+        child.passes += 1;
+
+        children.push((
+            TsumegoMove {
+                chess_type: state.to_move,
+                location: None,
+            },
+            child,
+            false,
+        ));
+
+        children
+    }
+
+    /// A side winning by a ko retake only wins the ko.
+    fn capped(&self, status: TsumegoStatus, chess_type: ChessType, retake: bool) -> TsumegoStatus {
+        match (retake, chess_type == self.tsumego.attacker) {
+            (false, _) => status,
+            (true, true) => std::cmp::min(status, TsumegoStatus::Ko),
+            (true, false) => std::cmp::max(status, TsumegoStatus::Ko),
+        }
+    }
+
+    fn settled(&self, board: &GoBoard) -> Option<TsumegoStatus> {
+        let defenders: Vec<&Location> = self.tsumego.region.iter()
+            .filter(|location| board.get(location) == self.defender)
+            .collect();
+
+        if defenders.is_empty() {
+            return Some(TsumegoStatus::Kill);
+        }
+
+        let alive = benson(board, self.defender);
+
+        match defenders.iter().any(|location| alive.get(location)) {
+            true => Some(TsumegoStatus::Live),
+            false => None,
+        }
+    }
+
+    /// The defender is not captured after both sides pass, it is seki if sharing liberties with the attacker in the region.
+    fn terminal(&self, board: &GoBoard) -> TsumegoStatus {
+        let region = &self.tsumego.region;
+
+        for location in region.iter() {
+            if board.get(location) != self.defender {
+                continue;
+            }
+
+            for liberty in board.liberties(location) {
+                if board.neighbors(&liberty).iter().any(|neighbor| board.get(neighbor) == self.tsumego.attacker && region.contains(neighbor)) {
+                    return TsumegoStatus::Seki;
+                }
+            }
+        }
+
+        TsumegoStatus::Live
+    }
+}
+
+fn opponent(chess_type: ChessType) -> ChessType {
+    match chess_type {
+        ChessType::Black => ChessType::White,
+        ChessType::White => ChessType::Black,
+        ChessType::None => ChessType::None,
+    }
+}
+
+/// The unconditionally alive stones of the color by Benson's algorithm.
+fn benson(board: &GoBoard, chess_type: ChessType) -> GoBoardVisited {
+    let size = board.size();
+
+    /* The chains of the color and the regions connected by the other points */
+    let mut ids = [[None; BOARD_SIZE_MAX]; BOARD_SIZE_MAX];
+    let mut chain_num = 0;
+    let mut regions: Vec<Vec<Location>> = Vec::new();
+
+    for alphabet in 0..size {
+        for digit in 0..size {
+            if ids[alphabet as usize][digit as usize].is_some() {
+                continue;
+            }
+
+            let location = Location {
+                alphabet,
+                digit,
+            };
+            let is_chain = board.get(&location) == chess_type;
+            let id = match is_chain {
+                true => chain_num,
+                false => regions.len(),
+            };
+
+            let mut points: Vec<Location> = vec![location];
+            let mut queue: Vec<Location> = vec![location];
+            ids[alphabet as usize][digit as usize] = Some(id);
+
+            while let Some(point) = queue.pop() {
+                for neighbor in board.neighbors(&point) {
+                    if (board.get(&neighbor) == chess_type) == is_chain && ids[neighbor.alphabet as usize][neighbor.digit as usize].is_none() {
+                        ids[neighbor.alphabet as usize][neighbor.digit as usize] = Some(id);
+                        points.push(neighbor);
+                        queue.push(neighbor);
+                    }
+                }
+            }
+
+            match is_chain {
+                true => chain_num += 1,
+                false => regions.push(points),
+            };
+        }
+    }
+
+    let chain_id = |location: &Location| match board.get(location) == chess_type {
+        true => ids[location.alphabet as usize][location.digit as usize],
+        false => None,
+    };
+
+    let mut neighbor_chains: Vec<Vec<usize>> = Vec::new();
+    let mut vital_chains: Vec<Vec<usize>> = Vec::new();
+
+    for region in regions.iter() {
+        let mut neighbors: Vec<usize> = Vec::new();
+
+        for point in region.iter() {
+            for neighbor in board.neighbors(point) {
+                if let Some(chain) = chain_id(&neighbor) {
+                    if !neighbors.contains(&chain) {
+                        neighbors.push(chain);
+                    }
+                }
+            }
+        }
+
+        /* A region is vital to the chain if all its empty points are liberties of the chain */
+        let vital: Vec<usize> = neighbors.iter()
+            .filter(|chain| region.iter()
+                .filter(|point| board.get(point) == ChessType::None)
+                .all(|point| board.neighbors(point).iter().any(|neighbor| chain_id(neighbor) == Some(**chain))))
+            .cloned()
+            .collect();
+
+        neighbor_chains.push(neighbors);
+        vital_chains.push(vital);
+    }
+
+    let mut alive_chains: Vec<bool> = vec![true; chain_num];
+    let mut healthy_regions: Vec<bool> = vec![true; regions.len()];
+
+    loop {
+        let mut vital_regions: Vec<usize> = vec![0; chain_num];
+
+        for region in 0..regions.len() {
+            if healthy_regions[region] {
+                for chain in vital_chains[region].iter() {
+                    vital_regions[*chain] += 1;
+                }
+            }
+        }
+
+        let mut removed = false;
+
+        for chain in 0..chain_num {
+            if alive_chains[chain] && vital_regions[chain] < 2 {
+                alive_chains[chain] = false;
+                removed = true;
+            }
+        }
+
+        if !removed {
+            break;
+        }
+
+        for region in 0..regions.len() {
+            if neighbor_chains[region].iter().any(|chain| !alive_chains[*chain]) {
+                healthy_regions[region] = false;
+            }
+        }
+    }
+
+    let mut alive = GoBoardVisited::new(size);
+    for alphabet in 0..size {
+        for digit in 0..size {
+            let location = Location {
+                alphabet,
+                digit,
+            };
+
+            if let Some(chain) = chain_id(&location) {
+                alive.set(&location, alive_chains[chain]);
+            }
+        }
+    }
+
+    alive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Diagram, GoGameEngine, Player};
+
+    fn engine(diagram: &str) -> GoGameEngine {
+        let diagram: Diagram = diagram.parse().unwrap();
+        GoGameEngine::from_diagram(&diagram, 6.5).unwrap()
+    }
+
+    /// The two bottom rows between the black walls.
+    fn region() -> Vec<Location> {
+        let mut region: Vec<Location> = Vec::new();
+
+        for alphabet in 2..7 {
+            for digit in 0..2 {
+                region.push(Location { alphabet, digit });
+            }
+        }
+
+        region
+    }
+
+    const STRAIGHT_THREE: &str = "
+        $$ +-------------------+
+        $$ | . . . . . . . . . |
+        $$ | . . . . . . . . . |
+        $$ | . . . . . . . . . |
+        $$ | . . . . . . . . . |
+        $$ | . . . . . . . . . |
+        $$ | . . . . . . . . . |
+        $$ | . X X X X X X X . |
+        $$ | . X O O O O O X . |
+        $$ | . X O . . . O X . |
+        $$ +-------------------+
+    ";
+
+    #[test]
+    fn test_kill() {
+        let engine = engine(&format!("$$B{}", STRAIGHT_THREE));
+        let solution = engine.tsumego(region(), Player::Black).solve().unwrap();
+
+        assert_eq!(solution.status, TsumegoStatus::Kill);
+        assert_eq!(solution.main_line[0], TsumegoMove {
+            chess_type: ChessType::Black,
+            location: Some(Location { alphabet: 4, digit: 0 }),
+        });
+
+        let wrong = solution.variations.iter()
+            .find(|variation| variation.mov.location == Some(Location { alphabet: 3, digit: 0 }))
+            .unwrap();
+        assert_eq!(wrong.status, TsumegoStatus::Live);
+        assert!(!wrong.children.is_empty());
+    }
+
+    #[test]
+    fn test_live() {
+        let engine = engine(&format!("$$W{}", STRAIGHT_THREE));
+        let solution = engine.tsumego(region(), Player::Black).solve().unwrap();
+
+        assert_eq!(solution.status, TsumegoStatus::Live);
+        assert_eq!(solution.main_line, vec![TsumegoMove {
+            chess_type: ChessType::White,
+            location: Some(Location { alphabet: 4, digit: 0 }),
+        }]);
+    }
+
+    #[test]
+    fn test_ko() {
+        let engine = engine("
+            $$W
+            $$ +-------------------+
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | X X X X X X X X . |
+            $$ | . X O O O O X . . |
+            $$ | . X O . O X . X . |
+            $$ +-------------------+
+        ");
+        let solution = engine.tsumego(region(), Player::Black).solve().unwrap();
+
+        assert_eq!(solution.status, TsumegoStatus::Ko);
+        assert_eq!(solution.main_line[0].location, Some(Location { alphabet: 6, digit: 0 }));
+    }
+
+    #[test]
+    fn test_no_defender() {
+        let engine = engine(&format!("$$W{}", STRAIGHT_THREE));
+        let region = vec![Location { alphabet: 0, digit: 0 }];
+
+        assert_eq!(engine.tsumego(region, Player::Black).solve().err(), Some(TsumegoError::NoDefender));
+    }
+}
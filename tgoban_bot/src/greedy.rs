@@ -0,0 +1,102 @@
+use go_game_engine::{GoGameEngine, ChessType, Location, Player};
+
+use crate::{Bot, BotMove, Rng};
+use crate::random::candidate_moves;
+
+const CAPTURE_SCORE: i32 = 100;
+const SAVE_SCORE: i32 = 50;
+const ATARI_SCORE: i32 = 10;
+const SELF_ATARI_SCORE: i32 = -1000;
+
+/// Captures, saves the own chains in atari and ataris the opponent, otherwise plays randomly.
+pub struct GreedyBot {
+    rng: Rng,
+}
+
+impl GreedyBot {
+    pub fn new(seed: u64) -> GreedyBot {
+        GreedyBot {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+fn evaluate(engine: &GoGameEngine, chess_type: ChessType, location: &Location) -> i32 {
+    let board = engine.board();
+    let mut score = 0;
+    let mut seen: Vec<Location> = Vec::new();
+
+    for neighbor in board.neighbors(location) {
+        let neighbor_type = board.get(&neighbor);
+
+        if neighbor_type == ChessType::None || seen.contains(&neighbor) {
+            continue;
+        }
+
+        let chain = board.chain(&neighbor);
+        let liberties = board.liberties(&neighbor).len() as i32;
+        let size = chain.len() as i32;
+        seen.extend(chain);
+
+        score += match (neighbor_type == chess_type, liberties) {
+            (false, 1) => CAPTURE_SCORE * size,
+            (false, 2) => ATARI_SCORE * size,
+            (true, 1) => SAVE_SCORE * size,
+            _ => 0,
+        };
+    }
+
+    let mut next_board = board.clone();
+
+    match next_board.make_move(chess_type, *location) {
+        Ok(chess_change) => {
+            let liberties = next_board.liberties(location).len();
+
+            if liberties == 1 && chess_change.remove.len() == 0 {
+                return SELF_ATARI_SCORE;
+            }
+
+            if liberties == 1 {
+                /* Saving or capturing does not count if the chain is in atari again */
+                score -= SAVE_SCORE * next_board.chain(location).len() as i32;
+            }
+        },
+        Err(_) => return SELF_ATARI_SCORE,
+    };
+
+    score
+}
+
+impl Bot for GreedyBot {
+    fn name(&self) -> String {
+        "TGoBan Greedy".to_string()
+    }
+
+    fn genmove(&mut self, engine: &GoGameEngine) -> BotMove {
+        let chess_type = match engine.player() {
+            Player::Black => ChessType::Black,
+            Player::White => ChessType::White,
+        };
+
+        let mut best_score = SELF_ATARI_SCORE;
+        let mut best_moves: Vec<Location> = Vec::new();
+
+        for location in candidate_moves(engine) {
+            let score = evaluate(engine, chess_type, &location);
+
+            if score > best_score {
+                best_score = score;
+                best_moves.clear();
+            }
+
+            if score == best_score {
+                best_moves.push(location);
+            }
+        }
+
+        match self.rng.choose(&best_moves) {
+            Some(location) => BotMove::Play(*location),
+            None => BotMove::Pass,
+        }
+    }
+}
@@ -0,0 +1,91 @@
+mod rng;
+mod random;
+mod greedy;
+
+pub use crate::rng::Rng;
+pub use crate::random::RandomBot;
+pub use crate::greedy::GreedyBot;
+
+use go_game_engine::{GoGameEngine, Location};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BotMove {
+    Play(Location),
+    Pass,
+    Resign,
+}
+
+/// The player who generates moves for the player to move of the engine.
+pub trait Bot {
+    fn name(&self) -> String;
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn genmove(&mut self, engine: &GoGameEngine) -> BotMove;
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Level {
+    Random,
+    Greedy,
+}
+
+impl std::str::FromStr for Level {
+    type Err = ();
+    fn from_str(level: &str) -> Result<Level, ()> {
+        match level {
+            "random" => Ok(Level::Random),
+            "greedy" => Ok(Level::Greedy),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn new_bot(level: Level, seed: u64) -> Box<dyn Bot + Send> {
+    match level {
+        Level::Random => Box::new(RandomBot::new(seed)),
+        Level::Greedy => Box::new(GreedyBot::new(seed)),
+    }
+}
+
+impl<B: Bot + ?Sized> Bot for Box<B> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn version(&self) -> String {
+        (**self).version()
+    }
+
+    fn genmove(&mut self, engine: &GoGameEngine) -> BotMove {
+        (**self).genmove(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use go_game_engine::GameStatus;
+
+    #[test]
+    fn test_full_game() {
+        let mut engine = GoGameEngine::new(9, 6.5);
+        let mut bots = [new_bot(Level::Random, 1), new_bot(Level::Greedy, 2)];
+        let mut turn = 0;
+
+        while engine.get_status() == GameStatus::Playing && engine.steps() < 500 {
+            match bots[turn % 2].genmove(&engine) {
+                BotMove::Play(location) => {
+                    assert!(engine.make_move(location).is_ok());
+                },
+                BotMove::Pass => engine.pass(),
+                BotMove::Resign => break,
+            };
+            turn += 1;
+        }
+
+        assert_eq!(engine.get_status(), GameStatus::Scoring);
+    }
+}
@@ -0,0 +1,41 @@
+use go_game_engine::{GoGameEngine, ChessType, Location, Player};
+
+use crate::{Bot, BotMove, Rng};
+
+pub struct RandomBot {
+    rng: Rng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+/// The legal moves that do not fill the own eyes of the player to move.
+pub fn candidate_moves(engine: &GoGameEngine) -> Vec<Location> {
+    let chess_type = match engine.player() {
+        Player::Black => ChessType::Black,
+        Player::White => ChessType::White,
+    };
+
+    engine.legal_moves()
+        .into_iter()
+        .filter(|location| !engine.board().is_eye(chess_type, location))
+        .collect()
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> String {
+        "TGoBan Random".to_string()
+    }
+
+    fn genmove(&mut self, engine: &GoGameEngine) -> BotMove {
+        match self.rng.choose(&candidate_moves(engine)) {
+            Some(location) => BotMove::Play(*location),
+            None => BotMove::Pass,
+        }
+    }
+}
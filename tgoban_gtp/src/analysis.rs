@@ -0,0 +1,229 @@
+use crate::command::{Color, Command, Id, Vertex};
+use crate::error::ParseError;
+
+/// The engine's analysis command, both stream the `info move ...` lines until interrupted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Analyzer {
+    /// `lz-analyze` of Leela Zero, the winrate and the prior are in 1/10000.
+    Leela,
+    /// `kata-analyze` of KataGo, the winrate and the prior are in 0 to 1.
+    Kata,
+}
+
+impl Analyzer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Analyzer::Leela => "lz-analyze",
+            Analyzer::Kata => "kata-analyze",
+        }
+    }
+}
+
+pub struct Analyze {
+    pub analyzer: Analyzer,
+    /// The player to analyze for, the player to move if None.
+    pub color: Option<Color>,
+    /// The centiseconds between the updates.
+    pub interval: u32,
+    /// Asks for the ownership of the board, only KataGo reports it.
+    pub ownership: bool,
+}
+
+impl Analyze {
+    pub fn command(&self, id: Option<Id>) -> Command {
+        let mut args: Vec<String> = Vec::new();
+
+        if let Some(color) = &self.color {
+            args.push(color.to_string());
+        }
+        args.push(self.interval.to_string());
+
+        if self.ownership && self.analyzer == Analyzer::Kata {
+            args.push("ownership".to_string());
+            args.push("true".to_string());
+        }
+
+        Command::Custom {
+            id,
+            name: self.analyzer.name().to_string(),
+            args,
+        }
+    }
+}
+
+pub struct Candidate {
+    pub vertex: Vertex,
+    pub visits: u32,
+    /// The winrate of the player to move, from 0 to 1.
+    pub winrate: f64,
+    /// The points the player to move leads by, only KataGo reports it.
+    pub score_lead: Option<f64>,
+    /// The policy of the move, from 0 to 1.
+    pub prior: f64,
+    /// The rank of the move, 0 for the best.
+    pub order: u32,
+    /// The principal variation started with the move.
+    pub pv: Vec<Vertex>,
+}
+
+/// One line of the analysis.
+pub struct AnalysisUpdate {
+    pub candidates: Vec<Candidate>,
+    /// The ownership of every point from A19 row by row, 1 for the player to move.
+    pub ownership: Option<Vec<f64>>,
+}
+
+impl AnalysisUpdate {
+    pub fn parse(line: &str, analyzer: Analyzer) -> Result<AnalysisUpdate, ParseError> {
+        let invalid = || ParseError::InvalidAnalysis(line.to_string());
+        let scale = match analyzer {
+            Analyzer::Leela => 10000.0,
+            Analyzer::Kata => 1.0,
+        };
+
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        let mut update = AnalysisUpdate {
+            candidates: Vec::new(),
+            ownership: None,
+        };
+        let mut index = 0;
+
+        while index < tokens.len() {
+            match tokens[index] {
+                "info" => {
+                    let (candidate, next) = parse_candidate(&tokens, index + 1, scale, update.candidates.len() as u32).ok_or_else(invalid)?;
+
+                    update.candidates.push(candidate);
+                    index = next;
+                },
+                "ownership" => {
+                    let values = numbers(&tokens[index + 1..]);
+
+                    update.ownership = Some(values.iter().map(|value| value.parse().unwrap()).collect());
+                    index += 1 + values.len();
+                },
+                _ => {
+                    index += 1 + skipped(&tokens[index + 1..]);
+                },
+            }
+        }
+
+        Ok(update)
+    }
+}
+
+/// The candidate of the tokens after `info`, and the index of the token after the candidate.
+fn parse_candidate(tokens: &[&str], start: usize, scale: f64, rank: u32) -> Option<(Candidate, usize)> {
+    let mut vertex: Option<Vertex> = None;
+    let mut visits: Option<u32> = None;
+    let mut winrate: Option<f64> = None;
+    let mut score_lead: Option<f64> = None;
+    let mut prior: f64 = 0.0;
+    let mut order: u32 = rank;
+    let mut pv: Vec<Vertex> = Vec::new();
+    let mut index = start;
+
+    while index < tokens.len() {
+        let value = tokens.get(index + 1);
+
+        match tokens[index] {
+            "info" | "ownership" => break,
+            "move" => vertex = Some(value?.parse().ok()?),
+            "visits" => visits = Some(value?.parse().ok()?),
+            "winrate" => winrate = Some(value?.parse::<f64>().ok()? / scale),
+            "scoreLead" => score_lead = Some(value?.parse().ok()?),
+            "prior" => prior = value?.parse::<f64>().ok()? / scale,
+            "order" => order = value?.parse().ok()?,
+            "pv" => {
+                pv = tokens[index + 1..].iter()
+                    .map_while(|token| token.parse::<Vertex>().ok())
+                    .collect();
+                index += 1 + pv.len();
+                continue;
+            },
+            _ => {
+                /* The other values such as lcb, utility or pvVisits are not kept */
+                index += 1 + skipped(&tokens[index + 1..]);
+                continue;
+            },
+        };
+
+        index += 2;
+    }
+
+    let candidate = Candidate {
+        vertex: vertex?,
+        visits: visits?,
+        winrate: winrate?,
+        score_lead,
+        prior,
+        order,
+        pv,
+    };
+
+    Some((candidate, index))
+}
+
+/// The leading tokens which are numbers.
+fn numbers<'a>(tokens: &[&'a str]) -> Vec<&'a str> {
+    tokens.iter()
+        .map_while(|token| token.parse::<f64>().ok().map(|_| *token))
+        .collect()
+}
+
+/// The number of the values of an unknown key, the values are numbers or vertices.
+fn skipped(tokens: &[&str]) -> usize {
+    tokens.iter()
+        .take_while(|token| token.parse::<f64>().is_ok() || token.parse::<Vertex>().is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_leela() {
+        let line = "info move D4 visits 120 winrate 4710 prior 1830 lcb 4600 order 0 pv D4 Q16 info move pass visits 3 winrate 3000 prior 10 lcb 0 order 1 pv pass";
+        let update = AnalysisUpdate::parse(line, Analyzer::Leela).unwrap();
+
+        assert_eq!(update.candidates.len(), 2);
+        assert_eq!(update.candidates[0].vertex.to_string(), "D4");
+        assert_eq!(update.candidates[0].visits, 120);
+        assert!((update.candidates[0].winrate - 0.471).abs() < 1e-9);
+        assert!((update.candidates[0].prior - 0.183).abs() < 1e-9);
+        assert_eq!(update.candidates[0].pv.iter().map(|vertex| vertex.to_string()).collect::<Vec<String>>(), vec!["D4", "Q16"]);
+        assert_eq!(update.candidates[1].vertex.to_string(), "pass");
+        assert_eq!(update.candidates[1].order, 1);
+        assert!(update.candidates[1].score_lead.is_none());
+        assert!(update.ownership.is_none());
+    }
+
+    #[test]
+    pub fn test_kata() {
+        let line = "info move Q16 visits 50 edgeVisits 50 utility 0.1 winrate 0.52 scoreMean 1.5 scoreLead 1.2 prior 0.2 lcb 0.5 order 0 isSymmetryOf D4 pv Q16 D4 C3 pvVisits 50 20 3 ownership 0.5 -0.25 0 1";
+        let update = AnalysisUpdate::parse(line, Analyzer::Kata).unwrap();
+
+        assert_eq!(update.candidates.len(), 1);
+        assert_eq!(update.candidates[0].score_lead, Some(1.2));
+        assert_eq!(update.candidates[0].prior, 0.2);
+        assert_eq!(update.candidates[0].pv.len(), 3);
+        assert_eq!(update.ownership, Some(vec![0.5, -0.25, 0.0, 1.0]));
+
+        assert!(AnalysisUpdate::parse("info visits 3 winrate 0.5", Analyzer::Kata).is_err());
+        assert!(AnalysisUpdate::parse("info move D4 visits x winrate 0.5", Analyzer::Kata).is_err());
+    }
+
+    #[test]
+    pub fn test_analyze_command() {
+        let analyze = Analyze {
+            analyzer: Analyzer::Kata,
+            color: Some(Color::Black),
+            interval: 50,
+            ownership: true,
+        };
+
+        assert_eq!(analyze.command(Some(2)).to_string(), "2 kata-analyze b 50 ownership true");
+        assert_eq!(Analyze { analyzer: Analyzer::Leela, ..analyze }.command(None).to_string(), "lz-analyze b 50");
+    }
+}
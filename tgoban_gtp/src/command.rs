@@ -1,6 +1,8 @@
 use std::num::{ParseIntError, ParseFloatError};
 use std::str::{ParseBoolError};
 
+use crate::error::{ParseError, ResponseError};
+
 pub enum RawCommand {
     ProtocolVersion,
     Name,
@@ -13,10 +15,23 @@ pub enum RawCommand {
     Komi,
     Play,
     GenMove,
+    RegGenMove,
+    FinalScore,
+    Undo,
+    ShowBoard,
+    FinalStatusList,
+    FixedHandicap,
+    PlaceFreeHandicap,
+    SetFreeHandicap,
+    LoadSgf,
+    TimeSettings,
+    TimeLeft,
+    KgsTimeSettings,
+    KgsGenMoveCleanup,
 }
 
 impl std::str::FromStr for RawCommand {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
         let raw = match command {
             "protocol_version" => RawCommand::ProtocolVersion,
@@ -30,8 +45,21 @@ impl std::str::FromStr for RawCommand {
             "komi" => RawCommand::Komi,
             "play" => RawCommand::Play,
             "genmove" => RawCommand::GenMove,
+            "reg_genmove" => RawCommand::RegGenMove,
+            "final_score" => RawCommand::FinalScore,
+            "undo" => RawCommand::Undo,
+            "showboard" => RawCommand::ShowBoard,
+            "final_status_list" => RawCommand::FinalStatusList,
+            "fixed_handicap" => RawCommand::FixedHandicap,
+            "place_free_handicap" => RawCommand::PlaceFreeHandicap,
+            "set_free_handicap" => RawCommand::SetFreeHandicap,
+            "loadsgf" => RawCommand::LoadSgf,
+            "time_settings" => RawCommand::TimeSettings,
+            "time_left" => RawCommand::TimeLeft,
+            "kgs-time_settings" => RawCommand::KgsTimeSettings,
+            "kgs-genmove_cleanup" => RawCommand::KgsGenMoveCleanup,
             _ => {
-                return Err(())
+                return Err(ParseError::UnknownCommand(command.to_string()))
             },
         };
 
@@ -43,6 +71,27 @@ pub type Id = i32;
 pub type Integer = i32;
 pub type Float = f32;
 
+/// The largest board of GTP, the vertex letters are A to Z without I.
+pub const MAX_BOARD_SIZE: u8 = 25;
+
+/// Applies the preprocessing of GTP to the input: the control characters other than HT and LF
+/// are removed, HT is converted to the space and the comment after `#` is discarded.
+pub fn preprocess(line: &str) -> String {
+    let line = match line.find('#') {
+        Some(index) => &line[..index],
+        None => line,
+    };
+
+    line.chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            '\n' => Some('\n'),
+            c if c.is_ascii_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
 pub enum Command {
     ProtocolVersion(Option<Id>),
     Name(Option<Id>),
@@ -55,34 +104,66 @@ pub enum Command {
     Komi(Option<Id>, Float),
     Play(Option<Id>, Move),
     GenMove(Option<Id>, Color),
+    RegGenMove(Option<Id>, Color),
+    FinalScore(Option<Id>),
+    Undo(Option<Id>),
+    ShowBoard(Option<Id>),
+    FinalStatusList(Option<Id>, FinalStatus),
+    FixedHandicap(Option<Id>, Integer),
+    PlaceFreeHandicap(Option<Id>, Integer),
+    SetFreeHandicap(Option<Id>, Vec<Vertex>),
+    /// The file and the move number to stop before.
+    LoadSgf(Option<Id>, String, Option<Integer>),
+    /// The main time, the byo-yomi time and the byo-yomi stones, all in seconds.
+    TimeSettings(Option<Id>, Integer, Integer, Integer),
+    /// The time and the stones left in the period, 0 stones for the main time.
+    TimeLeft(Option<Id>, Color, Integer, Integer),
+    KgsTimeSettings(Option<Id>, KgsTimeSystem),
+    KgsGenMoveCleanup(Option<Id>, Color),
+    /// The command not in the GTP specification, such as `lz-analyze` or `gogui-analyze_commands`.
+    Custom {
+        id: Option<Id>,
+        name: String,
+        args: Vec<String>,
+    },
 }
 
 impl std::str::FromStr for Command {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
+        let command = preprocess(command);
         let mut tokens: Vec<&str> = command.split_ascii_whitespace().collect();
 
         if tokens.len() == 0 {
-            return Err(());
+            return Err(ParseError::Empty);
         }
 
         let id: Result<Id, ParseIntError> = tokens[0].parse();
 
+        /* The id is a non-negative integer */
         let id = match id {
-            Ok(val) => {
+            Ok(val) if val >= 0 => {
                 tokens.remove(0);
                 Some(val)
             },
-            Err(_) => None,
+            _ => None,
         };
 
         if tokens.len() == 0 {
-            return Err(());
+            return Err(ParseError::Empty);
         }
 
         let command_token = tokens[0];
         tokens.remove(0);
 
+        let wrong_arguments = || ParseError::WrongArguments(command_token.to_string());
+        let integer = |token: &str| -> Result<Integer, ParseError> {
+            match token.parse() {
+                Ok(number) => Ok(number),
+                Err(_) => Err(ParseError::InvalidInteger(token.to_string())),
+            }
+        };
+
         let command: Command = match command_token {
             "protocol_version" => {
                 Command::ProtocolVersion(id)
@@ -95,7 +176,7 @@ impl std::str::FromStr for Command {
             },
             "known_command" => {
                 if tokens.len() == 0 {
-                    return Err(());
+                    return Err(wrong_arguments());
                 }
                 Command::KnownCommand(id, tokens[0].to_string())
             },
@@ -106,12 +187,16 @@ impl std::str::FromStr for Command {
                 Command::Quit(id)
             },
             "boardsize" => {
+                if tokens.len() == 0 {
+                    return Err(wrong_arguments());
+                }
+
                 let boardsize: Result<Integer, ParseIntError> = tokens[0].parse();
 
                 let boardsize = match boardsize {
                     Ok(size) => size,
                     Err(_) => {
-                        return Err(())
+                        return Err(ParseError::InvalidInteger(tokens[0].to_string()))
                     },
                 };
                 Command::BoardSize(id, boardsize)
@@ -121,30 +206,24 @@ impl std::str::FromStr for Command {
             },
             "komi" => {
                 if tokens.len() == 0 {
-                    return Err(());
+                    return Err(wrong_arguments());
                 }
 
                 let new_komi: Result<Float, ParseFloatError> = tokens[0].parse();
                 let new_komi = match new_komi {
                     Ok(komi) => komi,
-                    Err(_) => return Err(()),
+                    Err(_) => return Err(ParseError::InvalidFloat(tokens[0].to_string())),
                 };
 
                 Command::Komi(id, new_komi)
             },
             "play" => {
                 if tokens.len() != 2 {
-                    return Err(());
+                    return Err(wrong_arguments());
                 }
 
-                let color: Color = match tokens[0].parse() {
-                    Ok(c) => c,
-                    Err(_) => return Err(()),
-                };
-                let vertex: Vertex = match tokens[1].parse() {
-                    Ok(v) => v,
-                    Err(_) => return Err(()),
-                };
+                let color: Color = tokens[0].parse()?;
+                let vertex: Vertex = tokens[1].parse()?;
 
                 Command::Play(id, Move {
                     color: color,
@@ -153,19 +232,131 @@ impl std::str::FromStr for Command {
             },
             "genmove" => {
                 if tokens.len() != 1 {
-                    return Err(());
+                    return Err(wrong_arguments());
+                }
+
+                Command::GenMove(id, tokens[0].parse()?)
+            },
+            "reg_genmove" => {
+                if tokens.len() != 1 {
+                    return Err(wrong_arguments());
+                }
+
+                Command::RegGenMove(id, tokens[0].parse()?)
+            },
+            "final_score" => {
+                Command::FinalScore(id)
+            },
+            "undo" => {
+                if !tokens.is_empty() {
+                    return Err(wrong_arguments());
+                }
+
+                Command::Undo(id)
+            },
+            "showboard" => {
+                if !tokens.is_empty() {
+                    return Err(wrong_arguments());
+                }
+
+                Command::ShowBoard(id)
+            },
+            "final_status_list" => {
+                if tokens.len() != 1 {
+                    return Err(wrong_arguments());
+                }
+
+                Command::FinalStatusList(id, tokens[0].parse()?)
+            },
+            "fixed_handicap" | "place_free_handicap" => {
+                if tokens.len() != 1 {
+                    return Err(wrong_arguments());
                 }
 
-                let color: Result<Color, ()> = tokens[0].parse();
-                let color = match color{
-                    Ok(color) => color,
-                    Err(_) => return Err(()),
+                let stones = integer(tokens[0])?;
+
+                match command_token {
+                    "fixed_handicap" => Command::FixedHandicap(id, stones),
+                    _ => Command::PlaceFreeHandicap(id, stones),
+                }
+            },
+            "set_free_handicap" => {
+                if tokens.len() == 0 {
+                    return Err(wrong_arguments());
+                }
+
+                let mut vertices: Vec<Vertex> = Vec::new();
+                for token in tokens {
+                    vertices.push(token.parse()?);
+                }
+
+                Command::SetFreeHandicap(id, vertices)
+            },
+            "loadsgf" => {
+                let move_number = match tokens.len() {
+                    1 => None,
+                    2 => Some(integer(tokens[1])?),
+                    _ => return Err(wrong_arguments()),
+                };
+
+                Command::LoadSgf(id, tokens[0].to_string(), move_number)
+            },
+            "time_settings" => {
+                if tokens.len() != 3 {
+                    return Err(wrong_arguments());
+                }
+
+                Command::TimeSettings(id, integer(tokens[0])?, integer(tokens[1])?, integer(tokens[2])?)
+            },
+            "time_left" => {
+                if tokens.len() != 3 {
+                    return Err(wrong_arguments());
+                }
+
+                Command::TimeLeft(id, tokens[0].parse()?, integer(tokens[1])?, integer(tokens[2])?)
+            },
+            "kgs-time_settings" => {
+                let numbers = match tokens.first() {
+                    None => return Err(wrong_arguments()),
+                    Some(&"none") => 0,
+                    Some(&"absolute") => 1,
+                    Some(_) => 3,
+                };
+
+                if tokens.len() != numbers + 1 {
+                    return Err(wrong_arguments());
+                }
+
+                let time_system = match tokens[0] {
+                    "none" => KgsTimeSystem::None,
+                    "absolute" => KgsTimeSystem::Absolute(integer(tokens[1])?),
+                    "byoyomi" => KgsTimeSystem::ByoYomi(integer(tokens[1])?, integer(tokens[2])?, integer(tokens[3])?),
+                    "canadian" => KgsTimeSystem::Canadian(integer(tokens[1])?, integer(tokens[2])?, integer(tokens[3])?),
+                    _ => return Err(ParseError::InvalidTimeSystem(tokens[0].to_string())),
                 };
 
-                Command::GenMove(id, color)
+                Command::KgsTimeSettings(id, time_system)
+            },
+            "kgs-genmove_cleanup" => {
+                if tokens.len() != 1 {
+                    return Err(wrong_arguments());
+                }
+
+                Command::KgsGenMoveCleanup(id, tokens[0].parse()?)
             },
             _ => {
-                return Err(())
+                let is_name = command_token.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && command_token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+
+                if !is_name {
+                    return Err(ParseError::UnknownCommand(command_token.to_string()));
+                }
+
+                Command::Custom {
+                    id,
+                    name: command_token.to_string(),
+                    args: tokens.iter().map(|token| token.to_string()).collect(),
+                }
             },
         };
 
@@ -286,24 +477,171 @@ impl std::string::ToString for Command {
                     },
                 }
             },
+            Self::RegGenMove(id, color) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "reg_genmove", color.to_string())
+                    },
+                    None => {
+                        format!("{} {}", "reg_genmove", color.to_string())
+                    },
+                }
+            },
+            Self::FinalScore(id) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {}", id, "final_score")
+                    },
+                    None => {
+                        "final_score".to_string()
+                    },
+                }
+            },
+            Self::Undo(id) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {}", id, "undo")
+                    },
+                    None => {
+                        "undo".to_string()
+                    },
+                }
+            },
+            Self::ShowBoard(id) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {}", id, "showboard")
+                    },
+                    None => {
+                        "showboard".to_string()
+                    },
+                }
+            },
+            Self::FinalStatusList(id, status) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "final_status_list", status.to_string())
+                    },
+                    None => {
+                        format!("{} {}", "final_status_list", status.to_string())
+                    },
+                }
+            },
+            Self::FixedHandicap(id, stones) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "fixed_handicap", stones)
+                    },
+                    None => {
+                        format!("{} {}", "fixed_handicap", stones)
+                    },
+                }
+            },
+            Self::PlaceFreeHandicap(id, stones) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "place_free_handicap", stones)
+                    },
+                    None => {
+                        format!("{} {}", "place_free_handicap", stones)
+                    },
+                }
+            },
+            Self::SetFreeHandicap(id, vertices) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "set_free_handicap", vertices_to_string(vertices))
+                    },
+                    None => {
+                        format!("{} {}", "set_free_handicap", vertices_to_string(vertices))
+                    },
+                }
+            },
+            Self::LoadSgf(id, file, move_number) => {
+                let arguments = match move_number {
+                    Some(move_number) => format!("{} {}", file, move_number),
+                    None => file.to_string(),
+                };
+
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "loadsgf", arguments)
+                    },
+                    None => {
+                        format!("{} {}", "loadsgf", arguments)
+                    },
+                }
+            },
+            Self::TimeSettings(id, main_time, byo_yomi_time, byo_yomi_stones) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {} {} {}", id, "time_settings", main_time, byo_yomi_time, byo_yomi_stones)
+                    },
+                    None => {
+                        format!("{} {} {} {}", "time_settings", main_time, byo_yomi_time, byo_yomi_stones)
+                    },
+                }
+            },
+            Self::TimeLeft(id, color, time, stones) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {} {} {}", id, "time_left", color.to_string(), time, stones)
+                    },
+                    None => {
+                        format!("{} {} {} {}", "time_left", color.to_string(), time, stones)
+                    },
+                }
+            },
+            Self::KgsTimeSettings(id, time_system) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "kgs-time_settings", time_system.to_string())
+                    },
+                    None => {
+                        format!("{} {}", "kgs-time_settings", time_system.to_string())
+                    },
+                }
+            },
+            Self::KgsGenMoveCleanup(id, color) => {
+                match id {
+                    Some(id) => {
+                        format!("{} {} {}", id, "kgs-genmove_cleanup", color.to_string())
+                    },
+                    None => {
+                        format!("{} {}", "kgs-genmove_cleanup", color.to_string())
+                    },
+                }
+            },
+            Self::Custom { id, name, args } => {
+                let mut tokens: Vec<String> = Vec::new();
+
+                if let Some(id) = id {
+                    tokens.push(id.to_string());
+                }
+                tokens.push(name.to_string());
+                tokens.extend(args.iter().cloned());
+
+                tokens.join(" ")
+            },
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Color {
     White,
     Black,
 }
 
 impl std::str::FromStr for Color {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
         let lower = command.to_lowercase();
 
         match lower.as_str() {
             "b" | "black" => Ok(Color::Black),
             "w" | "white" => Ok(Color::White),
-            _ => return Err(()),
+            _ => return Err(ParseError::InvalidColor(command.to_string())),
         }
     }
 }
@@ -317,6 +655,7 @@ impl std::string::ToString for Color {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Vertex {
     Pass,
     Coordinate(char, Integer),
@@ -340,6 +679,24 @@ impl Vertex {
         }
     }
 
+    /// Parses the vertex and checks it is on the board of the size.
+    pub fn parse_on_board(text: &str, size: u8) -> Result<Vertex, ParseError> {
+        let vertex: Vertex = text.parse()?;
+
+        match vertex.is_on_board(size) {
+            true => Ok(vertex),
+            false => Err(ParseError::InvalidVertex(text.to_string())),
+        }
+    }
+
+    /// Whether the vertex is on the board of the size, the pass is on every board.
+    pub fn is_on_board(&self, size: u8) -> bool {
+        match self.to_number() {
+            Ok((alphabet, digit)) => alphabet < size && digit < size,
+            Err(_) => true,
+        }
+    }
+
     pub fn from_number(alphabet: u8, digit: u8) -> Vertex {
         Vertex::Coordinate(
             ('A' as u8 + alphabet + match alphabet >= ('I' as u8 - 'A' as u8) {
@@ -365,33 +722,39 @@ impl std::string::ToString for Vertex {
 }
 
 impl std::str::FromStr for Vertex {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
-        if command == "pass" {
+        if command.eq_ignore_ascii_case("pass") {
             return Ok(Vertex::Pass);
         }
 
-        let mut command:Vec<char> = command.chars().collect();
+        let invalid = || ParseError::InvalidVertex(command.to_string());
+        let mut chars = command.chars();
 
-        let letter = command[0];
+        let letter = match chars.next() {
+            Some(letter) => letter,
+            None => return Err(invalid()),
+        };
         if !letter.is_ascii_alphabetic() || letter == 'i'  || letter == 'I' {
-            return Err(());
+            return Err(invalid());
         }
 
-        command.remove(0);
-        if command.len() == 0 || command.len() >= 3 {
-            return Err(());
+        let number = chars.as_str();
+        if number.is_empty() || number.len() >= 3 || !number.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
         }
 
-        let number: String = command.into_iter().collect();
-
         let number: Integer = match number.parse() {
             Ok(num) => num,
             Err(_) => {
-                return Err(());
+                return Err(invalid());
             }
         };
 
+        if number < 1 || number > MAX_BOARD_SIZE as Integer {
+            return Err(invalid());
+        }
+
         return Ok(Vertex::Coordinate(letter, number));
     }
 }
@@ -408,23 +771,16 @@ impl std::string::ToString for Move {
 }
 
 impl std::str::FromStr for Move {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
         let tokens: Vec<&str> = command.split_ascii_whitespace().collect();
 
         if tokens.len() != 2 {
-            return Err(());
+            return Err(ParseError::InvalidMove(command.to_string()));
         }
 
-        let color: Color = match tokens[0].parse() {
-            Ok(c) => c,
-            Err(_) => return Err(()),
-        };
-
-        let vertex: Vertex = match tokens[1].parse() {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
+        let color: Color = tokens[0].parse()?;
+        let vertex: Vertex = tokens[1].parse()?;
 
         Ok(Move {
             color: color,
@@ -439,17 +795,17 @@ pub enum GenMoveResult {
 }
 
 impl std::str::FromStr for GenMoveResult {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
         if command == "resign" {
            return Ok(Self::Resign);
         }
 
-        let vertex: Result<Vertex, ()> = command.parse();
+        let vertex: Result<Vertex, ParseError> = command.parse();
 
         match vertex {
-            Err(_) => {
-                return Err(());
+            Err(error) => {
+                return Err(error);
             },
             Ok(vertex) => {
                 Ok(Self::Move(vertex))
@@ -469,6 +825,99 @@ impl std::string::ToString for GenMoveResult {
     }
 }
 
+/// The result of the game counted by the engine, "0" is a draw.
+pub enum Score {
+    Black(Float),
+    White(Float),
+    Jigo,
+}
+
+impl std::str::FromStr for Score {
+    type Err = ParseError;
+    fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
+        if command == "0" {
+            return Ok(Score::Jigo);
+        }
+
+        let winner = match command.get(0..2) {
+            Some("B+") | Some("b+") => Color::Black,
+            Some("W+") | Some("w+") => Color::White,
+            _ => return Err(ParseError::InvalidScore(command.to_string())),
+        };
+
+        let points: Float = match command[2..].parse() {
+            Ok(points) => points,
+            Err(_) => return Err(ParseError::InvalidScore(command.to_string())),
+        };
+
+        match winner {
+            Color::Black => Ok(Score::Black(points)),
+            Color::White => Ok(Score::White(points)),
+        }
+    }
+}
+
+impl std::string::ToString for Score {
+    fn to_string(&self) -> String {
+        match self {
+            Score::Black(points) => format!("B+{}", points),
+            Score::White(points) => format!("W+{}", points),
+            Score::Jigo => "0".to_string(),
+        }
+    }
+}
+
+/// The status of the stones for `final_status_list`.
+pub enum FinalStatus {
+    Alive,
+    Dead,
+    Seki,
+}
+
+impl std::str::FromStr for FinalStatus {
+    type Err = ParseError;
+    fn from_str(command: &str) -> std::result::Result<Self, <Self as std::str::FromStr>::Err> {
+        match command {
+            "alive" => Ok(FinalStatus::Alive),
+            "dead" => Ok(FinalStatus::Dead),
+            "seki" => Ok(FinalStatus::Seki),
+            _ => Err(ParseError::InvalidStatus(command.to_string())),
+        }
+    }
+}
+
+impl std::string::ToString for FinalStatus {
+    fn to_string(&self) -> String {
+        match self {
+            FinalStatus::Alive => "alive".to_string(),
+            FinalStatus::Dead => "dead".to_string(),
+            FinalStatus::Seki => "seki".to_string(),
+        }
+    }
+}
+
+/// The time system of `kgs-time_settings`, the times are in seconds.
+pub enum KgsTimeSystem {
+    None,
+    /// The main time.
+    Absolute(Integer),
+    /// The main time, the period time and the periods.
+    ByoYomi(Integer, Integer, Integer),
+    /// The main time, the period time and the stones in the period.
+    Canadian(Integer, Integer, Integer),
+}
+
+impl std::string::ToString for KgsTimeSystem {
+    fn to_string(&self) -> String {
+        match self {
+            KgsTimeSystem::None => "none".to_string(),
+            KgsTimeSystem::Absolute(main_time) => format!("absolute {}", main_time),
+            KgsTimeSystem::ByoYomi(main_time, period_time, periods) => format!("byoyomi {} {} {}", main_time, period_time, periods),
+            KgsTimeSystem::Canadian(main_time, period_time, stones) => format!("canadian {} {} {}", main_time, period_time, stones),
+        }
+    }
+}
+
 pub enum CommandResult {
     ProtocolVersion(Option<Id>, Integer),
     Name(Option<Id>, String),
@@ -480,90 +929,137 @@ pub enum CommandResult {
     ClearBoard(Option<Id>),
     Komi(Option<Id>),
     Play(Option<Id>),
-    GenMove(Option<Id>, GenMoveResult)
+    GenMove(Option<Id>, GenMoveResult),
+    RegGenMove(Option<Id>, GenMoveResult),
+    FinalScore(Option<Id>, Score),
+    Undo(Option<Id>),
+    /// The lines of the board drawn by the engine.
+    ShowBoard(Option<Id>, Vec<String>),
+    FinalStatusList(Option<Id>, Vec<Vertex>),
+    FixedHandicap(Option<Id>, Vec<Vertex>),
+    PlaceFreeHandicap(Option<Id>, Vec<Vertex>),
+    SetFreeHandicap(Option<Id>),
+    LoadSgf(Option<Id>),
+    TimeSettings(Option<Id>),
+    TimeLeft(Option<Id>),
+    KgsTimeSettings(Option<Id>),
+    KgsGenMoveCleanup(Option<Id>, GenMoveResult),
+    /// The response of the custom command as it is, the lines are joined by the newline.
+    Raw(Option<Id>, String),
 }
 
 impl Command {
-    pub fn parse_result(&self, lines: &str) -> Result<CommandResult, ()> {
-        let mut lines = lines.lines();
+    pub fn id(&self) -> Option<Id> {
+        let id = match self {
+            Self::ProtocolVersion(id) | Self::Name(id) | Self::Version(id) | Self::ListCommands(id) | Self::Quit(id)
+                | Self::ClearBoard(id) | Self::FinalScore(id) | Self::Undo(id) | Self::ShowBoard(id) => id,
+            Self::KnownCommand(id, _) | Self::BoardSize(id, _) | Self::Komi(id, _) | Self::Play(id, _)
+                | Self::GenMove(id, _) | Self::RegGenMove(id, _) | Self::FinalStatusList(id, _)
+                | Self::FixedHandicap(id, _) | Self::PlaceFreeHandicap(id, _) | Self::SetFreeHandicap(id, _)
+                | Self::KgsTimeSettings(id, _) | Self::KgsGenMoveCleanup(id, _) => id,
+            Self::LoadSgf(id, _, _) => id,
+            Self::TimeSettings(id, _, _, _) | Self::TimeLeft(id, _, _, _) => id,
+            Self::Custom { id, .. } => id,
+        };
 
-        let first_line = lines.next();
+        *id
+    }
 
-        if let None = first_line {
-            return Err(());
+    /// The name of the command, such as `genmove`.
+    pub fn name(&self) -> String {
+        if let Self::Custom { name, .. } = self {
+            return name.clone();
         }
 
-        /* Start to get id */
-        let first_line = first_line.unwrap();
-        let space_idx = match first_line.find(' ') {
-            Some(idx) => idx,
-            None => return Err(()),
-        };
+        let text = self.to_string();
+        let mut tokens = text.split_ascii_whitespace();
 
-        let id = {
-            let header = &first_line[0..space_idx];
+        if self.id().is_some() {
+            tokens.next();
+        }
 
-            match header.find('=') {
-                Some(idx) => match idx {
-                    0 => {},
-                    _ => return Err(()),
-                },
-                None => return Err(()),
-            };
+        tokens.next().unwrap_or("").to_string()
+    }
 
-            let header = &first_line[1..space_idx];
+    /// Parses the response of the command, a failure response `?id message` is the `ResponseError::Failure`.
+    pub fn parse_result(&self, lines: &str) -> Result<CommandResult, ResponseError> {
+        let mut lines = lines.lines();
 
-            match header.len() {
-                0 => None,
-                _ => {
-                    let id: Result<Integer, ParseIntError> = header.parse();
-                    match id {
-                        Err(_) => return Err(()),
-                        Ok(number) => Some(number),
-                    }
+        let first_line = match lines.next() {
+            Some(line) => line,
+            None => return Err(ResponseError::Empty),
+        };
+
+        /* Start to get id */
+        let header_end = first_line.find(char::is_whitespace).unwrap_or(first_line.len());
+        let header = &first_line[0..header_end];
+
+        let is_success = match header.chars().next() {
+            Some('=') => true,
+            Some('?') => false,
+            _ => return Err(ResponseError::Malformed(first_line.to_string())),
+        };
+
+        let id = match header.len() {
+            1 => None,
+            _ => {
+                let id: Result<Id, ParseIntError> = header[1..].parse();
+                match id {
+                    Err(_) => return Err(ResponseError::Malformed(first_line.to_string())),
+                    Ok(number) => Some(number),
                 }
             }
         };
 
-        let lines: Vec<String> = (first_line[(space_idx+1)..]).lines().chain(lines)
+        /* The board of showboard is aligned by the spaces, only the one separating the header is dropped */
+        let rest = &first_line[header_end..];
+        let rest = rest.strip_prefix(char::is_whitespace).unwrap_or(rest);
+        let raw_lines: Vec<String> = rest.lines().chain(lines)
+            .filter(|x| x.trim().len() > 0)
+            .map(|x| x.trim_end().to_string())
+            .collect();
+
+        let lines: Vec<String> = raw_lines.iter()
             .map(|x| x.trim().to_string())
-            .filter(|x| x.len() > 0)
             .collect();
 
+        if !is_success {
+            return Err(ResponseError::Failure(id, lines.join("\n")));
+        }
+
+        /* The number of the lines of the response is checked */
+        let expect = |count: usize| -> Result<(), ResponseError> {
+            match lines.len() == count {
+                true => Ok(()),
+                false => Err(ResponseError::Malformed(lines.join("\n"))),
+            }
+        };
+
         let result = match self {
             Self::ProtocolVersion(_id) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(1)?;
+
                 let version: Result<Integer, ParseIntError> = lines[0].parse();
-                if let Err(_) = version {
-                    return Err(());
+                match version {
+                    Err(_) => return Err(ParseError::InvalidInteger(lines[0].to_string()).into()),
+                    Ok(version) => CommandResult::ProtocolVersion(id, version),
                 }
-
-                CommandResult::ProtocolVersion(id, version.unwrap())
             },
             Self::Name(_id) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
-
+                expect(1)?;
                 CommandResult::Name(id, lines[0].to_string())
             },
             Self::Version(_id) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(1)?;
                 CommandResult::Version(id, lines[0].to_string())
             },
             Self::KnownCommand(_id, _command) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(1)?;
 
                 let result: Result<bool, ParseBoolError> = lines[0].parse();
 
                 match result {
-                    Err(_) => return Err(()),
+                    Err(_) => return Err(ParseError::InvalidBool(lines[0].to_string()).into()),
                     Ok(known) => CommandResult::KnownCommand(id, known),
                 }
             },
@@ -571,45 +1067,79 @@ impl Command {
                 CommandResult::ListCommands(id, lines)
             },
             Self::Quit(_id) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(0)?;
                 CommandResult::Quit(id)
             },
             Self::BoardSize(_id, _size) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(0)?;
                 CommandResult::BoardSize(id)
             },
             Self::ClearBoard(_id) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(0)?;
                 CommandResult::ClearBoard(id)
             },
             Self::Komi(_id, _komi) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(0)?;
                 CommandResult::Komi(id)
             },
             Self::Play(_id, _mov) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
+                expect(0)?;
                 CommandResult::Play(id)
             },
             Self::GenMove(_id, _color) => {
-                if lines.len() != 1 {
-                    return Err(());
-                }
-                let result: Result<GenMoveResult, ()> = lines[0].parse();
-
-                match result {
-                    Err(_) => return Err(()),
-                    Ok(mov) => CommandResult::GenMove(id, mov),
-                }
+                expect(1)?;
+                CommandResult::GenMove(id, lines[0].parse()?)
+            },
+            Self::RegGenMove(_id, _color) => {
+                expect(1)?;
+                CommandResult::RegGenMove(id, lines[0].parse()?)
+            },
+            Self::FinalScore(_id) => {
+                expect(1)?;
+                CommandResult::FinalScore(id, lines[0].parse()?)
+            },
+            Self::Undo(_id) => {
+                expect(0)?;
+                CommandResult::Undo(id)
+            },
+            Self::ShowBoard(_id) => {
+                CommandResult::ShowBoard(id, raw_lines)
+            },
+            Self::FinalStatusList(_id, _status) => {
+                CommandResult::FinalStatusList(id, parse_vertices(&lines)?)
+            },
+            Self::FixedHandicap(_id, _stones) => {
+                CommandResult::FixedHandicap(id, parse_vertices(&lines)?)
+            },
+            Self::PlaceFreeHandicap(_id, _stones) => {
+                CommandResult::PlaceFreeHandicap(id, parse_vertices(&lines)?)
+            },
+            Self::SetFreeHandicap(_id, _vertices) => {
+                expect(0)?;
+                CommandResult::SetFreeHandicap(id)
+            },
+            Self::LoadSgf(_id, _file, _move_number) => {
+                /* Some engines reply the color to move */
+                CommandResult::LoadSgf(id)
+            },
+            Self::TimeSettings(_id, _main_time, _byo_yomi_time, _byo_yomi_stones) => {
+                expect(0)?;
+                CommandResult::TimeSettings(id)
+            },
+            Self::TimeLeft(_id, _color, _time, _stones) => {
+                expect(0)?;
+                CommandResult::TimeLeft(id)
+            },
+            Self::KgsTimeSettings(_id, _time_system) => {
+                expect(0)?;
+                CommandResult::KgsTimeSettings(id)
+            },
+            Self::KgsGenMoveCleanup(_id, _color) => {
+                expect(1)?;
+                CommandResult::KgsGenMoveCleanup(id, lines[0].parse()?)
+            },
+            Self::Custom { .. } => {
+                CommandResult::Raw(id, raw_lines.join("\n"))
             },
         };
 
@@ -617,6 +1147,29 @@ impl Command {
     }
 }
 
+/// The vertices separated by the spaces and the lines.
+fn parse_vertices(lines: &[String]) -> Result<Vec<Vertex>, ParseError> {
+    let mut vertices: Vec<Vertex> = Vec::new();
+
+    for token in lines.iter().flat_map(|line| line.split_ascii_whitespace()) {
+        vertices.push(token.parse()?);
+    }
+
+    Ok(vertices)
+}
+
+fn vertices_to_string(vertices: &[Vertex]) -> String {
+    vertices.iter().map(|vertex| vertex.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+/// The failure response `?id message` of the engine.
+pub fn failure_response(id: Option<Id>, message: &str) -> String {
+    match id {
+        Some(id) => format!("?{} {}", id, message),
+        None => format!("? {}", message),
+    }
+}
+
 impl std::string::ToString for CommandResult {
     fn to_string(&self) -> String {
         match self {
@@ -744,6 +1297,171 @@ impl std::string::ToString for CommandResult {
                     },
                 }
             },
+            Self::RegGenMove(id, move_result) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, move_result.to_string())
+                    },
+                    None => {
+                        format!("= {}", move_result.to_string())
+                    },
+                }
+            },
+            Self::FinalScore(id, score) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, score.to_string())
+                    },
+                    None => {
+                        format!("= {}", score.to_string())
+                    },
+                }
+            },
+            Self::Undo(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::ShowBoard(id, board) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, board.join("\n"))
+                    },
+                    None => {
+                        format!("= {}", board.join("\n"))
+                    },
+                }
+            },
+            Self::FinalStatusList(id, vertices) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, vertices_to_string(vertices))
+                    },
+                    None => {
+                        format!("= {}", vertices_to_string(vertices))
+                    },
+                }
+            },
+            Self::FixedHandicap(id, vertices) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, vertices_to_string(vertices))
+                    },
+                    None => {
+                        format!("= {}", vertices_to_string(vertices))
+                    },
+                }
+            },
+            Self::PlaceFreeHandicap(id, vertices) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, vertices_to_string(vertices))
+                    },
+                    None => {
+                        format!("= {}", vertices_to_string(vertices))
+                    },
+                }
+            },
+            Self::SetFreeHandicap(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::LoadSgf(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::TimeSettings(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::TimeLeft(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::KgsTimeSettings(id) => {
+                match id {
+                    Some(id) => {
+                        format!("={} ", id)
+                    },
+                    None => {
+                        "= ".to_string()
+                    },
+                }
+            },
+            Self::KgsGenMoveCleanup(id, move_result) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, move_result.to_string())
+                    },
+                    None => {
+                        format!("= {}", move_result.to_string())
+                    },
+                }
+            },
+            Self::Raw(id, response) => {
+                match id {
+                    Some(id) => {
+                        format!("={} {}", id, response)
+                    },
+                    None => {
+                        format!("= {}", response)
+                    },
+                }
+            },
+        }
+    }
+}
+
+impl CommandResult {
+    pub fn id(&self) -> Option<Id> {
+        let id = match self {
+            Self::ProtocolVersion(id, _) | Self::Name(id, _) | Self::Version(id, _) | Self::KnownCommand(id, _)
+                | Self::ListCommands(id, _) | Self::GenMove(id, _) | Self::RegGenMove(id, _) | Self::FinalScore(id, _)
+                | Self::ShowBoard(id, _) | Self::FinalStatusList(id, _) | Self::FixedHandicap(id, _)
+                | Self::PlaceFreeHandicap(id, _) | Self::KgsGenMoveCleanup(id, _) | Self::Raw(id, _) => id,
+            Self::Quit(id) | Self::BoardSize(id) | Self::ClearBoard(id) | Self::Komi(id) | Self::Play(id) | Self::Undo(id)
+                | Self::SetFreeHandicap(id) | Self::LoadSgf(id) | Self::TimeSettings(id) | Self::TimeLeft(id)
+                | Self::KgsTimeSettings(id) => id,
+        };
+
+        *id
+    }
+
+    /// Whether the command is listed in the result of `list_commands`, the features of the
+    /// engine are known by it.
+    pub fn lists(&self, command: &str) -> bool {
+        match self {
+            Self::ListCommands(_, commands) => commands.iter().any(|listed| listed == command),
+            _ => false,
         }
     }
 }
@@ -751,6 +1469,7 @@ impl std::string::ToString for CommandResult {
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     pub fn test_protocol_version() {
@@ -932,4 +1651,201 @@ mod test {
             Err(_) => assert!(false, "Parse command Error"),
         };
     }
+
+    #[test]
+    pub fn test_final_score() {
+        let command: Command = "3 final_score".parse().unwrap();
+
+        match command.parse_result("=3 W+12.5\n") {
+            Ok(CommandResult::FinalScore(Some(3), Score::White(points))) => assert_eq!(points, 12.5),
+            _ => assert!(false, "Parse result Error"),
+        };
+
+        match command.parse_result("=3 0\n") {
+            Ok(CommandResult::FinalScore(_, Score::Jigo)) => (),
+            _ => assert!(false, "Parse result Error"),
+        };
+
+        assert!(command.parse_result("=3 X+1\n").is_err());
+        assert_eq!(Score::Black(0.5).to_string(), "B+0.5");
+    }
+
+    #[test]
+    pub fn test_failure_response() {
+        let command: Command = "7 play b d4".parse().unwrap();
+
+        match command.parse_result("?7 illegal move\n") {
+            Err(ResponseError::Failure(id, message)) => {
+                assert_eq!(id, Some(7));
+                assert_eq!(message, "illegal move");
+            },
+            _ => assert!(false, "The failure is not parsed"),
+        };
+
+        assert!(command.parse_result("=7\n").is_ok());
+        assert_eq!(command.parse_result("illegal move\n").err(), Some(ResponseError::Malformed("illegal move".to_string())));
+        assert_eq!(command.parse_result("").err(), Some(ResponseError::Empty));
+
+        let command: Command = "genmove b".parse().unwrap();
+        assert_eq!(command.parse_result("= zz\n").err(), Some(ResponseError::Invalid(ParseError::InvalidVertex("zz".to_string()))));
+    }
+
+    #[test]
+    pub fn test_parse_error() {
+        assert_eq!("".parse::<Command>().err(), Some(ParseError::Empty));
+        assert_eq!("foo?".parse::<Command>().err(), Some(ParseError::UnknownCommand("foo?".to_string())));
+        assert_eq!("boardsize".parse::<Command>().err(), Some(ParseError::WrongArguments("boardsize".to_string())));
+        assert_eq!("undo 2".parse::<Command>().err(), Some(ParseError::WrongArguments("undo".to_string())));
+        assert_eq!("showboard all".parse::<Command>().err(), Some(ParseError::WrongArguments("showboard".to_string())));
+        assert_eq!("play red d4".parse::<Command>().err(), Some(ParseError::InvalidColor("red".to_string())));
+        assert_eq!("".parse::<Vertex>().err(), Some(ParseError::InvalidVertex("".to_string())));
+    }
+
+    #[test]
+    pub fn test_handicap() {
+        let command: Command = "fixed_handicap 3".parse().unwrap();
+
+        match command.parse_result("= D4 Q16\nD16\n") {
+            Ok(CommandResult::FixedHandicap(None, vertices)) => assert_eq!(vertices_to_string(&vertices), "D4 Q16 D16"),
+            _ => assert!(false, "Parse result Error"),
+        };
+
+        match "set_free_handicap D4 Q16".parse::<Command>() {
+            Ok(command) => assert_eq!(command.to_string(), "set_free_handicap D4 Q16"),
+            Err(_) => assert!(false, "Parse command Error"),
+        };
+
+        assert_eq!("set_free_handicap".parse::<Command>().err(), Some(ParseError::WrongArguments("set_free_handicap".to_string())));
+    }
+
+    #[test]
+    pub fn test_time_settings() {
+        match "5 kgs-time_settings byoyomi 600 30 5".parse::<Command>() {
+            Ok(command) => assert_eq!(command.to_string(), "5 kgs-time_settings byoyomi 600 30 5"),
+            Err(_) => assert!(false, "Parse command Error"),
+        };
+
+        match "time_left w 120 3".parse::<Command>() {
+            Ok(Command::TimeLeft(None, Color::White, time, stones)) => assert_eq!((time, stones), (120, 3)),
+            _ => assert!(false, "Parse command Error"),
+        };
+
+        assert!("kgs-time_settings absolute".parse::<Command>().is_err());
+        assert_eq!("kgs-time_settings fischer 1 2 3".parse::<Command>().err(), Some(ParseError::InvalidTimeSystem("fischer".to_string())));
+        assert_eq!("loadsgf game.sgf 12".parse::<Command>().unwrap().to_string(), "loadsgf game.sgf 12");
+    }
+
+    #[test]
+    pub fn test_show_board() {
+        let command: Command = "showboard".parse().unwrap();
+
+        match command.parse_result("= \n   A B\n 2 . .\n 1 X .\n") {
+            Ok(CommandResult::ShowBoard(None, board)) => assert_eq!(board, vec!["   A B", " 2 . .", " 1 X ."]),
+            _ => assert!(false, "Parse result Error"),
+        };
+    }
+
+    #[test]
+    pub fn test_custom() {
+        let command: Command = "3 kata-set-param maxVisits 100".parse().unwrap();
+
+        match &command {
+            Command::Custom { id, name, args } => {
+                assert_eq!(*id, Some(3));
+                assert_eq!(name, "kata-set-param");
+                assert_eq!(args, &vec!["maxVisits".to_string(), "100".to_string()]);
+            },
+            _ => assert!(false, "Incorrect command"),
+        };
+        assert_eq!(command.to_string(), "3 kata-set-param maxVisits 100");
+
+        let result = command.parse_result("=3  line 1\nline 2\n").unwrap();
+        assert_eq!(result.to_string(), "=3  line 1\nline 2");
+        match result {
+            CommandResult::Raw(Some(3), response) => assert_eq!(response, " line 1\nline 2"),
+            _ => assert!(false, "Parse result Error"),
+        };
+
+        assert!("play@ b d4".parse::<Command>().is_err());
+    }
+
+    #[test]
+    pub fn test_preprocess() {
+        assert_eq!(preprocess("1 play\tb d4\r # comment"), "1 play b d4 ");
+        assert_eq!(preprocess("na\x07me\x7f"), "name");
+
+        match "2\tgenmove\x00 w\r\n".parse::<Command>() {
+            Ok(Command::GenMove(Some(2), Color::White)) => (),
+            _ => assert!(false, "Incorrect command"),
+        };
+
+        assert_eq!("# only comment".parse::<Command>().err(), Some(ParseError::Empty));
+        assert_eq!("-1 name".parse::<Command>().err(), Some(ParseError::UnknownCommand("-1".to_string())));
+    }
+
+    #[test]
+    pub fn test_vertex() {
+        assert_eq!("PASS".parse::<Vertex>(), Ok(Vertex::Pass));
+        assert_eq!("Pass".parse::<Vertex>(), Ok(Vertex::Pass));
+        assert_eq!("z25".parse::<Vertex>(), Ok(Vertex::Coordinate('z', 25)));
+
+        for text in ["A", "A0", "A26", "A+1", "I5", "5A", "\u{e9}1", "A1\u{e9}"].iter() {
+            assert_eq!(text.parse::<Vertex>(), Err(ParseError::InvalidVertex(text.to_string())));
+        }
+
+        assert_eq!(Vertex::parse_on_board("T19", 19), Ok(Vertex::Coordinate('T', 19)));
+        assert!(Vertex::parse_on_board("U1", 19).is_err());
+        assert!(Vertex::parse_on_board("A20", 19).is_err());
+        assert_eq!(Vertex::parse_on_board("pass", 9), Ok(Vertex::Pass));
+    }
+
+    proptest! {
+        #[test]
+        fn test_vertex_round_trip(alphabet in 0..MAX_BOARD_SIZE, digit in 0..MAX_BOARD_SIZE, lower in any::<bool>()) {
+            let vertex = match Vertex::from_number(alphabet, digit) {
+                Vertex::Coordinate(letter, number) if lower => Vertex::Coordinate(letter.to_ascii_lowercase(), number),
+                vertex => vertex,
+            };
+
+            prop_assert_eq!(vertex.to_string().parse::<Vertex>(), Ok(vertex));
+            prop_assert_eq!(vertex.to_number(), Ok((alphabet, digit)));
+            prop_assert!(vertex.is_on_board(alphabet.max(digit) + 1));
+            prop_assert!(!vertex.is_on_board(alphabet.max(digit)));
+        }
+
+        #[test]
+        fn test_parse_no_panic(line in "\\PC*") {
+            let _ = line.parse::<Command>();
+            let _ = line.parse::<Vertex>();
+        }
+
+        #[test]
+        fn test_command_round_trip(id in proptest::option::of(0..10000i32), alphabet in 0..19u8, digit in 0..19u8) {
+            let command = Command::Play(id, Move {
+                color: Color::Black,
+                vertex: Vertex::from_number(alphabet, digit),
+            });
+
+            prop_assert_eq!(command.to_string().parse::<Command>().map(|command| command.to_string()), Ok(command.to_string()));
+        }
+    }
+
+    #[test]
+    pub fn test_ids_and_names() {
+        let command: Command = "5 kgs-genmove_cleanup b".parse().unwrap();
+
+        assert_eq!(command.name(), "kgs-genmove_cleanup");
+        assert_eq!(Command::Undo(None).name(), "undo");
+        assert_eq!(command.parse_result("=5 D4\n").unwrap().id(), Some(5));
+        assert_eq!(Command::Undo(None).parse_result("=\n").unwrap().id(), None);
+    }
+
+    #[test]
+    pub fn test_lists() {
+        let command: Command = "list_commands".parse().unwrap();
+        let result = command.parse_result("= name\nlz-analyze\n").unwrap();
+
+        assert!(result.lists("lz-analyze"));
+        assert!(!result.lists("kata-analyze"));
+    }
 }
@@ -0,0 +1,84 @@
+use crate::command::Id;
+
+/// The failure of parsing a command or a value of it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    UnknownCommand(String),
+    /// The command is given the wrong number of arguments.
+    WrongArguments(String),
+    InvalidInteger(String),
+    InvalidFloat(String),
+    InvalidBool(String),
+    InvalidColor(String),
+    InvalidVertex(String),
+    InvalidMove(String),
+    InvalidScore(String),
+    InvalidStatus(String),
+    InvalidTimeSystem(String),
+    /// The line of `lz-analyze` or `kata-analyze` is not understood.
+    InvalidAnalysis(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty command"),
+            ParseError::UnknownCommand(command) => write!(f, "unknown command: {}", command),
+            ParseError::WrongArguments(command) => write!(f, "wrong number of arguments for {}", command),
+            ParseError::InvalidInteger(value) => write!(f, "invalid integer: {}", value),
+            ParseError::InvalidFloat(value) => write!(f, "invalid float: {}", value),
+            ParseError::InvalidBool(value) => write!(f, "invalid boolean: {}", value),
+            ParseError::InvalidColor(value) => write!(f, "invalid color: {}", value),
+            ParseError::InvalidVertex(value) => write!(f, "invalid vertex: {}", value),
+            ParseError::InvalidMove(value) => write!(f, "invalid move: {}", value),
+            ParseError::InvalidScore(value) => write!(f, "invalid score: {}", value),
+            ParseError::InvalidStatus(value) => write!(f, "invalid status: {}", value),
+            ParseError::InvalidTimeSystem(value) => write!(f, "invalid time system: {}", value),
+            ParseError::InvalidAnalysis(line) => write!(f, "invalid analysis: {}", line),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The failure of getting the result of a command from the response.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResponseError {
+    /// The engine replies the failure response `?id message`.
+    Failure(Option<Id>, String),
+    Empty,
+    /// The response is not started with `=` or `?`, or has the wrong number of lines.
+    Malformed(String),
+    /// The success response carries a value which is not understood.
+    Invalid(ParseError),
+    /// The connection to the engine is closed before the response.
+    Closed,
+}
+
+impl std::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponseError::Failure(_, message) => write!(f, "engine failure: {}", message),
+            ResponseError::Empty => write!(f, "empty response"),
+            ResponseError::Malformed(response) => write!(f, "malformed response: {}", response),
+            ResponseError::Invalid(error) => write!(f, "invalid response, {}", error),
+            ResponseError::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ResponseError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for ResponseError {
+    fn from(error: ParseError) -> ResponseError {
+        ResponseError::Invalid(error)
+    }
+}
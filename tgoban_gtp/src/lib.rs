@@ -1,6 +1,10 @@
+mod analysis;
 mod command;
+mod error;
 
+pub use analysis::*;
 pub use command::*;
+pub use error::*;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,110 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use tgoban_gtp::{Command, ParseError, ResponseError, failure_response, preprocess};
+use tgoban_ws_adapter::arbitator::adaptor::Adaptor;
+
+/// Answers the GTP commands of the reader by the adaptor until quit or the end of the input.
+pub async fn serve<R, W>(reader: R, mut writer: W, adaptor: &mut (impl Adaptor + Unpin + Send)) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+
+    while let Some(line) = lines.next_line().await? {
+        /* The comments and the empty lines are ignored */
+        let line = preprocess(&line);
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, is_quit) = respond(&line, adaptor).await;
+
+        writer.write_all(format!("{}\n\n", response).as_bytes()).await?;
+        writer.flush().await?;
+
+        if is_quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The response of the command line and whether the command is quit.
+async fn respond(line: &str, adaptor: &mut (impl Adaptor + Unpin + Send)) -> (String, bool) {
+    let command: Command = match line.parse() {
+        Ok(command) => command,
+        Err(error) => {
+            /* The id is kept even if the command is not understood */
+            let id = line.split_ascii_whitespace().next().and_then(|token| token.parse().ok());
+            let message = match error {
+                ParseError::UnknownCommand(_) => "unknown command",
+                _ => "syntax error",
+            };
+
+            return (failure_response(id, message), false);
+        },
+    };
+
+    let id = command.id();
+    let is_quit = matches!(command, Command::Quit(_));
+
+    match adaptor.send_command(command).await {
+        Ok(result) => (result.to_string().trim_end_matches('\n').to_string(), is_quit),
+        Err(ResponseError::Failure(_, message)) => (failure_response(id, &message), false),
+        Err(error) => (failure_response(id, &error.to_string()), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgoban_bot::{Level, new_bot};
+    use tgoban_ws_adapter::arbitator::bot_adaptor::BotAdaptor;
+
+    async fn run(input: &str) -> String {
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 19, 6.5);
+        let mut output: Vec<u8> = Vec::new();
+
+        serve(tokio::io::BufReader::new(input.as_bytes()), &mut output, &mut adaptor).await.unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let output = run("1 boardsize 9\n# comment\n\n2 play\tb e5\r\n3 play w e5\n4 genmove w\n5 foo\nbar 1\n6 undo\n7 quit\n8 name\n").await;
+        let responses: Vec<&str> = output.split("\n\n").collect();
+
+        assert_eq!(responses[0], "=1 ");
+        assert_eq!(responses[1], "=2 ");
+        assert_eq!(responses[2], "?3 illegal move");
+        assert!(responses[3].starts_with("=4 "));
+        assert_eq!(responses[4], "?5 unknown command");
+        assert_eq!(responses[5], "? unknown command");
+        assert_eq!(responses[6], "=6 ");
+        assert_eq!(responses[7], "=7 ");
+
+        /* Nothing is answered after quit */
+        assert_eq!(responses.len(), 9);
+        assert_eq!(responses[8], "");
+    }
+
+    #[tokio::test]
+    async fn test_show_board() {
+        let output = run("boardsize 5\nplay b c3\nshowboard\n").await;
+
+        assert!(output.ends_with("= .....\n.....\n..X..\n.....\n.....\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_gogui() {
+        let output = run("boardsize 3\nplay b a1\n1 gogui-analyze_commands\n2 tgoban-liberties\n").await;
+        let responses: Vec<&str> = output.split("\n\n").collect();
+
+        assert!(responses[2].starts_with("=1 sboard/Liberties/tgoban-liberties\n"));
+        assert_eq!(responses[3], "=2 \"\" \"\" \"\"\n\"\" \"\" \"\"\n2 \"\" \"\"");
+    }
+}
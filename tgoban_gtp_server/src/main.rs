@@ -0,0 +1,83 @@
+use tokio::io::BufReader;
+
+use tgoban_bot::{Bot, Level, new_bot};
+use tgoban_gtp_server::serve;
+use tgoban_mcts::{Config, MctsBot};
+use tgoban_ws_adapter::arbitator::bot_adaptor::BotAdaptor;
+use tgoban_ws_adapter::{BOARD_SIZE, KOMI_DEFAULT};
+
+const USAGE: &str = "Usage: tgoban_gtp_server [--bot random|greedy|mcts] [--seed N] [--playouts N] [--threads N]";
+
+struct Options {
+    bot: String,
+    seed: u64,
+    playouts: u32,
+    threads: usize,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        bot: "mcts".to_string(),
+        seed: 7,
+        playouts: Config::default().playouts.unwrap_or(10000),
+        threads: 1,
+    };
+
+    while let Some(arg) = args.next() {
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+
+        let is_valid = match arg.as_str() {
+            "--bot" => {
+                options.bot = value.clone();
+                true
+            },
+            "--seed" => value.parse().map(|seed| options.seed = seed).is_ok(),
+            "--playouts" => value.parse().map(|playouts| options.playouts = playouts).is_ok(),
+            "--threads" => value.parse().map(|threads| options.threads = threads).is_ok(),
+            _ => return Err(format!("Unknown option {}", arg)),
+        };
+
+        if !is_valid {
+            return Err(format!("Invalid value {} of {}", value, arg));
+        }
+    }
+
+    Ok(options)
+}
+
+fn new_move_generator(options: &Options) -> Result<Box<dyn Bot + Send>, String> {
+    if options.bot == "mcts" {
+        return Ok(Box::new(MctsBot::new(Config {
+            playouts: Some(options.playouts),
+            threads: options.threads,
+            seed: options.seed,
+            ..Config::default()
+        })));
+    }
+
+    match options.bot.parse::<Level>() {
+        Ok(level) => Ok(new_bot(level, options.seed)),
+        Err(_) => Err(format!("Unknown bot {}", options.bot)),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let bot = match parse_options(std::env::args().skip(1)).and_then(|options| new_move_generator(&options)) {
+        Ok(bot) => bot,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let mut adaptor = BotAdaptor::new(bot, BOARD_SIZE, KOMI_DEFAULT);
+
+    if let Err(error) = serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout(), &mut adaptor).await {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
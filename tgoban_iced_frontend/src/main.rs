@@ -156,6 +156,15 @@ impl Application for App {
                 );
             },
             go_game_engine::GameStatus::Playing => {
+                let estimate = go_game.estimate_score();
+
+                panel = panel
+                .push(
+                    Text::new(format!("Black Estimate: {}", estimate.score.0))
+                )
+                .push(
+                    Text::new(format!("White Estimate: {}", estimate.score.1))
+                );
             },
         };
 
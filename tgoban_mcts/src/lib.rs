@@ -0,0 +1,210 @@
+mod playout;
+mod search;
+
+use std::time::{Duration, Instant};
+
+use go_game_engine::GoGameEngine;
+
+use tgoban_bot::{Bot, BotMove};
+
+pub use crate::search::Candidate;
+use crate::playout::Position;
+use crate::search::Search;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The playouts of the search, shared by the threads.
+    pub playouts: Option<u32>,
+    /// The time of the search, the search stops at whichever budget runs out first.
+    pub time: Option<Duration>,
+    /// Every thread searches its own tree from the root, the root statistics are merged.
+    pub threads: usize,
+    /// The same seed gives the same move with a playout budget.
+    pub seed: u64,
+    pub exploration: f64,
+    /// The visits where the RAVE value and the UCT value weigh the same.
+    pub rave_equivalence: f64,
+    /// Resigns if the win rate of the best move is lower.
+    pub resign_threshold: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            playouts: Some(10000),
+            time: None,
+            threads: 1,
+            seed: 7,
+            exploration: 0.2,
+            rave_equivalence: 1000.0,
+            resign_threshold: 0.1,
+        }
+    }
+}
+
+pub struct SearchResult {
+    pub best: BotMove,
+    /// The win rate of the best move for the player to move.
+    pub win_rate: f64,
+    pub playouts: u32,
+    pub candidates: Vec<Candidate>,
+}
+
+pub fn search(engine: &GoGameEngine, config: &Config) -> SearchResult {
+    let root = Position::new(engine);
+    let threads = std::cmp::max(config.threads, 1);
+    let deadline = config.time.map(|time| Instant::now() + time);
+    let playouts = match (config.playouts, config.time) {
+        (None, None) => Config::default().playouts,
+        (playouts, _) => playouts,
+    };
+
+    let results: Vec<Vec<Candidate>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|thread| {
+            let root = root.clone();
+            let thread_playouts = playouts.map(|playouts| {
+                playouts / threads as u32 + match (thread as u32) < playouts % threads as u32 {
+                    true => 1,
+                    false => 0,
+                }
+            });
+
+            scope.spawn(move || {
+                let mut search = Search::new(config, root, config.seed.wrapping_add(thread as u64));
+                search.run(thread_playouts, deadline);
+                search.candidates()
+            })
+        }).collect();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for candidate in results.into_iter().flatten() {
+        match candidates.iter_mut().find(|merged| merged.mov == candidate.mov) {
+            Some(merged) => {
+                merged.visits += candidate.visits;
+                merged.wins += candidate.wins;
+            },
+            None => candidates.push(candidate),
+        };
+    }
+
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.visits));
+
+    let total: u32 = candidates.iter().map(|candidate| candidate.visits).sum();
+
+    let (best, win_rate) = match candidates.first() {
+        None => (BotMove::Pass, 0.0),
+        Some(candidate) if candidate.win_rate() < config.resign_threshold => (BotMove::Resign, candidate.win_rate()),
+        Some(candidate) => match candidate.mov {
+            Some(location) => (BotMove::Play(location), candidate.win_rate()),
+            None => (BotMove::Pass, candidate.win_rate()),
+        },
+    };
+
+    SearchResult {
+        best,
+        win_rate,
+        playouts: total,
+        candidates,
+    }
+}
+
+pub struct MctsBot {
+    config: Config,
+}
+
+impl MctsBot {
+    pub fn new(config: Config) -> MctsBot {
+        MctsBot {
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
+impl Bot for MctsBot {
+    fn name(&self) -> String {
+        "TGoBan MCTS".to_string()
+    }
+
+    fn genmove(&mut self, engine: &GoGameEngine) -> BotMove {
+        search(engine, &self.config).best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use go_game_engine::{Diagram, Location};
+
+    fn config(playouts: u32, threads: usize) -> Config {
+        Config {
+            playouts: Some(playouts),
+            threads,
+            seed: 7,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_capture() {
+        let diagram: Diagram = "
+            $$B
+            $$ +-------------------+
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . X X X X . . . |
+            $$ | . X O O O O X . . |
+            $$ | . X O O O O . . . |
+            $$ | . . X X X X . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ | . . . . . . . . . |
+            $$ +-------------------+
+        ".parse().unwrap();
+        let engine = GoGameEngine::from_diagram(&diagram, 6.5).unwrap();
+
+        let result = search(&engine, &config(400, 2));
+
+        assert_eq!(result.best, BotMove::Play(Location { alphabet: 6, digit: 4 }));
+        assert_eq!(result.playouts, 400);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut engine = GoGameEngine::new(9, 6.5);
+        assert!(engine.make_move(Location { alphabet: 4, digit: 4 }).is_ok());
+
+        let first = search(&engine, &config(120, 3));
+        let second = search(&engine, &config(120, 3));
+
+        assert_eq!(first.best, second.best);
+        assert_eq!(first.candidates[0].visits, second.candidates[0].visits);
+    }
+
+    #[test]
+    fn test_resign() {
+        let diagram: Diagram = "
+            $$W
+            $$ +-----------+
+            $$ | . X . X . |
+            $$ | X X X X X |
+            $$ | . X . X . |
+            $$ | X X X X X |
+            $$ | . X . X . |
+            $$ +-----------+
+        ".parse().unwrap();
+        let engine = GoGameEngine::from_diagram(&diagram, 0.5).unwrap();
+
+        assert_eq!(search(&engine, &config(200, 1)).best, BotMove::Resign);
+    }
+}
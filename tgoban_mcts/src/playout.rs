@@ -0,0 +1,185 @@
+use go_game_engine::{GoGameEngine, GoBoard, ChessType, Location, Player};
+
+use tgoban_bot::Rng;
+
+/// The light weight position for the search, only the board, the ko and the passes are tracked.
+#[derive(Clone)]
+pub(crate) struct Position {
+    pub board: GoBoard,
+    pub to_move: ChessType,
+    pub ko: Option<Location>,
+    pub passes: u32,
+    pub komi: f64,
+}
+
+pub(crate) fn opponent(chess_type: ChessType) -> ChessType {
+    match chess_type {
+        ChessType::Black => ChessType::White,
+        ChessType::White => ChessType::Black,
+        ChessType::None => ChessType::None,
+    }
+}
+
+impl Position {
+    pub fn new(engine: &GoGameEngine) -> Position {
+        let passes = match engine.last_move() {
+            Some(record) if record.location.is_none() => 1,
+            _ => 0,
+        };
+
+        Position {
+            board: engine.board().clone(),
+            to_move: match engine.player() {
+                Player::Black => ChessType::Black,
+                Player::White => ChessType::White,
+            },
+            ko: engine.ko(),
+            passes,
+            komi: engine.komi(),
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.board.size()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.passes >= 2
+    }
+
+    /// The legal move that does not fill the own eye.
+    pub fn is_candidate(&self, location: &Location) -> bool {
+        self.ko != Some(*location)
+            && self.board.is_legal(self.to_move, location)
+            && !self.board.is_eye(self.to_move, location)
+    }
+
+    pub fn candidates(&self) -> Vec<Location> {
+        self.empties().into_iter()
+            .filter(|location| self.is_candidate(location))
+            .collect()
+    }
+
+    fn empties(&self) -> Vec<Location> {
+        let mut empties: Vec<Location> = Vec::new();
+
+        for alphabet in 0..self.size() {
+            for digit in 0..self.size() {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+
+                if self.board.get(&location) == ChessType::None {
+                    empties.push(location);
+                }
+            }
+        }
+
+        empties
+    }
+
+    /// Plays the move, None is the pass. The move must be a legal move.
+    pub fn play(&mut self, mov: Option<Location>) {
+        match mov {
+            Some(location) => {
+                let chess_change = self.board.make_move(self.to_move, location)
+                    .expect("The searched move should be legal");
+
+                self.ko = self.board.ko(&chess_change);
+                self.passes = 0;
+            },
+            None => {
+                self.ko = None;
+                self.passes += 1;
+            },
+        };
+
+        self.to_move = opponent(self.to_move);
+    }
+
+    /// A random candidate move, None if there is no candidate.
+    pub fn random_move(&self, rng: &mut Rng) -> Option<Location> {
+        let mut empties = self.empties();
+
+        while !empties.is_empty() {
+            let location = empties.swap_remove(rng.below(empties.len()));
+
+            if self.is_candidate(&location) {
+                return Some(location);
+            }
+        }
+
+        None
+    }
+
+    /// Plays randomly to the end, the played moves are appended to the sequence.
+    pub fn playout(&mut self, rng: &mut Rng, sequence: &mut Vec<(ChessType, Option<Location>)>) {
+        let max_moves = self.size() as usize * self.size() as usize * 3;
+        let mut moves = 0;
+
+        while !self.is_over() && moves < max_moves {
+            let mov = self.random_move(rng);
+
+            sequence.push((self.to_move, mov));
+            self.play(mov);
+            moves += 1;
+        }
+    }
+
+    /// The Tromp-Taylor area score, positive if black wins.
+    pub fn score(&self) -> f64 {
+        let size = self.size();
+        let mut score: f64 = -self.komi;
+        let mut visited = vec![vec![false; size as usize]; size as usize];
+
+        for alphabet in 0..size {
+            for digit in 0..size {
+                let location = Location {
+                    alphabet,
+                    digit,
+                };
+
+                match self.board.get(&location) {
+                    ChessType::Black => score += 1.0,
+                    ChessType::White => score -= 1.0,
+                    ChessType::None => {
+                        if visited[alphabet as usize][digit as usize] {
+                            continue;
+                        }
+
+                        let mut region = 0.0;
+                        let mut borders = (false, false);
+                        let mut queue: Vec<Location> = vec![location];
+                        visited[alphabet as usize][digit as usize] = true;
+
+                        while let Some(location) = queue.pop() {
+                            region += 1.0;
+
+                            for neighbor in self.board.neighbors(&location) {
+                                match self.board.get(&neighbor) {
+                                    ChessType::Black => borders.0 = true,
+                                    ChessType::White => borders.1 = true,
+                                    ChessType::None => {
+                                        if !visited[neighbor.alphabet as usize][neighbor.digit as usize] {
+                                            visited[neighbor.alphabet as usize][neighbor.digit as usize] = true;
+                                            queue.push(neighbor);
+                                        }
+                                    },
+                                };
+                            }
+                        }
+
+                        match borders {
+                            (true, false) => score += region,
+                            (false, true) => score -= region,
+                            _ => {},
+                        };
+                    },
+                };
+            }
+        }
+
+        score
+    }
+}
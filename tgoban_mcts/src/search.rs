@@ -0,0 +1,234 @@
+use std::time::Instant;
+
+use go_game_engine::{ChessType, Location};
+
+use tgoban_bot::Rng;
+
+use crate::Config;
+use crate::playout::{Position, opponent};
+
+/// The first play urgency of the unvisited moves, the pass is tried last.
+const FIRST_PLAY_URGENCY: f64 = 1.0;
+const PASS_URGENCY: f64 = 0.1;
+
+struct Node {
+    /// The move to the node, None is the pass.
+    mov: Option<Location>,
+    /// The player who made the move.
+    player: ChessType,
+    visits: u32,
+    wins: f64,
+    rave_visits: u32,
+    rave_wins: f64,
+    children: Vec<usize>,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(mov: Option<Location>, player: ChessType) -> Node {
+        Node {
+            mov,
+            player,
+            visits: 0,
+            wins: 0.0,
+            rave_visits: 0,
+            rave_wins: 0.0,
+            children: Vec::new(),
+            expanded: false,
+        }
+    }
+}
+
+/// The statistics of a move at the root.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    /// None is the pass.
+    pub mov: Option<Location>,
+    pub visits: u32,
+    pub wins: f64,
+}
+
+impl Candidate {
+    pub fn win_rate(&self) -> f64 {
+        match self.visits {
+            0 => 0.0,
+            visits => self.wins / visits as f64,
+        }
+    }
+}
+
+/// The UCT search with RAVE on a single thread.
+pub(crate) struct Search<'a> {
+    config: &'a Config,
+    root: Position,
+    nodes: Vec<Node>,
+    rng: Rng,
+}
+
+impl<'a> Search<'a> {
+    pub fn new(config: &'a Config, root: Position, seed: u64) -> Search<'a> {
+        let player = opponent(root.to_move);
+
+        Search {
+            config,
+            root,
+            nodes: vec![Node::new(None, player)],
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn run(&mut self, playouts: Option<u32>, deadline: Option<Instant>) {
+        let mut count = 0;
+
+        loop {
+            if let Some(playouts) = playouts {
+                if count >= playouts {
+                    break;
+                }
+            }
+
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+
+            self.iterate();
+            count += 1;
+        }
+    }
+
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.nodes[0].children.iter()
+            .map(|child| {
+                let node = &self.nodes[*child];
+
+                Candidate {
+                    mov: node.mov,
+                    visits: node.visits,
+                    wins: node.wins,
+                }
+            })
+            .collect()
+    }
+
+    fn expand(&mut self, node: usize, position: &Position) {
+        let player = position.to_move;
+        let mut children: Vec<usize> = Vec::new();
+
+        for location in position.candidates() {
+            children.push(self.nodes.len());
+            self.nodes.push(Node::new(Some(location), player));
+        }
+
+        children.push(self.nodes.len());
+        self.nodes.push(Node::new(None, player));
+
+        self.nodes[node].children = children;
+        self.nodes[node].expanded = true;
+    }
+
+    fn select(&self, node: usize) -> usize {
+        let parent = &self.nodes[node];
+        let log_visits = ((parent.visits + 1) as f64).ln();
+
+        let mut best: Option<(usize, f64)> = None;
+
+        for child in parent.children.iter() {
+            let child_node = &self.nodes[*child];
+
+            let value = match (child_node.visits, child_node.rave_visits, child_node.mov) {
+                (0, 0, None) => PASS_URGENCY,
+                (0, 0, Some(_)) => FIRST_PLAY_URGENCY,
+                (visits, rave_visits, _) => {
+                    let win_rate = match visits {
+                        0 => 0.5,
+                        _ => child_node.wins / visits as f64,
+                    };
+                    let rave_win_rate = match rave_visits {
+                        0 => 0.5,
+                        _ => child_node.rave_wins / rave_visits as f64,
+                    };
+                    let beta = (self.config.rave_equivalence / (3.0 * visits as f64 + self.config.rave_equivalence)).sqrt();
+
+                    (1.0 - beta) * win_rate + beta * rave_win_rate
+                        + self.config.exploration * (log_visits / (visits + 1) as f64).sqrt()
+                },
+            };
+
+            match best {
+                Some((_, best_value)) if best_value >= value => {},
+                _ => best = Some((*child, value)),
+            };
+        }
+
+        best.unwrap().0 /* The expanded node always has the pass */
+    }
+
+    fn iterate(&mut self) {
+        let mut position = self.root.clone();
+        let mut path: Vec<usize> = vec![0];
+        let mut sequence: Vec<(ChessType, Option<Location>)> = Vec::new();
+        let mut node = 0;
+
+        while !position.is_over() {
+            if !self.nodes[node].expanded {
+                if self.nodes[node].visits == 0 && node != 0 {
+                    break;
+                }
+                self.expand(node, &position);
+            }
+
+            node = self.select(node);
+
+            sequence.push((position.to_move, self.nodes[node].mov));
+            position.play(self.nodes[node].mov);
+            path.push(node);
+        }
+
+        position.playout(&mut self.rng, &mut sequence);
+
+        let winner = match position.score() > 0.0 {
+            true => ChessType::Black,
+            false => ChessType::White,
+        };
+
+        self.backup(&path, &sequence, winner);
+    }
+
+    fn backup(&mut self, path: &[usize], sequence: &[(ChessType, Option<Location>)], winner: ChessType) {
+        let size = self.root.size() as usize;
+        /* The first player who played on the point after the depth */
+        let mut first_player = vec![vec![ChessType::None; size]; size];
+        let mut sequence_idx = sequence.len();
+
+        for (depth, node) in path.iter().enumerate().rev() {
+            while sequence_idx > depth {
+                sequence_idx -= 1;
+                if let (player, Some(location)) = sequence[sequence_idx] {
+                    first_player[location.alphabet as usize][location.digit as usize] = player;
+                }
+            }
+
+            let children = self.nodes[*node].children.clone();
+            for child in children {
+                let child_node = &mut self.nodes[child];
+
+                if let Some(location) = child_node.mov {
+                    if first_player[location.alphabet as usize][location.digit as usize] == child_node.player {
+                        child_node.rave_visits += 1;
+                        if child_node.player == winner {
+                            child_node.rave_wins += 1.0;
+                        }
+                    }
+                }
+            }
+
+            let node = &mut self.nodes[*node];
+            node.visits += 1;
+            if node.player == winner {
+                node.wins += 1.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use go_game_engine::{Annotation, GoGameEngine, Location, Player};
+
+use tgoban_gtp::{Color, Command, CommandResult, GenMoveResult, Move, ResponseError, Score, Vertex};
+use tgoban_ws_adapter::arbitator::adaptor::Adaptor;
+
+/// The score lost by a move, in points, to be annotated.
+#[derive(Copy, Clone, Debug)]
+pub struct ReviewConfig {
+    pub doubtful: f64,
+    pub bad: f64,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> ReviewConfig {
+        ReviewConfig {
+            doubtful: 2.0,
+            bad: 5.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Suggestion {
+    Play(Location),
+    Pass,
+    Resign,
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveReview {
+    pub number: i32,
+    pub player: Player,
+    /// None for the pass.
+    pub location: Option<Location>,
+    /// The move preferred by the engine at the position before the move.
+    pub suggestion: Suggestion,
+    /// The score after the move, positive for black.
+    pub score: f64,
+    /// The score lost by the move for the player.
+    pub loss: f64,
+}
+
+#[derive(Debug)]
+pub enum ReviewError {
+    /// The engine fails or gives an unexpected result for the command.
+    Engine(String, Option<ResponseError>),
+}
+
+impl std::fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewError::Engine(command, Some(error)) => write!(f, "The engine fails on \"{}\": {}", command, error),
+            ReviewError::Engine(command, None) => write!(f, "The engine fails on \"{}\"", command),
+        }
+    }
+}
+
+impl std::error::Error for ReviewError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReviewError::Engine(_, Some(error)) => Some(error),
+            ReviewError::Engine(_, None) => None,
+        }
+    }
+}
+
+/// Replays the main line of the game through the engine and annotates every move.
+///
+/// The engine's score before and after a move tells the loss of the move, a move losing
+/// more than the thresholds is marked doubtful or bad, and the engine's suggestion is added
+/// as a variation. The engine is left at the end of the main line.
+pub async fn review(engine: &mut GoGameEngine, adaptor: &mut (impl Adaptor + Unpin + Send), config: &ReviewConfig) -> Result<Vec<MoveReview>, ReviewError> {
+    while engine.back() {}
+
+    send(adaptor, Command::BoardSize(None, engine.size() as i32)).await?;
+    send(adaptor, Command::ClearBoard(None)).await?;
+    send(adaptor, Command::Komi(None, engine.komi() as f32)).await?;
+
+    /* The setup stones are on the board of the root */
+    for alphabet in 0..engine.size() {
+        for digit in 0..engine.size() {
+            let location = Location {
+                alphabet,
+                digit,
+            };
+
+            let player = match engine.get_chess(location) {
+                go_game_engine::ChessType::Black => Player::Black,
+                go_game_engine::ChessType::White => Player::White,
+                go_game_engine::ChessType::None => continue,
+            };
+
+            play(adaptor, player, Some(location)).await?;
+        }
+    }
+
+    let mut reviews: Vec<MoveReview> = Vec::new();
+    let mut before = final_score(adaptor).await?;
+
+    loop {
+        let suggestion = reg_genmove(adaptor, engine.player()).await?;
+
+        if !engine.forward(0) {
+            break;
+        }
+
+        let mov = engine.last_move().unwrap() /* The head is not the root after moving forward */;
+
+        play(adaptor, mov.player, mov.location).await?;
+
+        let after = final_score(adaptor).await?;
+        let loss = match mov.player {
+            Player::Black => before - after,
+            Player::White => after - before,
+        };
+
+        let is_suggested = match suggestion {
+            Suggestion::Play(location) => mov.location == Some(location),
+            Suggestion::Pass => mov.location.is_none(),
+            Suggestion::Resign => false,
+        };
+
+        let mut comment = format!("Score: {}\nEngine: {}", score_text(after), suggestion_text(&suggestion));
+        if !is_suggested {
+            comment.push_str(&format!("\nLoss: {:.1}", loss));
+        }
+
+        engine.set_comment(Some(match engine.comment() {
+            Some(origin) => format!("{}\n\n{}", origin, comment),
+            None => comment,
+        }));
+        engine.set_value(Some(after));
+
+        if !is_suggested {
+            if loss >= config.bad {
+                engine.set_annotation(Some(Annotation::Bad));
+            } else if loss >= config.doubtful {
+                engine.set_annotation(Some(Annotation::Doubtful));
+            }
+
+            if let Suggestion::Play(location) = suggestion {
+                if loss >= config.doubtful {
+                    add_variation(engine, location);
+                }
+            }
+        }
+
+        reviews.push(MoveReview {
+            number: mov.number,
+            player: mov.player,
+            location: mov.location,
+            suggestion,
+            score: after,
+            loss,
+        });
+
+        before = after;
+    }
+
+    Ok(reviews)
+}
+
+/// Adds the suggestion as the variation of the move at the head, the head is kept.
+fn add_variation(engine: &mut GoGameEngine, location: Location) {
+    engine.back();
+
+    if engine.make_move(location).is_ok() {
+        engine.set_comment(Some("Engine suggestion".to_string()));
+        engine.back();
+    }
+
+    engine.forward(0);
+}
+
+async fn send(adaptor: &mut (impl Adaptor + Unpin + Send), command: Command) -> Result<CommandResult, ReviewError> {
+    let text = command.to_string();
+
+    match adaptor.send_command(command).await {
+        Ok(result) => Ok(result),
+        Err(error) => Err(ReviewError::Engine(text, Some(error))),
+    }
+}
+
+async fn play(adaptor: &mut (impl Adaptor + Unpin + Send), player: Player, location: Option<Location>) -> Result<(), ReviewError> {
+    let vertex = match location {
+        Some(location) => Vertex::from_number(location.alphabet, location.digit),
+        None => Vertex::Pass,
+    };
+
+    send(adaptor, Command::Play(None, Move {
+        color: color(player),
+        vertex,
+    })).await?;
+
+    Ok(())
+}
+
+async fn reg_genmove(adaptor: &mut (impl Adaptor + Unpin + Send), player: Player) -> Result<Suggestion, ReviewError> {
+    let command = Command::RegGenMove(None, color(player));
+    let text = command.to_string();
+
+    let suggestion = match send(adaptor, command).await? {
+        CommandResult::RegGenMove(_, GenMoveResult::Resign) => Suggestion::Resign,
+        CommandResult::RegGenMove(_, GenMoveResult::Move(vertex)) => match vertex.to_number() {
+            Ok((alphabet, digit)) => Suggestion::Play(Location {
+                alphabet,
+                digit,
+            }),
+            Err(_) => Suggestion::Pass,
+        },
+        _ => return Err(ReviewError::Engine(text, None)),
+    };
+
+    Ok(suggestion)
+}
+
+/// The score of the position, positive for black.
+async fn final_score(adaptor: &mut (impl Adaptor + Unpin + Send)) -> Result<f64, ReviewError> {
+    let command = Command::FinalScore(None);
+    let text = command.to_string();
+
+    let score = match send(adaptor, command).await? {
+        CommandResult::FinalScore(_, Score::Black(points)) => points as f64,
+        CommandResult::FinalScore(_, Score::White(points)) => -points as f64,
+        CommandResult::FinalScore(_, Score::Jigo) => 0.0,
+        _ => return Err(ReviewError::Engine(text, None)),
+    };
+
+    Ok(score)
+}
+
+fn color(player: Player) -> Color {
+    match player {
+        Player::Black => Color::Black,
+        Player::White => Color::White,
+    }
+}
+
+fn score_text(score: f64) -> String {
+    match score {
+        score if score > 0.0 => format!("B+{:.1}", score),
+        score if score < 0.0 => format!("W+{:.1}", -score),
+        _ => "0".to_string(),
+    }
+}
+
+fn suggestion_text(suggestion: &Suggestion) -> String {
+    match suggestion {
+        Suggestion::Play(location) => Vertex::from_number(location.alphabet, location.digit).to_string(),
+        Suggestion::Pass => "pass".to_string(),
+        Suggestion::Resign => "resign".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgoban_bot::{Level, new_bot};
+    use tgoban_ws_adapter::arbitator::bot_adaptor::BotAdaptor;
+
+    #[tokio::test]
+    async fn test_review() {
+        let mut engine = GoGameEngine::from_sgf("(;GM[1]FF[4]SZ[9]KM[6.5]AB[ee];W[aa]C[Corner];B[ce];W[ab];B[ec])").unwrap();
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 19, 0.0);
+
+        let config = ReviewConfig {
+            doubtful: 0.0,
+            bad: 100.0,
+        };
+        let reviews = review(&mut engine, &mut adaptor, &config).await.unwrap();
+
+        assert_eq!(reviews.len(), 4);
+        assert_eq!(reviews[0].player, Player::White);
+        assert_eq!(reviews[0].location, Some(Location { alphabet: 0, digit: 8 }));
+        assert_eq!(engine.steps(), 4);
+
+        let sgf = engine.to_sgf();
+        assert!(sgf.contains("C[Corner\n\nScore: "));
+        assert!(sgf.contains("V["));
+
+        /* The first line of the engine's choices is the main line, the others are variations */
+        let mismatches = reviews.iter()
+            .filter(|review| match review.suggestion {
+                Suggestion::Play(location) => review.location != Some(location) && review.loss >= 0.0,
+                _ => false,
+            })
+            .count();
+        assert_eq!(sgf.matches("C[Engine suggestion]").count(), mismatches);
+        assert!(mismatches > 0);
+    }
+}
@@ -0,0 +1,112 @@
+use go_game_engine::GoGameEngine;
+
+use tgoban_review::{review, ReviewConfig};
+use tgoban_ws_adapter::arbitator::command_adaptor::spawn_command;
+use tgoban_ws_adapter::config::EngineConfig;
+
+const USAGE: &str = "Usage: tgoban_review [--doubtful POINTS] [--bad POINTS] [-o OUTPUT] GAME.sgf ENGINE [ARGS...]";
+
+struct Options {
+    config: ReviewConfig,
+    output: Option<String>,
+    game: String,
+    engine: EngineConfig,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut config = ReviewConfig::default();
+    let mut output: Option<String> = None;
+
+    let game = loop {
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => return Err("The game is not given".to_string()),
+        };
+
+        match arg.as_str() {
+            "--doubtful" | "--bad" => {
+                let points: f64 = match args.next().map(|points| points.parse()) {
+                    Some(Ok(points)) => points,
+                    _ => return Err(format!("{} needs the points", arg)),
+                };
+
+                match arg.as_str() {
+                    "--doubtful" => config.doubtful = points,
+                    _ => config.bad = points,
+                };
+            },
+            "-o" => {
+                output = match args.next() {
+                    Some(output) => Some(output),
+                    None => return Err("-o needs the output file".to_string()),
+                };
+            },
+            _ => break arg,
+        };
+    };
+
+    let program = match args.next() {
+        Some(program) => program,
+        None => return Err("The engine is not given".to_string()),
+    };
+
+    Ok(Options {
+        config,
+        output,
+        game,
+        engine: EngineConfig {
+            program,
+            args: args.collect(),
+            working_dir: None,
+        },
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let sgf = std::fs::read_to_string(&options.game).unwrap_or_else(|error| {
+        eprintln!("Cannot read {}: {}", options.game, error);
+        std::process::exit(1);
+    });
+
+    let (mut engine, warnings) = GoGameEngine::from_sgf_with_warnings(&sgf).unwrap_or_else(|error| {
+        eprintln!("Cannot load {}: {}", options.game, error);
+        std::process::exit(1);
+    });
+
+    for warning in warnings.iter() {
+        eprintln!("{}: {}", options.game, warning);
+    }
+
+    let mut adaptor = spawn_command(&options.engine).unwrap_or_else(|error| {
+        eprintln!("Cannot run {}: {}", options.engine.program, error);
+        std::process::exit(1);
+    });
+
+    let reviews = review(&mut engine, &mut adaptor, &options.config).await.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
+
+    for review in reviews.iter().filter(|review| review.loss >= options.config.doubtful) {
+        eprintln!("Move {}: {:?} loses {:.1}", review.number, review.player, review.loss);
+    }
+
+    match options.output {
+        Some(output) => {
+            if let Err(error) = std::fs::write(&output, engine.to_sgf()) {
+                eprintln!("Cannot write {}: {}", output, error);
+                std::process::exit(1);
+            }
+        },
+        None => println!("{}", engine.to_sgf()),
+    };
+}
@@ -1,7 +1,34 @@
 use std::future::Future;
 
-use tgoban_gtp::{Command, CommandResult};
+use tgoban_gtp::{Command, CommandResult, ResponseError};
 
 pub trait Adaptor {
-    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ()>> + Unpin + Send + 'a>;
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a>;
+
+    /// Whether the player answers the command, the optional commands are sent only if it does.
+    /// The GTP engine answers every command, if only with the failure.
+    fn knows_command(&self, _name: &str) -> bool {
+        true
+    }
+
+    /// Reads the next response as it is, such as the one left by the command whose future is
+    /// dropped. The players answering every command at once have no response left.
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        Box::new(std::future::ready(Err(ResponseError::Empty)))
+    }
+}
+
+/// The borrowed adaptor, the caller keeps the adaptor after the game.
+impl<A: Adaptor + ?Sized> Adaptor for &mut A {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+        (**self).send_command(command)
+    }
+
+    fn knows_command(&self, name: &str) -> bool {
+        (**self).knows_command(name)
+    }
+
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        (**self).read_response()
+    }
 }
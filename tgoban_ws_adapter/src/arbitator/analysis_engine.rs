@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use go_game_engine::{ChessType, GoGameEngine, Location, Player};
+use tgoban_gtp::{Candidate, Vertex};
+
+/// The query of KataGo's analysis engine, one response is replied for every turn to analyze.
+pub struct AnalysisQuery {
+    /// The rules known by KataGo, such as `tromp-taylor`, `chinese` or `japanese`.
+    pub rules: String,
+    pub komi: f64,
+    pub board_size: u8,
+    pub initial_stones: Vec<(Player, Location)>,
+    pub initial_player: Player,
+    /// The moves from the initial position, None for the pass.
+    pub moves: Vec<(Player, Option<Location>)>,
+    /// The number of the moves played before the positions to analyze.
+    pub analyze_turns: Vec<usize>,
+    /// The visits of the engine's config if None.
+    pub max_visits: Option<u32>,
+    pub include_ownership: bool,
+}
+
+impl AnalysisQuery {
+    /// Analyzes every position from the root to the head of the game.
+    pub fn from_engine(engine: &GoGameEngine, rules: &str) -> AnalysisQuery {
+        let moves: Vec<(Player, Option<Location>)> = engine.moves().iter()
+            .map(|record| (record.player, record.location))
+            .collect();
+
+        AnalysisQuery {
+            rules: rules.to_string(),
+            komi: engine.komi(),
+            board_size: engine.size(),
+            initial_stones: engine.setup().iter()
+                .filter_map(|chess| match chess.chess_type {
+                    ChessType::Black => Some((Player::Black, chess.location)),
+                    ChessType::White => Some((Player::White, chess.location)),
+                    ChessType::None => None,
+                })
+                .collect(),
+            initial_player: engine.first_player(),
+            analyze_turns: (0..=moves.len()).collect(),
+            moves,
+            max_visits: None,
+            include_ownership: false,
+        }
+    }
+
+    /// The line of the query sent to the engine.
+    pub fn to_json(&self, id: &str) -> String {
+        let stone = |player: &Player, location: Option<Location>| {
+            let vertex = match location {
+                Some(location) => Vertex::from_number(location.alphabet, location.digit),
+                None => Vertex::Pass,
+            };
+
+            json!([player_text(player), vertex.to_string()])
+        };
+
+        let mut query = json!({
+            "id": id,
+            "rules": self.rules,
+            "komi": self.komi,
+            "boardXSize": self.board_size,
+            "boardYSize": self.board_size,
+            "initialStones": self.initial_stones.iter().map(|(player, location)| stone(player, Some(*location))).collect::<Vec<Value>>(),
+            "initialPlayer": player_text(&self.initial_player),
+            "moves": self.moves.iter().map(|(player, location)| stone(player, *location)).collect::<Vec<Value>>(),
+            "analyzeTurns": self.analyze_turns,
+            "includeOwnership": self.include_ownership,
+        });
+
+        if let Some(max_visits) = self.max_visits {
+            query["maxVisits"] = json!(max_visits);
+        }
+
+        query.to_string()
+    }
+}
+
+/// The analysis of one turn of the query.
+pub struct TurnAnalysis {
+    pub turn: usize,
+    pub candidates: Vec<Candidate>,
+    /// The winrate of the position, of the side set by `reportAnalysisWinratesAs` of the engine.
+    pub winrate: f64,
+    pub score_lead: f64,
+    pub visits: u32,
+    /// The ownership of every point from A19 row by row.
+    pub ownership: Option<Vec<f64>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnalysisError {
+    /// The engine rejects the query with the message.
+    Engine(String),
+    /// The response is not understood.
+    Malformed(String),
+    /// The engine exits before all the turns are analyzed.
+    Closed,
+}
+
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::Engine(message) => write!(f, "engine error: {}", message),
+            AnalysisError::Malformed(line) => write!(f, "malformed response: {}", line),
+            AnalysisError::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+/// The responses of a query not yet received and where to send them.
+struct Route {
+    sender: UnboundedSender<Result<TurnAnalysis, AnalysisError>>,
+    remaining: usize,
+}
+
+/// The routes by the id of the query, None after the engine exits.
+type Routes = Arc<Mutex<Option<HashMap<String, Route>>>>;
+
+/// The client of KataGo's JSON analysis engine, the queries are analyzed concurrently and
+/// the responses are routed back to the query by its id.
+pub struct AnalysisEngine<W: AsyncWrite + Unpin + Send> {
+    child: Option<Child>,
+    writer: W,
+    routes: Routes,
+    next_id: u64,
+}
+
+impl<W: AsyncWrite + Unpin + Send> AnalysisEngine<W> {
+    /// The responses are read from the reader by a spawned task.
+    pub fn new<R: AsyncRead + Unpin + Send + 'static>(reader: R, writer: W) -> AnalysisEngine<W> {
+        let routes: Routes = Arc::new(Mutex::new(Some(HashMap::new())));
+
+        tokio::spawn(read_responses(reader, routes.clone()));
+
+        AnalysisEngine {
+            child: None,
+            writer,
+            routes,
+            next_id: 1,
+        }
+    }
+
+    /// Sends the query, the responses of it are received from the returned `QueryResponses`.
+    pub async fn query(&mut self, query: &AnalysisQuery) -> Result<QueryResponses, AnalysisError> {
+        let id = self.next_id.to_string();
+        let (sender, receiver) = unbounded_channel();
+
+        self.next_id += 1;
+        match self.routes.lock().unwrap().as_mut() {
+            Some(routes) => routes.insert(id.clone(), Route {
+                sender,
+                remaining: query.analyze_turns.len(),
+            }),
+            None => return Err(AnalysisError::Closed),
+        };
+
+        let line = format!("{}\n", query.to_json(&id));
+        if self.writer.write_all(line.as_bytes()).await.is_err() || self.writer.flush().await.is_err() {
+            if let Some(routes) = self.routes.lock().unwrap().as_mut() {
+                routes.remove(&id);
+            }
+            return Err(AnalysisError::Closed);
+        }
+
+        Ok(QueryResponses {
+            receiver,
+            turns: query.analyze_turns.len(),
+        })
+    }
+
+    /// The process of the engine if it is spawned.
+    pub fn child(&mut self) -> Option<&mut Child> {
+        self.child.as_mut()
+    }
+}
+
+impl AnalysisEngine<ChildStdin> {
+    /// Runs the engine in the analysis mode, such as `katago analysis -config analysis.cfg -model model.bin.gz`.
+    pub fn spawn(program: &str, args: &[String]) -> std::io::Result<AnalysisEngine<ChildStdin>> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let mut engine = AnalysisEngine::new(stdout, stdin);
+        engine.child = Some(child);
+
+        Ok(engine)
+    }
+}
+
+/// The responses of one query, in the order the engine finishes the turns.
+pub struct QueryResponses {
+    receiver: UnboundedReceiver<Result<TurnAnalysis, AnalysisError>>,
+    turns: usize,
+}
+
+impl QueryResponses {
+    /// The next analyzed turn, None after all the turns or the error.
+    pub async fn next(&mut self) -> Option<Result<TurnAnalysis, AnalysisError>> {
+        self.receiver.recv().await
+    }
+
+    /// Waits for all the turns, sorted by the turn.
+    pub async fn collect(mut self) -> Result<Vec<TurnAnalysis>, AnalysisError> {
+        let mut analyses: Vec<TurnAnalysis> = Vec::new();
+
+        while let Some(analysis) = self.next().await {
+            analyses.push(analysis?);
+        }
+
+        if analyses.len() < self.turns {
+            return Err(AnalysisError::Closed);
+        }
+
+        analyses.sort_by_key(|analysis| analysis.turn);
+
+        Ok(analyses)
+    }
+}
+
+async fn read_responses<R: AsyncRead + Unpin + Send>(reader: R, routes: Routes) {
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response: Value = match serde_json::from_str(&line) {
+            Ok(response) => response,
+            Err(_) => continue,
+        };
+
+        /* The responses without the known id, such as the warnings of the engine, are dropped */
+        let id = match response["id"].as_str() {
+            Some(id) => id.to_string(),
+            None => continue,
+        };
+
+        let mut guard = routes.lock().unwrap();
+        let routes = guard.as_mut().unwrap() /* Only set to None below */;
+        let route = match routes.get_mut(&id) {
+            Some(route) => route,
+            None => continue,
+        };
+
+        if let Some(message) = response["error"].as_str() {
+            let _ = route.sender.send(Err(AnalysisError::Engine(message.to_string())));
+            routes.remove(&id);
+            continue;
+        }
+
+        /* The warnings and the reports during the search don't finish a turn */
+        if response.get("warning").is_some() || response["isDuringSearch"].as_bool() == Some(true) {
+            continue;
+        }
+
+        let analysis = parse_turn(&response).ok_or_else(|| AnalysisError::Malformed(line.clone()));
+        let is_failed = analysis.is_err();
+
+        let _ = route.sender.send(analysis);
+        route.remaining -= 1;
+
+        if route.remaining == 0 || is_failed {
+            routes.remove(&id);
+        }
+    }
+
+    /* The engine exits, the waiting queries are failed */
+    if let Some(routes) = routes.lock().unwrap().take() {
+        for (_, route) in routes {
+            let _ = route.sender.send(Err(AnalysisError::Closed));
+        }
+    }
+}
+
+fn parse_turn(response: &Value) -> Option<TurnAnalysis> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    for (index, info) in response["moveInfos"].as_array()?.iter().enumerate() {
+        candidates.push(Candidate {
+            vertex: info["move"].as_str()?.parse().ok()?,
+            visits: info["visits"].as_u64()? as u32,
+            winrate: info["winrate"].as_f64()?,
+            score_lead: info["scoreLead"].as_f64(),
+            prior: info["prior"].as_f64().unwrap_or(0.0),
+            order: info["order"].as_u64().unwrap_or(index as u64) as u32,
+            pv: match info["pv"].as_array() {
+                Some(pv) => pv.iter().filter_map(|vertex| vertex.as_str()?.parse().ok()).collect(),
+                None => Vec::new(),
+            },
+        });
+    }
+
+    let root = &response["rootInfo"];
+
+    Some(TurnAnalysis {
+        turn: response["turnNumber"].as_u64()? as usize,
+        candidates,
+        winrate: root["winrate"].as_f64()?,
+        score_lead: root["scoreLead"].as_f64()?,
+        visits: root["visits"].as_u64()? as u32,
+        ownership: response["ownership"].as_array()
+            .map(|ownership| ownership.iter().filter_map(|value| value.as_f64()).collect()),
+    })
+}
+
+fn player_text(player: &Player) -> &'static str {
+    match player {
+        Player::Black => "B",
+        Player::White => "W",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let mut engine = GoGameEngine::new(9, 7.0);
+        engine.set_handicap(&[Location { alphabet: 2, digit: 2 }]).unwrap();
+        engine.make_move(Location { alphabet: 4, digit: 4 }).unwrap();
+        engine.pass();
+
+        let mut query = AnalysisQuery::from_engine(&engine, "chinese");
+        query.max_visits = Some(100);
+
+        let json: Value = serde_json::from_str(&query.to_json("7")).unwrap();
+
+        assert_eq!(json["id"], "7");
+        assert_eq!(json["rules"], "chinese");
+        assert_eq!(json["boardXSize"], 9);
+        assert_eq!(json["initialStones"], json!([["B", "C3"]]));
+        assert_eq!(json["initialPlayer"], "W");
+        assert_eq!(json["moves"], json!([["W", "E5"], ["B", "pass"]]));
+        assert_eq!(json["analyzeTurns"], json!([0, 1, 2]));
+        assert_eq!(json["maxVisits"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_routing() {
+        /* The stand-in answers the second query before the turns of the first one out of order */
+        let script = r#"
+            read first
+            read second
+            echo '{"id":"2","error":"Unknown rules","field":"rules"}'
+            echo '{"id":"1","turnNumber":1,"moveInfos":[{"move":"D4","visits":10,"winrate":0.4,"scoreLead":-1.5,"prior":0.3,"order":0,"pv":["D4","E5"]}],"rootInfo":{"winrate":0.41,"scoreLead":-1.4,"visits":10}}'
+            echo '{"id":"1","warning":"Unused field"}'
+            echo '{"id":"1","turnNumber":0,"moveInfos":[],"rootInfo":{"winrate":0.5,"scoreLead":0.5,"visits":12},"ownership":[0.5,-1]}'
+        "#;
+        let mut engine = AnalysisEngine::spawn("sh", &["-c".to_string(), script.to_string()]).unwrap();
+
+        let mut query = AnalysisQuery::from_engine(&GoGameEngine::new(9, 7.0), "tromp-taylor");
+        query.analyze_turns = vec![0, 1];
+
+        let first = engine.query(&query).await.unwrap();
+        let mut second = engine.query(&query).await.unwrap();
+
+        match second.next().await {
+            Some(Err(AnalysisError::Engine(message))) => assert_eq!(message, "Unknown rules"),
+            _ => assert!(false, "The error is not routed to the second query"),
+        };
+
+        let analyses = first.collect().await.unwrap();
+
+        assert_eq!(analyses.len(), 2);
+        assert_eq!(analyses[0].turn, 0);
+        assert_eq!(analyses[0].visits, 12);
+        assert_eq!(analyses[0].ownership, Some(vec![0.5, -1.0]));
+        assert_eq!(analyses[1].candidates[0].vertex.to_string(), "D4");
+        assert_eq!(analyses[1].candidates[0].score_lead, Some(-1.5));
+        assert_eq!(analyses[1].candidates[0].pv.len(), 2);
+
+        /* The stand-in exits, the following query is failed */
+        match engine.query(&query).await {
+            Ok(responses) => assert!(responses.collect().await.is_err()),
+            Err(error) => assert_eq!(error, AnalysisError::Closed),
+        };
+    }
+}
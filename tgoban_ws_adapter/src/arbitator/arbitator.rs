@@ -1,65 +1,468 @@
-use tgoban_gtp::{Command, Color, CommandResult, Move, GenMoveResult};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use go_game_engine::{ChessType, GameResult, GoGameEngine, Location, Player};
+use tgoban_gtp::{Command, CommandResult, FinalStatus, Move, GenMoveResult, Float, Integer, ResponseError, Vertex};
 
 use super::adaptor::Adaptor;
+use super::referee::{Referee, to_color};
+
+/// The time to wait for the response of the player after the game is over.
+const ANNOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 
 struct Arbitator {
 }
 
+/// The settings of the game sent to both players.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSettings {
+    pub board_size: u8,
+    pub komi: f64,
+    /// The rules known by KataGo, such as `chinese` or `japanese`, set by `kata-set-rules`.
+    /// The referee counts the territories whatever the rules.
+    pub rules: Option<String>,
+    /// The game is void after so many moves, the passes included.
+    pub max_moves: Option<u32>,
+}
 
-pub async fn run(mut player_1: impl Adaptor + Unpin + Send, mut player_2: impl Adaptor + Unpin + Send) {
-    player_1.send_command(Command::BoardSize(None, 19)).await;
-    player_2.send_command(Command::BoardSize(None, 19)).await;
-
-    player_1.send_command(Command::Komi(None, 6.5)).await;
-    player_2.send_command(Command::Komi(None, 6.5)).await;
-
-    loop {
-        let command_result = player_1.send_command(Command::GenMove(None, Color::Black)).await;
-        let command_result = command_result.unwrap();
-
-        match command_result {
-            CommandResult::GenMove(id, mov) => {
-                match mov {
-                    GenMoveResult::Resign => {
-                        panic!("Not implemented");
-                    },
-                    GenMoveResult::Move(vertex) => {
-                        player_2.send_command(
-                            Command::Play(None, Move {
-                                color: Color::White,
-                                vertex: vertex,
-                            })
-                        ).await;
-                    }
-                }
-            },
-            _ => {
-                panic!("Not expect result");
-            },
+/// How the game ends.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEnd {
+    /// Both players pass in a row and the board is counted, the dead stones are the ones agreed
+    /// by the players.
+    Scored,
+    /// Both players pass in a row but disagree on the dead stones, the game is void.
+    Disputed {
+        black: Vec<Vertex>,
+        white: Vec<Vertex>,
+    },
+    Resigned(Player),
+    /// The player makes the illegal move, with the reason.
+    Illegal(Player, String),
+    /// The player fails the command or disconnects.
+    Failed(Player, ResponseError),
+    /// The game reaches the move limit and is void.
+    MoveLimit,
+}
+
+/// The finished game.
+pub struct Game {
+    pub end: GameEnd,
+    /// The record of the game with the result and the names of the players.
+    pub record: GoGameEngine,
+}
+
+impl Game {
+    pub fn result(&self) -> GameResult {
+        self.record.result().unwrap_or(GameResult::Void)
+    }
+
+    /// Writes the record to a new SGF file in the directory.
+    pub fn save(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        let path = dir.join(format!("tgoban-{}.sgf", time.as_nanos()));
+
+        std::fs::write(&path, self.record.to_sgf())?;
+
+        Ok(path)
+    }
+}
+
+/// Sets up the player for the game, the rules are set only if the player knows `kata-set-rules`.
+///
+/// The name of the player is given by `name` and `version`, None if the player doesn't tell.
+/// Only `boardsize` and `komi` are sent to the player not knowing the other commands, such as
+/// the web frontend.
+pub async fn setup(player: &mut (impl Adaptor + Unpin + Send), settings: &GameSettings) -> Result<Option<String>, ResponseError> {
+    player.send_command(Command::BoardSize(None, settings.board_size as Integer)).await?;
+
+    if player.knows_command("clear_board") {
+        player.send_command(Command::ClearBoard(None)).await?;
+    }
+
+    player.send_command(Command::Komi(None, settings.komi as Float)).await?;
+
+    if let (Some(rules), true) = (&settings.rules, player.knows_command("known_command")) {
+        let known_command = player.send_command(Command::KnownCommand(None, "kata-set-rules".to_string())).await;
+
+        if let Ok(CommandResult::KnownCommand(_, true)) = known_command {
+            player.send_command(Command::Custom {
+                id: None,
+                name: "kata-set-rules".to_string(),
+                args: vec![rules.clone()],
+            }).await?;
+        }
+    }
+
+    if !player.knows_command("name") {
+        return Ok(None);
+    }
+
+    let name = match player.send_command(Command::Name(None)).await {
+        Ok(CommandResult::Name(_, name)) if !name.trim().is_empty() => name.trim().to_string(),
+        _ => return Ok(None),
+    };
+
+    if !player.knows_command("version") {
+        return Ok(Some(name));
+    }
+
+    match player.send_command(Command::Version(None)).await {
+        Ok(CommandResult::Version(_, version)) if !version.trim().is_empty() => Ok(Some(format!("{} {}", name, version.trim()))),
+        _ => Ok(Some(name)),
+    }
+}
+
+/// Plays the game between the players, the moves are checked by the referee before they are forwarded.
+///
+/// The player failing a command or disconnecting loses by the forfeit. The result is announced to
+/// both players knowing the commands by `tgoban-game_over <result>` before they quit.
+pub async fn run(mut black: impl Adaptor + Unpin + Send, mut white: impl Adaptor + Unpin + Send, settings: &GameSettings) -> Game {
+    let mut referee = Referee::new(settings);
+
+    let mut end = None;
+    for player in [Player::Black, Player::White].iter() {
+        let name = match player {
+            Player::Black => setup(&mut black, settings).await,
+            Player::White => setup(&mut white, settings).await,
         };
 
-        let command_result = player_2.send_command(Command::GenMove(None, Color::White)).await;
-        let command_result = command_result.unwrap();
-
-        match command_result {
-            CommandResult::GenMove(id, mov) => {
-                match mov {
-                    GenMoveResult::Resign => {
-                        panic!("Not implemented");
-                    },
-                    GenMoveResult::Move(vertex) => {
-                        player_1.send_command(
-                            Command::Play(None, Move {
-                                color: Color::Black,
-                                vertex: vertex,
-                            })
-                        ).await;
-                    }
-                }
-            },
-            _ => {
-                panic!("Not expected result");
+        match name {
+            Ok(Some(name)) => referee.engine_mut().set_player_name(*player, &name),
+            Ok(None) => {},
+            Err(error) => {
+                referee.forfeit(*player);
+                end = Some(GameEnd::Failed(*player, error));
+                break;
             },
         };
     }
+
+    while end.is_none() {
+        end = match referee.player() {
+            Player::Black => turn(&mut black, &mut white, &mut referee).await,
+            Player::White => turn(&mut white, &mut black, &mut referee).await,
+        };
+    }
+
+    let game = Game {
+        end: end.unwrap(),
+        record: referee.into_engine(),
+    };
+
+    let result = game.result().to_string();
+    announce(&mut black, &result).await;
+    announce(&mut white, &result).await;
+
+    game
+}
+
+/// Tells the player the result and quits, the player may be gone already or never answer.
+async fn announce(player: &mut (impl Adaptor + Unpin + Send), result: &str) {
+    if player.knows_command("tgoban-game_over") {
+        let _ = tokio::time::timeout(ANNOUNCE_TIMEOUT, player.send_command(Command::Custom {
+            id: None,
+            name: "tgoban-game_over".to_string(),
+            args: vec![result.to_string()],
+        })).await;
+    }
+
+    if player.knows_command("quit") {
+        let _ = tokio::time::timeout(ANNOUNCE_TIMEOUT, player.send_command(Command::Quit(None))).await;
+    }
+}
+
+/// Asks the player to move for the move and forwards it to the opponent, the end of the game if
+/// the game is over.
+async fn turn(player: &mut (impl Adaptor + Unpin + Send), opponent: &mut (impl Adaptor + Unpin + Send), referee: &mut Referee) -> Option<GameEnd> {
+    let mover = referee.player();
+    let color = to_color(mover);
+
+    let command_result = match player.send_command(Command::GenMove(None, color)).await {
+        Ok(command_result) => command_result,
+        Err(error) => {
+            referee.forfeit(mover);
+            return Some(GameEnd::Failed(mover, error));
+        },
+    };
+
+    let vertex = match command_result {
+        CommandResult::GenMove(_id, GenMoveResult::Move(vertex)) => vertex,
+        CommandResult::GenMove(_id, GenMoveResult::Resign) => {
+            referee.resign(mover);
+            return Some(GameEnd::Resigned(mover));
+        },
+        command_result => {
+            referee.forfeit(mover);
+            return Some(GameEnd::Failed(mover, ResponseError::Malformed(command_result.to_string())));
+        },
+    };
+
+    if let Err(violation) = referee.play(color, vertex) {
+        referee.forfeit(mover);
+        return Some(GameEnd::Illegal(mover, violation.to_string()));
+    }
+
+    let play = opponent.send_command(Command::Play(None, Move {
+        color,
+        vertex,
+    })).await;
+
+    if let Err(error) = play {
+        referee.forfeit(mover.switch());
+        return Some(GameEnd::Failed(mover.switch(), error));
+    }
+
+    if referee.is_counting() {
+        return Some(match mover {
+            Player::Black => count(player, opponent, referee).await,
+            Player::White => count(opponent, player, referee).await,
+        });
+    }
+
+    match referee.settings().max_moves {
+        Some(max_moves) if referee.engine().steps() >= max_moves as i32 => {
+            referee.void();
+            Some(GameEnd::MoveLimit)
+        },
+        _ => None,
+    }
+}
+
+/// Counts the board by the dead stones of the players as twogtp does, the player not knowing
+/// `final_status_list` agrees with the other one.
+async fn count(black: &mut (impl Adaptor + Unpin + Send), white: &mut (impl Adaptor + Unpin + Send), referee: &mut Referee) -> GameEnd {
+    let dead = match (dead_stones(black, referee).await, dead_stones(white, referee).await) {
+        (Some(black), Some(white)) if black != white => {
+            referee.dispute(&black, &white);
+            return GameEnd::Disputed {
+                black,
+                white,
+            };
+        },
+        (Some(dead), _) | (None, Some(dead)) => dead,
+        (None, None) => Vec::new(),
+    };
+
+    let locations: Vec<Location> = dead.iter()
+        .filter_map(|vertex| vertex.to_number().ok())
+        .map(|(alphabet, digit)| Location {
+            alphabet,
+            digit,
+        })
+        .collect();
+
+    referee.score(&locations);
+
+    GameEnd::Scored
+}
+
+/// The sorted dead stones by `final_status_list dead`, None if the player doesn't tell. The
+/// vertices off the board or without a stone are dropped.
+async fn dead_stones(player: &mut (impl Adaptor + Unpin + Send), referee: &Referee) -> Option<Vec<Vertex>> {
+    if !player.knows_command("final_status_list") {
+        return None;
+    }
+
+    match player.send_command(Command::FinalStatusList(None, FinalStatus::Dead)).await {
+        Ok(CommandResult::FinalStatusList(_, mut vertices)) => {
+            let board = referee.engine().board();
+            vertices.retain(|vertex| match vertex.to_number() {
+                Ok((alphabet, digit)) => vertex.is_on_board(board.size()) && board.get(&Location {
+                    alphabet,
+                    digit,
+                }) != ChessType::None,
+                Err(_) => false,
+            });
+            vertices.sort_by_key(|vertex| vertex.to_number().ok());
+            vertices.dedup();
+
+            Some(vertices)
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+
+    use super::super::mock_adaptor::{MockAdaptor, MockEngine};
+    use super::super::ws_adaptor::FRONTEND_COMMANDS;
+
+    fn settings() -> GameSettings {
+        GameSettings {
+            board_size: 9,
+            komi: 6.5,
+            rules: None,
+            max_moves: None,
+        }
+    }
+
+    fn mock(script: &str) -> MockAdaptor {
+        MockAdaptor::new(MockEngine::parse_script(script).unwrap())
+    }
+
+    /// The stand-in of the web frontend, answering only its commands. Any other command is
+    /// answered by the next click of the human, as the frontend would.
+    struct Frontend {
+        clicks: Vec<&'static str>,
+        received: Vec<String>,
+    }
+
+    impl Adaptor for Frontend {
+        fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+            self.received.push(command.to_string());
+
+            let result = match &command {
+                Command::BoardSize(..) | Command::Komi(..) | Command::Play(..) => command.parse_result("="),
+                _ if self.clicks.is_empty() => Err(ResponseError::Closed),
+                _ => command.parse_result(&format!("= {}", self.clicks.remove(0))),
+            };
+
+            Box::new(Box::pin(async move {
+                result
+            }))
+        }
+
+        fn knows_command(&self, name: &str) -> bool {
+            FRONTEND_COMMANDS.contains(&name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frontend() {
+        let mut human = Frontend {
+            clicks: vec!["E5", "pass"],
+            received: Vec::new(),
+        };
+
+        let game = run(&mut human, mock(""), &settings()).await;
+        assert_eq!(game.end, GameEnd::Scored);
+        assert_eq!(game.record.get_chess(go_game_engine::Location { alphabet: 4, digit: 4 }), go_game_engine::ChessType::Black);
+        assert_eq!(human.received, [
+            "boardsize 9".to_string(),
+            "komi 6.5".to_string(),
+            "genmove b".to_string(),
+            "play w pass".to_string(),
+            "genmove b".to_string(),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_announce_timeout() {
+        let mut white = mock("genmove resign\ntgoban-game_over delay 60000 ok");
+
+        let game = tokio::time::timeout(Duration::from_secs(10), run(mock(""), &mut white, &settings())).await;
+        assert_eq!(game.map(|game| game.end), Ok(GameEnd::Resigned(Player::White)));
+        assert_eq!(white.engine().received().last(), Some(&"quit".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_two_passes() {
+        let mut black = mock("genmove move E5\nversion ok 3.8");
+        let mut white = mock("name ok Leela\nversion fail unknown command");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Scored);
+        assert_eq!(game.result(), GameResult::Score(Player::Black, 73.5));
+        assert!(game.record.to_sgf().contains("RE[B+73.5]PB[Mock 3.8]PW[Leela]"));
+
+        assert_eq!(white.engine().received()[5..], [
+            "play b E5".to_string(),
+            "genmove w".to_string(),
+            "play b pass".to_string(),
+            "final_status_list dead".to_string(),
+            "tgoban-game_over B+73.5".to_string(),
+            "quit".to_string(),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_dead_stones() {
+        let mut black = mock("genmove move E5\ngenmove move C4\nfinal_status_list ok C3 C2");
+        let mut white = mock("genmove move C3\ngenmove move C2\nfinal_status_list ok C2\\nC3");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Scored);
+        assert_eq!(game.result(), GameResult::Score(Player::Black, 74.5));
+
+        /* White thinks its stones alive */
+        let game = run(mock("genmove move E5\ngenmove move C4\nfinal_status_list ok C3 C2"), mock("genmove move C3\ngenmove move C2"), &settings()).await;
+        assert_eq!(game.end, GameEnd::Disputed {
+            black: vec![Vertex::Coordinate('C', 2), Vertex::Coordinate('C', 3)],
+            white: Vec::new(),
+        });
+        assert_eq!(game.result(), GameResult::Void);
+        assert!(game.record.to_sgf().contains("Dead stones disputed\nBlack: C2 C3\nWhite: ]"));
+
+        /* The vertices off the board or without a stone are dropped */
+        let game = run(mock("genmove move E5\ngenmove move C4\nfinal_status_list ok C3 Z25 C2 A1"), mock("genmove move C3\ngenmove move C2\nfinal_status_list ok C2 C3"), &settings()).await;
+        assert_eq!(game.end, GameEnd::Scored);
+        assert_eq!(game.result(), GameResult::Score(Player::Black, 74.5));
+    }
+
+    #[tokio::test]
+    async fn test_illegal_move() {
+        let mut black = mock("genmove move E5");
+        let mut white = mock("genmove move E5");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Illegal(Player::White, "illegal move E5 on the stone".to_string()));
+        assert_eq!(game.result(), GameResult::Forfeit(Player::Black));
+        assert_eq!(black.engine().received()[6..], ["tgoban-game_over B+F".to_string(), "quit".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_resign() {
+        let mut black = mock("genmove move E5");
+        let mut white = mock("genmove resign");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Resigned(Player::White));
+        assert!(game.record.to_sgf().contains("RE[B+R]"));
+    }
+
+    #[tokio::test]
+    async fn test_failures() {
+        let mut black = mock("genmove move E5\ngenmove raw garbage");
+        let mut white = mock("");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Failed(Player::Black, ResponseError::Malformed("garbage".to_string())));
+        assert_eq!(game.result(), GameResult::Forfeit(Player::White));
+
+        let mut black = mock("");
+        let mut white = mock("boardsize fail unacceptable size");
+
+        let game = run(&mut black, &mut white, &settings()).await;
+        assert_eq!(game.end, GameEnd::Failed(Player::White, ResponseError::Failure(None, "unacceptable size".to_string())));
+        assert_eq!(black.engine().received().iter().filter(|command| command.starts_with("genmove")).count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_move_limit() {
+        let settings = GameSettings {
+            max_moves: Some(3),
+            ..settings()
+        };
+
+        let game = run(mock("genmove move E5\ngenmove move C3"), mock("genmove move G7"), &settings).await;
+        assert_eq!(game.end, GameEnd::MoveLimit);
+        assert_eq!(game.result(), GameResult::Void);
+        assert_eq!(game.record.steps(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_save() {
+        let game = run(mock("genmove resign"), mock(""), &settings()).await;
+
+        let path = game.save(&std::env::temp_dir()).unwrap();
+        let record = GoGameEngine::from_sgf(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(record.result(), Some(GameResult::Resign(Player::White)));
+        assert_eq!(record.player_name(Player::Black), Some("Mock 1.0"));
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -1,16 +1,21 @@
 use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
+use futures_util::stream::Stream;
 use tokio::io::{AsyncWrite, AsyncRead};
-use tokio::io::BufReader;
+use tokio::io::{BufReader, Lines};
 use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt};
 
-use tgoban_gtp::{Command, CommandResult};
+use tgoban_gtp::{Analyze, AnalysisUpdate, Analyzer, Command, CommandResult, ResponseError};
 
 use super::adaptor::Adaptor;
 
 pub struct AsyncAdaptor <R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send>{
     reader: BufReader<R>,
     writer: W,
+    /// The analysis is dropped before its end, it is interrupted before the next command.
+    is_analyzing: bool,
 }
 
 impl<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> AsyncAdaptor<R, W> {
@@ -23,38 +28,279 @@ impl<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> AsyncAdaptor<R,
         AsyncAdaptor {
             reader: BufReader::new(reader),
             writer: writer,
+            is_analyzing: false,
         }
     }
+
+    /// Interrupts the analysis left running by the dropped `Analysis`, and drops the rest of it.
+    async fn interrupt(&mut self) -> Result<(), ResponseError> {
+        if !self.is_analyzing {
+            return Ok(());
+        }
+
+        interrupt_analysis(&mut self.reader, &mut self.writer).await?;
+
+        self.is_analyzing = false;
+
+        Ok(())
+    }
+
+    /// Reads the lines of the next response until the empty line ending it.
+    async fn read_lines(&mut self) -> Result<String, ResponseError> {
+        let mut lines = "".to_string();
+
+        loop {
+            let mut line = "".to_string();
+
+            match self.reader.read_line(&mut line).await {
+                /* The engine exits */
+                Ok(0) | Err(_) => return Err(ResponseError::Closed),
+                Ok(1) => break,
+                Ok(_) => lines.push_str(&line),
+            };
+        }
+
+        Ok(lines)
+    }
+
+    /// Starts the analysis of the engine, the updates are streamed until it is stopped.
+    pub async fn analyze(&mut self, analyze: &Analyze) -> Result<Analysis<'_, R, W>, ResponseError> {
+        let command = analyze.command(None);
+
+        self.interrupt().await?;
+
+        if self.writer.write_all(format!("{}\n", command.to_string()).as_bytes()).await.is_err()
+            || self.writer.flush().await.is_err() {
+            return Err(ResponseError::Closed);
+        }
+
+        let mut header = "".to_string();
+        match self.reader.read_line(&mut header).await {
+            Ok(0) | Err(_) => return Err(ResponseError::Closed),
+            Ok(_) => {},
+        };
+
+        /* The failure is ended by the empty line as the other responses */
+        if !header.starts_with('=') {
+            let lines = header + &self.read_lines().await?;
+
+            return match command.parse_result(&lines) {
+                Ok(_) => Err(ResponseError::Malformed(lines)),
+                Err(error) => Err(error),
+            };
+        }
+
+        Ok(Analysis {
+            analyzer: analyze.analyzer,
+            lines: (&mut self.reader).lines(),
+            writer: &mut self.writer,
+            is_analyzing: &mut self.is_analyzing,
+            is_ended: false,
+        })
+    }
+}
+
+/// Interrupts the running analysis, then drops the rest of it and the response of the command
+/// interrupting it.
+async fn interrupt_analysis(reader: &mut (impl AsyncBufRead + Unpin), writer: &mut (impl AsyncWrite + Unpin)) -> Result<(), ResponseError> {
+    /* Any command interrupts the analysis, the harmless one is sent and its response is dropped */
+    if writer.write_all(b"protocol_version\n").await.is_err() || writer.flush().await.is_err() {
+        return Err(ResponseError::Closed);
+    }
+
+    /* The rest of the analysis, then the response of protocol_version */
+    for _ in 0..2 {
+        loop {
+            let mut line = "".to_string();
+
+            match reader.read_line(&mut line).await {
+                Ok(0) | Err(_) => return Err(ResponseError::Closed),
+                Ok(_) if line.trim().is_empty() => break,
+                Ok(_) => {},
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// The running analysis of the engine, a stream of the updates.
+///
+/// The adaptor is borrowed until the analysis is stopped, the stream ends if the engine
+/// stops the analysis itself. The analysis dropped before its end is interrupted by the next
+/// command of the adaptor.
+pub struct Analysis<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> {
+    analyzer: Analyzer,
+    lines: Lines<&'a mut BufReader<R>>,
+    writer: &'a mut W,
+    is_analyzing: &'a mut bool,
+    /// The empty line ending the response is read.
+    is_ended: bool,
+}
+
+impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> Analysis<'a, R, W> {
+    /// Interrupts the analysis and waits for the end of it, the adaptor is ready for the next command.
+    pub async fn stop(mut self) -> Result<(), ResponseError> {
+        if self.is_ended {
+            return Ok(());
+        }
+
+        interrupt_analysis(self.lines.get_mut(), &mut *self.writer).await?;
+
+        self.is_ended = true;
+
+        Ok(())
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> Drop for Analysis<'a, R, W> {
+    fn drop(&mut self) {
+        *self.is_analyzing = !self.is_ended;
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> Stream for Analysis<'a, R, W> {
+    type Item = Result<AnalysisUpdate, ResponseError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let analysis = self.get_mut();
+
+        while !analysis.is_ended {
+            let line = match Pin::new(&mut analysis.lines).poll_next_line(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(Some(line))) => line,
+                Poll::Ready(Ok(None)) | Poll::Ready(Err(_)) => {
+                    analysis.is_ended = true;
+                    return Poll::Ready(Some(Err(ResponseError::Closed)));
+                },
+            };
+
+            if line.trim().is_empty() {
+                analysis.is_ended = true;
+                break;
+            }
+
+            /* The engine may print the other lines, such as the ones of play in kata-analyze */
+            if !line.starts_with("info") {
+                continue;
+            }
+
+            return Poll::Ready(Some(AnalysisUpdate::parse(&line, analysis.analyzer).map_err(ResponseError::from)));
+        }
+
+        Poll::Ready(None)
+    }
 }
 
 
 impl<R: AsyncRead + Unpin + Send, W: AsyncWrite + Unpin + Send> Adaptor for AsyncAdaptor<R, W> {
-    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ()>> + Unpin + Send + 'a> {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
         let future = async move {
-            let mut lines = "".to_string();
-
-            self.writer.write(format!("{}\n", command.to_string()).as_bytes()).await;
-
-            loop {
-                let mut line = "".to_string();
-                let result = self.reader.read_line(&mut line).await;
-
-                match result {
-                    Ok(size) => {
-                        if size == 1 {
-                            break;
-                        }
-                        lines.push_str(&line);
-                    },
-                    Err(_) => {
-                        return Err(());
-                    },
-                };
+            self.interrupt().await?;
+
+            let line = format!("{}\n", command.to_string());
+            if self.writer.write_all(line.as_bytes()).await.is_err() || self.writer.flush().await.is_err() {
+                return Err(ResponseError::Closed);
             }
 
+            let lines = self.read_lines().await?;
+
             return command.parse_result(&lines);
         };
 
         Box::new(Box::pin(future))
     }
+
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        let future = async move {
+            self.interrupt().await?;
+            self.read_lines().await
+        };
+
+        Box::new(Box::pin(future))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream::StreamExt;
+    use tgoban_gtp::Analyzer;
+
+    #[tokio::test]
+    async fn test_analyze() {
+        let input = "=\ninfo move D4 visits 1 winrate 5000 prior 100 order 0 pv D4\ninfo move Q16 visits 2 winrate 5100 prior 100 order 0 pv Q16 D4\ninfo move Q16 visits 3 winrate 5200 prior 100 order 0 pv Q16\n\n= 2\n\n= Mock\n\n";
+        let mut output: Vec<u8> = Vec::new();
+        let mut adaptor = AsyncAdaptor::new(input.as_bytes(), &mut output);
+
+        let analyze = Analyze {
+            analyzer: Analyzer::Leela,
+            color: None,
+            interval: 10,
+            ownership: false,
+        };
+        let mut analysis = adaptor.analyze(&analyze).await.unwrap();
+
+        let update = analysis.next().await.unwrap().unwrap();
+        assert_eq!(update.candidates[0].vertex.to_string(), "D4");
+
+        let update = analysis.next().await.unwrap().unwrap();
+        assert_eq!(update.candidates[0].visits, 2);
+        assert_eq!(update.candidates[0].pv.len(), 2);
+
+        analysis.stop().await.unwrap();
+
+        match adaptor.send_command(Command::Name(None)).await {
+            Ok(CommandResult::Name(None, name)) => assert_eq!(name, "Mock"),
+            _ => assert!(false, "The adaptor is out of sync after the analysis"),
+        };
+
+        drop(adaptor);
+        assert_eq!(String::from_utf8(output).unwrap(), "lz-analyze 10\nprotocol_version\nname\n");
+    }
+
+    #[tokio::test]
+    async fn test_analyze_dropped() {
+        let input = "=\ninfo move D4 visits 1 winrate 5000 prior 100 order 0 pv D4\ninfo move Q16 visits 2 winrate 5100 prior 100 order 0 pv Q16 D4\n\n= 2\n\n= Mock\n\n";
+        let mut output: Vec<u8> = Vec::new();
+        let mut adaptor = AsyncAdaptor::new(input.as_bytes(), &mut output);
+
+        let analyze = Analyze {
+            analyzer: Analyzer::Leela,
+            color: None,
+            interval: 10,
+            ownership: false,
+        };
+
+        {
+            let mut analysis = adaptor.analyze(&analyze).await.unwrap();
+            assert!(analysis.next().await.unwrap().is_ok());
+        }
+
+        match adaptor.send_command(Command::Name(None)).await {
+            Ok(CommandResult::Name(None, name)) => assert_eq!(name, "Mock"),
+            _ => assert!(false, "The adaptor is out of sync after the analysis is dropped"),
+        };
+
+        drop(adaptor);
+        assert_eq!(String::from_utf8(output).unwrap(), "lz-analyze 10\nprotocol_version\nname\n");
+    }
+
+    #[tokio::test]
+    async fn test_analyze_failure() {
+        let mut output: Vec<u8> = Vec::new();
+        let mut adaptor = AsyncAdaptor::new("? unknown command\n\n".as_bytes(), &mut output);
+
+        let analyze = Analyze {
+            analyzer: Analyzer::Kata,
+            color: None,
+            interval: 10,
+            ownership: true,
+        };
+
+        match adaptor.analyze(&analyze).await {
+            Err(ResponseError::Failure(None, message)) => assert_eq!(message, "unknown command"),
+            _ => assert!(false, "The failure is not reported"),
+        };
+    }
 }
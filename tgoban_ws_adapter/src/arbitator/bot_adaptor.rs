@@ -0,0 +1,396 @@
+use std::future::Future;
+
+use go_game_engine::{ChessType, GoGameEngine, Location, MoveError, fixed_handicap};
+
+use tgoban_bot::{Bot, BotMove};
+use tgoban_gtp::{Command, CommandResult, Color, FinalStatus, GenMoveResult, Id, ResponseError, Score, Vertex};
+
+use super::adaptor::Adaptor;
+use super::gogui;
+
+const KNOWN_COMMANDS: [&str; 24] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "reg_genmove",
+    "final_score",
+    "undo",
+    "showboard",
+    "final_status_list",
+    "fixed_handicap",
+    "place_free_handicap",
+    "set_free_handicap",
+    "loadsgf",
+    "time_settings",
+    "time_left",
+    "kgs-time_settings",
+    "kgs-genmove_cleanup",
+];
+
+fn player(color: &Color) -> go_game_engine::Player {
+    match color {
+        Color::Black => go_game_engine::Player::Black,
+        Color::White => go_game_engine::Player::White,
+    }
+}
+
+/// Plays the in-process bot through the GTP commands, no external engine is needed.
+pub struct BotAdaptor<B: Bot + Send> {
+    bot: B,
+    engine: GoGameEngine,
+}
+
+impl<B: Bot + Send> BotAdaptor<B> {
+    pub fn new(bot: B, size: u8, komi: f64) -> BotAdaptor<B> {
+        BotAdaptor {
+            bot,
+            engine: GoGameEngine::new(size, komi),
+        }
+    }
+
+    /// Passes for the player to move if the command is for the other color,
+    /// nothing is changed and false is returned if the pass would end the game.
+    fn turn_to(&mut self, color: &Color) -> bool {
+        if self.engine.player() == player(color) {
+            return true;
+        }
+
+        if self.engine.is_head_pass() {
+            return false;
+        }
+
+        self.engine.pass();
+        true
+    }
+
+    /// Asks the bot for a move, the move is played only if the play is true.
+    fn generate(&mut self, id: Option<Id>, color: &Color, play: bool) -> Result<GenMoveResult, ResponseError> {
+        if !self.turn_to(color) {
+            return Err(ResponseError::Failure(id, "illegal move".to_string()));
+        }
+
+        let result = match self.bot.genmove(&self.engine) {
+            BotMove::Play(location) => {
+                if play && self.engine.make_move(location).is_err() {
+                    return Err(ResponseError::Failure(id, "illegal move".to_string()));
+                }
+                GenMoveResult::Move(Vertex::from_number(location.alphabet, location.digit))
+            },
+            BotMove::Pass => {
+                if play {
+                    self.engine.pass();
+                }
+                GenMoveResult::Move(Vertex::Pass)
+            },
+            BotMove::Resign => GenMoveResult::Resign,
+        };
+
+        Ok(result)
+    }
+
+    /// Places the handicap stones on the empty board.
+    fn handicap(&mut self, id: Option<Id>, locations: Option<Vec<Location>>) -> Result<Vec<Vertex>, ResponseError> {
+        let locations = match locations {
+            Some(locations) => locations,
+            None => return Err(ResponseError::Failure(id, "invalid number of stones".to_string())),
+        };
+
+        match self.engine.set_handicap(&locations) {
+            Ok(_) => {},
+            Err(MoveError::NoMove) => return Err(ResponseError::Failure(id, "board not empty".to_string())),
+            Err(_) => return Err(ResponseError::Failure(id, "bad vertex list".to_string())),
+        };
+
+        Ok(locations.iter().map(|location| Vertex::from_number(location.alphabet, location.digit)).collect())
+    }
+
+    fn handle(&mut self, command: &Command) -> Result<CommandResult, ResponseError> {
+        let result = match command {
+            Command::ProtocolVersion(id) => CommandResult::ProtocolVersion(*id, 2),
+            Command::Name(id) => CommandResult::Name(*id, self.bot.name()),
+            Command::Version(id) => CommandResult::Version(*id, self.bot.version()),
+            Command::KnownCommand(id, command) => CommandResult::KnownCommand(*id, KNOWN_COMMANDS.contains(&command.as_str()) || gogui::COMMANDS.contains(&command.as_str())),
+            Command::ListCommands(id) => CommandResult::ListCommands(*id, KNOWN_COMMANDS.iter().chain(gogui::COMMANDS.iter()).map(|command| command.to_string()).collect()),
+            Command::Quit(id) => CommandResult::Quit(*id),
+            Command::BoardSize(id, size) => {
+                if *size < 1 || *size as usize > go_game_engine::BOARD_SIZE_MAX {
+                    return Err(ResponseError::Failure(*id, "unacceptable size".to_string()));
+                }
+                self.engine = GoGameEngine::new(*size as u8, self.engine.komi());
+                CommandResult::BoardSize(*id)
+            },
+            Command::ClearBoard(id) => {
+                self.engine = GoGameEngine::new(self.engine.size(), self.engine.komi());
+                CommandResult::ClearBoard(*id)
+            },
+            Command::Komi(id, komi) => {
+                self.engine.set_komi(*komi as f64);
+                CommandResult::Komi(*id)
+            },
+            Command::Play(id, mov) => {
+                /* Checked on the position after the implied pass, before anything is changed */
+                let is_legal = match mov.vertex.to_number() {
+                    Ok((alphabet, digit)) => mov.vertex.is_on_board(self.engine.size())
+                        && self.engine.is_legal_for(player(&mov.color), Location { alphabet, digit }),
+                    /* An implied pass followed by the pass would end the game */
+                    Err(_) => self.engine.player() == player(&mov.color),
+                };
+
+                if !is_legal || !self.turn_to(&mov.color) {
+                    return Err(ResponseError::Failure(*id, "illegal move".to_string()));
+                }
+
+                match mov.vertex.to_number() {
+                    Ok((alphabet, digit)) => {
+                        if self.engine.make_move(Location { alphabet, digit }).is_err() {
+                            return Err(ResponseError::Failure(*id, "illegal move".to_string()));
+                        }
+                    },
+                    Err(_) => self.engine.pass(),
+                };
+
+                CommandResult::Play(*id)
+            },
+            Command::GenMove(id, color) => CommandResult::GenMove(*id, self.generate(*id, color, true)?),
+            Command::RegGenMove(id, color) => CommandResult::RegGenMove(*id, self.generate(*id, color, false)?),
+            Command::FinalScore(id) => {
+                let (black, white) = self.engine.estimate_score().score;
+
+                let score = match black - white {
+                    diff if diff > 0.0 => Score::Black(diff as f32),
+                    diff if diff < 0.0 => Score::White(-diff as f32),
+                    _ => Score::Jigo,
+                };
+
+                CommandResult::FinalScore(*id, score)
+            },
+            Command::Undo(id) => {
+                if self.engine.steps() == 0 {
+                    return Err(ResponseError::Failure(*id, "cannot undo".to_string()));
+                }
+
+                self.engine.regret();
+                CommandResult::Undo(*id)
+            },
+            Command::ShowBoard(id) => CommandResult::ShowBoard(*id, self.engine.board().to_string().lines().map(|line| line.to_string()).collect()),
+            Command::FinalStatusList(id, status) => {
+                let dead = self.engine.estimate_score().influence.dead;
+                let size = self.engine.size();
+
+                let mut vertices: Vec<Vertex> = Vec::new();
+                for alphabet in 0..size {
+                    for digit in 0..size {
+                        let location = Location {
+                            alphabet,
+                            digit,
+                        };
+
+                        if self.engine.get_chess(location) == ChessType::None {
+                            continue;
+                        }
+
+                        /* The estimation knows no seki */
+                        let is_listed = match status {
+                            FinalStatus::Alive => !dead.contains(&location),
+                            FinalStatus::Dead => dead.contains(&location),
+                            FinalStatus::Seki => false,
+                        };
+
+                        if is_listed {
+                            vertices.push(Vertex::from_number(alphabet, digit));
+                        }
+                    }
+                }
+
+                CommandResult::FinalStatusList(*id, vertices)
+            },
+            Command::FixedHandicap(id, stones) | Command::PlaceFreeHandicap(id, stones) => {
+                let locations = fixed_handicap(self.engine.size(), *stones as usize);
+                let vertices = self.handicap(*id, locations)?;
+
+                match command {
+                    Command::FixedHandicap(_, _) => CommandResult::FixedHandicap(*id, vertices),
+                    _ => CommandResult::PlaceFreeHandicap(*id, vertices),
+                }
+            },
+            Command::SetFreeHandicap(id, vertices) => {
+                let size = self.engine.size();
+
+                let mut locations: Vec<Location> = Vec::new();
+                for vertex in vertices {
+                    match vertex.to_number() {
+                        Ok((alphabet, digit)) if alphabet < size && digit < size => locations.push(Location { alphabet, digit }),
+                        _ => return Err(ResponseError::Failure(*id, "bad vertex list".to_string())),
+                    };
+                }
+
+                if locations.len() < 2 {
+                    return Err(ResponseError::Failure(*id, "bad vertex list".to_string()));
+                }
+
+                self.handicap(*id, Some(locations))?;
+                CommandResult::SetFreeHandicap(*id)
+            },
+            Command::LoadSgf(id, file, move_number) => {
+                let engine = match std::fs::read_to_string(file) {
+                    Ok(sgf) => GoGameEngine::from_sgf(&sgf).ok(),
+                    Err(_) => None,
+                };
+
+                self.engine = match engine {
+                    Some(engine) => engine,
+                    None => return Err(ResponseError::Failure(*id, "cannot load file".to_string())),
+                };
+
+                /* The position before the move of the number */
+                if let Some(move_number) = move_number {
+                    while self.engine.steps() >= *move_number && self.engine.back() {}
+                }
+
+                CommandResult::LoadSgf(*id)
+            },
+            /* The bot plays without the clock */
+            Command::TimeSettings(id, _, _, _) => CommandResult::TimeSettings(*id),
+            Command::TimeLeft(id, _, _, _) => CommandResult::TimeLeft(*id),
+            Command::KgsTimeSettings(id, _) => CommandResult::KgsTimeSettings(*id),
+            Command::KgsGenMoveCleanup(id, color) => CommandResult::KgsGenMoveCleanup(*id, self.generate(*id, color, true)?),
+            Command::Custom { id, name, .. } => match gogui::analyze(&self.engine, name) {
+                Some(response) => CommandResult::Raw(*id, response),
+                None => return Err(ResponseError::Failure(*id, "unknown command".to_string())),
+            },
+        };
+
+        Ok(result)
+    }
+}
+
+impl<B: Bot + Send> Adaptor for BotAdaptor<B> {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+        let result = self.handle(&command);
+
+        Box::new(Box::pin(async move {
+            result
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgoban_bot::{Level, new_bot};
+    use tgoban_gtp::Move;
+
+    #[tokio::test]
+    async fn test_bot_game() {
+        let mut players = [
+            BotAdaptor::new(new_bot(Level::Random, 1), 9, 6.5),
+            BotAdaptor::new(new_bot(Level::Greedy, 2), 9, 6.5),
+        ];
+        let color = |turn: usize| match turn % 2 {
+            0 => Color::Black,
+            _ => Color::White,
+        };
+        let mut passes = 0;
+        let mut turn = 0;
+
+        while passes < 2 && turn < 500 {
+            let (mover, other) = (turn % 2, (turn + 1) % 2);
+
+            let vertex = match players[mover].send_command(Command::GenMove(None, color(mover))).await {
+                Ok(CommandResult::GenMove(_, GenMoveResult::Move(vertex))) => vertex,
+                _ => panic!("The bot does not generate a move"),
+            };
+
+            passes = match vertex {
+                Vertex::Pass => passes + 1,
+                _ => 0,
+            };
+
+            let result = players[other].send_command(Command::Play(None, Move {
+                color: color(mover),
+                vertex,
+            })).await;
+            assert!(result.is_ok());
+
+            turn += 1;
+        }
+
+        assert_eq!(passes, 2);
+    }
+
+    #[tokio::test]
+    async fn test_handicap() {
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 9, 0.5);
+
+        match adaptor.send_command(Command::FixedHandicap(Some(1), 4)).await {
+            Ok(CommandResult::FixedHandicap(_, vertices)) => assert_eq!(vertices.len(), 4),
+            _ => panic!("The handicap is not placed"),
+        };
+
+        match adaptor.send_command(Command::FixedHandicap(Some(2), 2)).await {
+            Err(ResponseError::Failure(id, message)) => {
+                assert_eq!(id, Some(2));
+                assert_eq!(message, "board not empty");
+            },
+            _ => panic!("The handicap is placed twice"),
+        };
+
+        assert!(adaptor.send_command(Command::GenMove(None, Color::White)).await.is_ok());
+        assert!(adaptor.send_command(Command::Undo(None)).await.is_ok());
+        assert!(adaptor.send_command(Command::Undo(None)).await.is_err());
+
+        match adaptor.send_command(Command::ShowBoard(None)).await {
+            Ok(CommandResult::ShowBoard(_, board)) => assert_eq!(board.concat().matches('X').count(), 4),
+            _ => panic!("The board is not shown"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_final_status_list() {
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 19, 6.5);
+
+        play_all(&mut adaptor, &[("b D4", true), ("w Q16", true), ("b Q4", true), ("w D16", true)]).await;
+
+        match adaptor.send_command(Command::FinalStatusList(None, FinalStatus::Dead)).await {
+            Ok(CommandResult::FinalStatusList(_, vertices)) => assert!(vertices.is_empty()),
+            _ => panic!("The dead stones are not listed"),
+        };
+
+        match adaptor.send_command(Command::FinalStatusList(None, FinalStatus::Alive)).await {
+            Ok(CommandResult::FinalStatusList(_, vertices)) => assert_eq!(vertices.len(), 4),
+            _ => panic!("The alive stones are not listed"),
+        };
+    }
+
+    async fn play_all(adaptor: &mut BotAdaptor<Box<dyn Bot + Send>>, moves: &[(&str, bool)]) {
+        for (mov, is_ok) in moves.iter() {
+            let result = adaptor.send_command(format!("play {}", mov).parse().unwrap()).await;
+            assert_eq!(result.is_ok(), *is_ok, "play {}", mov);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_out_of_turn() {
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 9, 6.5);
+
+        /* The rejected move passes for nobody, the stone after two passes is rejected instead of dropped */
+        play_all(&mut adaptor, &[("w K10", false), ("b pass", true), ("w pass", true), ("b D4", false)]).await;
+        assert_eq!(adaptor.engine.get_status(), go_game_engine::GameStatus::Scoring);
+        assert_eq!(adaptor.engine.board().get(&Location { alphabet: 3, digit: 3 }), go_game_engine::ChessType::None);
+
+        let mut adaptor = BotAdaptor::new(new_bot(Level::Greedy, 1), 9, 6.5);
+
+        /* The implied pass of black would be the second pass in a row */
+        play_all(&mut adaptor, &[("b D4", true), ("w pass", true), ("w E5", false), ("w pass", false), ("b E5", true)]).await;
+        assert_eq!(adaptor.engine.get_status(), go_game_engine::GameStatus::Playing);
+        assert_eq!(adaptor.engine.moves().len(), 3);
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use tgoban_gtp::{Color, Command, CommandResult, Float, GenMoveResult, Id, Integer, Move, ResponseError, Score, Vertex};
+
+use super::adaptor::Adaptor;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GtpError {
+    Response(ResponseError),
+    /// The response carries the id of another command.
+    IdMismatch {
+        expected: Id,
+        actual: Option<Id>,
+    },
+    /// The result is not the one of the command.
+    Unexpected(String),
+    /// No response of the command within its timeout.
+    Timeout(String),
+    Cancelled(String),
+    /// A command is timed out or cancelled before, and the response left by it has no id, so it
+    /// can't be told from the later ones. The client can't recover, the engine is respawned.
+    Desynchronized,
+}
+
+impl std::fmt::Display for GtpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GtpError::Response(error) => write!(f, "{}", error),
+            GtpError::IdMismatch { expected, actual: Some(actual) } => write!(f, "response id {} instead of {}", actual, expected),
+            GtpError::IdMismatch { expected, actual: None } => write!(f, "response without id instead of {}", expected),
+            GtpError::Unexpected(command) => write!(f, "unexpected result of {}", command),
+            GtpError::Timeout(command) => write!(f, "{} timed out", command),
+            GtpError::Cancelled(command) => write!(f, "{} cancelled", command),
+            GtpError::Desynchronized => write!(f, "responses without id out of sync after the timeout or the cancellation"),
+        }
+    }
+}
+
+impl std::error::Error for GtpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GtpError::Response(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ResponseError> for GtpError {
+    fn from(error: ResponseError) -> GtpError {
+        GtpError::Response(error)
+    }
+}
+
+/// Cancels the command waiting in the client, from another task.
+#[derive(Clone)]
+pub struct CancelHandle {
+    generation: Arc<AtomicU64>,
+    notify: Arc<Notify>,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.notify.notify();
+    }
+
+    /// Waits for the cancellation after the generation.
+    async fn cancelled(&self, generation: u64) {
+        /* The permit may be left by the cancellation between the commands */
+        while self.generation.load(Ordering::SeqCst) == generation {
+            self.notify.notified().await;
+        }
+    }
+}
+
+enum Outcome {
+    Done(Result<Result<CommandResult, ResponseError>, GtpError>),
+    Timeout,
+    Cancelled,
+}
+
+/// The typed GTP client, the commands are numbered by the client and the responses are checked.
+///
+/// The adaptor answers every command by the next response, so the response of the command timed
+/// out or cancelled comes before the ones of the later commands. The next command is preceded by
+/// `protocol_version`, and the responses with the older ids are dropped until its response. If
+/// the left response has no id, every command fails with `GtpError::Desynchronized` and the
+/// caller has to quit and respawn the engine.
+pub struct GtpClient<A: Adaptor + Unpin + Send> {
+    adaptor: A,
+    next_id: Id,
+    default_timeout: Option<Duration>,
+    timeouts: HashMap<String, Duration>,
+    cancel: CancelHandle,
+    is_desynchronized: bool,
+}
+
+impl<A: Adaptor + Unpin + Send> GtpClient<A> {
+    pub fn new(adaptor: A) -> GtpClient<A> {
+        GtpClient {
+            adaptor,
+            next_id: 1,
+            default_timeout: None,
+            timeouts: HashMap::new(),
+            cancel: CancelHandle {
+                generation: Arc::new(AtomicU64::new(0)),
+                notify: Arc::new(Notify::new()),
+            },
+            is_desynchronized: false,
+        }
+    }
+
+    pub fn into_inner(self) -> A {
+        self.adaptor
+    }
+
+    /// The timeout of the commands without their own timeout, None to wait forever.
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    /// The timeout of the command name, such as a longer one for `genmove`.
+    pub fn set_timeout(&mut self, command: &str, timeout: Duration) {
+        self.timeouts.insert(command.to_string(), timeout);
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Whether the response of a command timed out or cancelled may be still coming, it is dropped
+    /// before the next command.
+    pub fn is_desynchronized(&self) -> bool {
+        self.is_desynchronized
+    }
+
+    /// Sends the command built with the next id and checks the id of the response.
+    pub async fn send(&mut self, command: impl FnOnce(Option<Id>) -> Command) -> Result<CommandResult, GtpError> {
+        let sync_id = match self.is_desynchronized {
+            true => {
+                self.next_id += 1;
+                Some(self.next_id - 1)
+            },
+            false => None,
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let command = command(Some(id));
+        let name = command.name();
+        let timeout = self.timeouts.get(&name).copied().or(self.default_timeout);
+        let generation = self.cancel.generation.load(Ordering::SeqCst);
+        let cancel = self.cancel.clone();
+
+        let outcome = {
+            let adaptor = &mut self.adaptor;
+            let is_desynchronized = &mut self.is_desynchronized;
+
+            let response = async move {
+                if let Some(sync_id) = sync_id {
+                    resync(adaptor, sync_id).await?;
+                    *is_desynchronized = false;
+                }
+
+                Ok(adaptor.send_command(command).await)
+            };
+            let timer = async {
+                match timeout {
+                    Some(timeout) => tokio::time::delay_for(timeout).await,
+                    None => std::future::pending::<()>().await,
+                }
+            };
+
+            tokio::select! {
+                result = response => Outcome::Done(result),
+                _ = timer => Outcome::Timeout,
+                _ = cancel.cancelled(generation) => Outcome::Cancelled,
+            }
+        };
+
+        let result = match outcome {
+            Outcome::Done(result) => result?,
+            Outcome::Timeout => {
+                self.is_desynchronized = true;
+                return Err(GtpError::Timeout(name));
+            },
+            Outcome::Cancelled => {
+                self.is_desynchronized = true;
+                return Err(GtpError::Cancelled(name));
+            },
+        };
+
+        let actual = match &result {
+            Ok(result) => result.id(),
+            Err(ResponseError::Failure(actual, _)) => *actual,
+            Err(_) => Some(id),
+        };
+
+        if actual != Some(id) {
+            return Err(GtpError::IdMismatch {
+                expected: id,
+                actual,
+            });
+        }
+
+        Ok(result?)
+    }
+
+    pub async fn protocol_version(&mut self) -> Result<Integer, GtpError> {
+        match self.send(Command::ProtocolVersion).await? {
+            CommandResult::ProtocolVersion(_, version) => Ok(version),
+            _ => Err(GtpError::Unexpected("protocol_version".to_string())),
+        }
+    }
+
+    pub async fn name(&mut self) -> Result<String, GtpError> {
+        match self.send(Command::Name).await? {
+            CommandResult::Name(_, name) => Ok(name),
+            _ => Err(GtpError::Unexpected("name".to_string())),
+        }
+    }
+
+    pub async fn version(&mut self) -> Result<String, GtpError> {
+        match self.send(Command::Version).await? {
+            CommandResult::Version(_, version) => Ok(version),
+            _ => Err(GtpError::Unexpected("version".to_string())),
+        }
+    }
+
+    pub async fn known_command(&mut self, command: &str) -> Result<bool, GtpError> {
+        match self.send(|id| Command::KnownCommand(id, command.to_string())).await? {
+            CommandResult::KnownCommand(_, is_known) => Ok(is_known),
+            _ => Err(GtpError::Unexpected("known_command".to_string())),
+        }
+    }
+
+    pub async fn list_commands(&mut self) -> Result<Vec<String>, GtpError> {
+        match self.send(Command::ListCommands).await? {
+            CommandResult::ListCommands(_, commands) => Ok(commands),
+            _ => Err(GtpError::Unexpected("list_commands".to_string())),
+        }
+    }
+
+    pub async fn quit(&mut self) -> Result<(), GtpError> {
+        self.send(Command::Quit).await.map(|_| ())
+    }
+
+    pub async fn boardsize(&mut self, size: Integer) -> Result<(), GtpError> {
+        self.send(|id| Command::BoardSize(id, size)).await.map(|_| ())
+    }
+
+    pub async fn clear_board(&mut self) -> Result<(), GtpError> {
+        self.send(Command::ClearBoard).await.map(|_| ())
+    }
+
+    pub async fn komi(&mut self, komi: Float) -> Result<(), GtpError> {
+        self.send(|id| Command::Komi(id, komi)).await.map(|_| ())
+    }
+
+    pub async fn play(&mut self, color: Color, vertex: Vertex) -> Result<(), GtpError> {
+        self.send(|id| Command::Play(id, Move {
+            color,
+            vertex,
+        })).await.map(|_| ())
+    }
+
+    pub async fn genmove(&mut self, color: Color) -> Result<GenMoveResult, GtpError> {
+        match self.send(|id| Command::GenMove(id, color)).await? {
+            CommandResult::GenMove(_, result) => Ok(result),
+            _ => Err(GtpError::Unexpected("genmove".to_string())),
+        }
+    }
+
+    pub async fn reg_genmove(&mut self, color: Color) -> Result<GenMoveResult, GtpError> {
+        match self.send(|id| Command::RegGenMove(id, color)).await? {
+            CommandResult::RegGenMove(_, result) => Ok(result),
+            _ => Err(GtpError::Unexpected("reg_genmove".to_string())),
+        }
+    }
+
+    pub async fn undo(&mut self) -> Result<(), GtpError> {
+        self.send(Command::Undo).await.map(|_| ())
+    }
+
+    pub async fn final_score(&mut self) -> Result<Score, GtpError> {
+        match self.send(Command::FinalScore).await? {
+            CommandResult::FinalScore(_, score) => Ok(score),
+            _ => Err(GtpError::Unexpected("final_score".to_string())),
+        }
+    }
+
+    pub async fn showboard(&mut self) -> Result<Vec<String>, GtpError> {
+        match self.send(Command::ShowBoard).await? {
+            CommandResult::ShowBoard(_, lines) => Ok(lines),
+            _ => Err(GtpError::Unexpected("showboard".to_string())),
+        }
+    }
+
+    pub async fn time_settings(&mut self, main_time: Integer, byo_yomi_time: Integer, byo_yomi_stones: Integer) -> Result<(), GtpError> {
+        self.send(|id| Command::TimeSettings(id, main_time, byo_yomi_time, byo_yomi_stones)).await.map(|_| ())
+    }
+
+    pub async fn time_left(&mut self, color: Color, time: Integer, stones: Integer) -> Result<(), GtpError> {
+        self.send(|id| Command::TimeLeft(id, color, time, stones)).await.map(|_| ())
+    }
+
+    /// Sends the command not in the GTP specification, the response is given as it is.
+    pub async fn custom(&mut self, name: &str, args: &[String]) -> Result<String, GtpError> {
+        let result = self.send(|id| Command::Custom {
+            id,
+            name: name.to_string(),
+            args: args.to_vec(),
+        }).await?;
+
+        match result {
+            CommandResult::Raw(_, response) => Ok(response),
+            _ => Err(GtpError::Unexpected(name.to_string())),
+        }
+    }
+}
+
+/// Sends `protocol_version` with the id and drops the responses with the older ids before its
+/// own, the ones left by the commands timed out or cancelled.
+async fn resync(adaptor: &mut (impl Adaptor + Unpin + Send), id: Id) -> Result<(), GtpError> {
+    /* Any response is taken as the raw one, since the commands of the left ones are not known */
+    let command = || Command::Custom {
+        id: Some(id),
+        name: "protocol_version".to_string(),
+        args: Vec::new(),
+    };
+
+    let mut result = adaptor.send_command(command()).await;
+
+    loop {
+        let actual = match &result {
+            Ok(result) => result.id(),
+            Err(ResponseError::Failure(actual, _)) => *actual,
+            Err(error) => return Err(GtpError::Response(error.clone())),
+        };
+
+        match actual {
+            Some(actual) if actual == id => return Ok(()),
+            Some(actual) if actual < id => {
+                let response = adaptor.read_response().await?;
+                result = command().parse_result(&response);
+            },
+            Some(actual) => return Err(GtpError::IdMismatch {
+                expected: id,
+                actual: Some(actual),
+            }),
+            None => return Err(GtpError::Desynchronized),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::mock_adaptor::{MockAdaptor, MockEngine, MockReply};
+
+    fn client(script: &str) -> GtpClient<MockAdaptor> {
+        GtpClient::new(MockAdaptor::new(MockEngine::parse_script(script).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let mut client = client("genmove move D4\nplay fail illegal move\nfinal_score ok W+2.5\nkata-raw ok line 1\\nline 2");
+
+        assert_eq!(client.name().await, Ok("Mock".to_string()));
+        assert_eq!(client.genmove(Color::Black).await.map(|result| result.to_string()), Ok("D4".to_string()));
+        assert_eq!(client.play(Color::White, Vertex::Coordinate('D', 4)).await, Err(GtpError::Response(ResponseError::Failure(Some(3), "illegal move".to_string()))));
+        assert!(client.undo().await.is_ok());
+
+        match client.final_score().await {
+            Ok(Score::White(points)) => assert_eq!(points, 2.5),
+            _ => assert!(false, "The score is not parsed"),
+        };
+
+        assert_eq!(client.custom("kata-raw", &[]).await, Ok("line 1\nline 2".to_string()));
+
+        let engine = client.into_inner();
+        assert_eq!(engine.engine().received()[..3], ["1 name".to_string(), "2 genmove b".to_string(), "3 play w D4".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_id_mismatch() {
+        let mut client = client("genmove raw =7 D4");
+
+        assert_eq!(client.genmove(Color::Black).await.err(), Some(GtpError::IdMismatch {
+            expected: 1,
+            actual: Some(7),
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let mut client = client("genmove delay 200 move D4");
+        client.set_default_timeout(Some(Duration::from_millis(1000)));
+        client.set_timeout("genmove", Duration::from_millis(20));
+
+        assert_eq!(client.genmove(Color::Black).await.err(), Some(GtpError::Timeout("genmove".to_string())));
+        assert!(client.is_desynchronized());
+
+        /* The response of genmove is dropped before the one of name */
+        assert_eq!(client.name().await, Ok("Mock".to_string()));
+        assert!(!client.is_desynchronized());
+        assert_eq!(client.into_inner().engine().received(), &["1 genmove b", "2 protocol_version", "3 name"]);
+
+        /* The left response without id can't be told from the later ones */
+        let mut client = self::client("genmove delay 200 raw = D4");
+        client.set_timeout("genmove", Duration::from_millis(20));
+
+        assert_eq!(client.genmove(Color::Black).await.err(), Some(GtpError::Timeout("genmove".to_string())));
+        assert_eq!(client.name().await.err(), Some(GtpError::Desynchronized));
+        assert!(client.is_desynchronized());
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let mut engine = MockEngine::new();
+        engine.script("genmove", Duration::from_millis(100), MockReply::Resign);
+
+        let mut client = GtpClient::new(MockAdaptor::new(engine));
+        let cancel = client.cancel_handle();
+
+        tokio::spawn(async move {
+            tokio::time::delay_for(Duration::from_millis(20)).await;
+            cancel.cancel();
+        });
+
+        assert_eq!(client.genmove(Color::White).await.err(), Some(GtpError::Cancelled("genmove".to_string())));
+        assert!(client.is_desynchronized());
+        assert_eq!(client.quit().await, Ok(()));
+    }
+}
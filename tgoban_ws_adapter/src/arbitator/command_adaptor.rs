@@ -7,9 +7,12 @@ use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use tgoban_gtp::Command as GtpCommand;
 use tgoban_gtp::CommandResult as GtpCommandResult;
+use tgoban_gtp::{Analyze, ResponseError};
+
+use crate::config::EngineConfig;
 
 use super::adaptor::Adaptor;
-use super::async_adaptor::AsyncAdaptor;
+use super::async_adaptor::{Analysis, AsyncAdaptor};
 
 pub struct CommandAdaptor {
     child: Child,
@@ -32,10 +35,15 @@ impl CommandAdaptor {
             child: child,
         }
     }
+
+    /// Starts `lz-analyze` or `kata-analyze` of the engine, see `AsyncAdaptor::analyze`.
+    pub async fn analyze(&mut self, analyze: &Analyze) -> Result<Analysis<'_, ChildStdout, ChildStdin>, ResponseError> {
+        self.async_adaptor.analyze(analyze).await
+    }
 }
 
 impl Adaptor for CommandAdaptor {
-    fn send_command<'a>(&'a mut self, command: GtpCommand) -> Box<Future<Output = Result<GtpCommandResult, ()>> + Unpin + Send + 'a> {
+    fn send_command<'a>(&'a mut self, command: GtpCommand) -> Box<Future<Output = Result<GtpCommandResult, ResponseError>> + Unpin + Send + 'a> {
         let future = async move {
             let mut line = "".to_string();
 
@@ -44,18 +52,23 @@ impl Adaptor for CommandAdaptor {
 
         Box::new(Box::pin(future))
     }
+
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        self.async_adaptor.read_response()
+    }
 }
 
-pub fn spawn_command() -> CommandAdaptor {
-    let child = Command::new("gnugo")
-        .arg("--mode").arg("gtp")
-        .arg("--level").arg("20")
+/// Runs the engine of the config.
+pub fn spawn_command(config: &EngineConfig) -> std::io::Result<CommandAdaptor> {
+    let mut command = Command::new(&config.program);
+    command.args(&config.args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn();
+        .stderr(Stdio::null());
 
-    let mut child = child.unwrap();
+    if let Some(working_dir) = &config.working_dir {
+        command.current_dir(working_dir);
+    }
 
-    CommandAdaptor::new(child)
+    Ok(CommandAdaptor::new(command.spawn()?))
 }
@@ -0,0 +1,185 @@
+use go_game_engine::{ChessType, GoGameEngine, Location, Player};
+use tgoban_gtp::Vertex;
+
+/// The analyze commands listed by `gogui-analyze_commands`, as `type/label/command`.
+pub const ANALYZE_COMMANDS: [&str; 4] = [
+    "sboard/Liberties/tgoban-liberties",
+    "gfx/Influence/tgoban-influence",
+    "pspairs/Ladders/tgoban-ladders",
+    "gfx/Legal Moves/tgoban-legal_moves",
+];
+
+/// The commands answered by `analyze`.
+pub const COMMANDS: [&str; 5] = [
+    "gogui-analyze_commands",
+    "tgoban-liberties",
+    "tgoban-influence",
+    "tgoban-ladders",
+    "tgoban-legal_moves",
+];
+
+/// The response of the GoGui analyze command on the position, None if the command is not known.
+pub fn analyze(engine: &GoGameEngine, command: &str) -> Option<String> {
+    let response = match command {
+        "gogui-analyze_commands" => ANALYZE_COMMANDS.join("\n"),
+        "tgoban-liberties" => liberties(engine),
+        "tgoban-influence" => influence(engine),
+        "tgoban-ladders" => ladders(engine),
+        "tgoban-legal_moves" => legal_moves(engine),
+        _ => return None,
+    };
+
+    Some(response)
+}
+
+/// The liberties of the chain on every stone, from the top row.
+fn liberties(engine: &GoGameEngine) -> String {
+    let size = engine.size();
+    let board = engine.board();
+
+    let rows: Vec<String> = (0..size).rev()
+        .map(|digit| {
+            let fields: Vec<String> = (0..size)
+                .map(|alphabet| {
+                    let location = Location {
+                        alphabet,
+                        digit,
+                    };
+
+                    match board.get(&location) {
+                        ChessType::None => "\"\"".to_string(),
+                        _ => board.liberties(&location).len().to_string(),
+                    }
+                })
+                .collect();
+
+            fields.join(" ")
+        })
+        .collect();
+
+    rows.join("\n")
+}
+
+/// The influence scaled to -1 to 1, positive for black, and the estimated territories.
+fn influence(engine: &GoGameEngine) -> String {
+    let estimate = engine.estimate_score().influence;
+    let max = estimate.influence.iter().flatten().map(|value| value.abs()).max().unwrap_or(0);
+
+    let mut influence = vec!["INFLUENCE".to_string()];
+    let mut black = vec!["TERRITORY b".to_string()];
+    let mut white = vec!["TERRITORY w".to_string()];
+
+    for_each_point(engine.size(), |location| {
+        let value = estimate.influence[location.alphabet as usize][location.digit as usize];
+        if value != 0 {
+            influence.push(format!("{} {:.2}", vertex(location), value as f64 / max as f64));
+        }
+
+        match estimate.ownership[location.alphabet as usize][location.digit as usize] {
+            Some(Player::Black) => black.push(vertex(location)),
+            Some(Player::White) => white.push(vertex(location)),
+            None => {},
+        };
+    });
+
+    [influence, black, white].iter()
+        .filter(|line| line.len() > 1)
+        .map(|line| line.join(" "))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Whether the chains in atari or with two liberties are captured by a ladder, with the player to move.
+fn ladders(engine: &GoGameEngine) -> String {
+    let board = engine.board();
+    let mut visited: Vec<Location> = Vec::new();
+    let mut pairs: Vec<String> = Vec::new();
+
+    for_each_point(engine.size(), |location| {
+        if board.get(&location) == ChessType::None || visited.contains(&location) {
+            return;
+        }
+
+        visited.extend(board.chain(&location));
+
+        if let Some(ladder) = engine.ladder(location) {
+            pairs.push(format!("{} {}", vertex(location), match ladder.captured {
+                true => "captured",
+                false => "escapes",
+            }));
+        }
+    });
+
+    pairs.join(" ")
+}
+
+fn legal_moves(engine: &GoGameEngine) -> String {
+    let moves: Vec<String> = engine.legal_moves().into_iter().map(vertex).collect();
+
+    match moves.is_empty() {
+        true => "".to_string(),
+        false => format!("MARK {}", moves.join(" ")),
+    }
+}
+
+fn for_each_point(size: u8, mut f: impl FnMut(Location)) {
+    for alphabet in 0..size {
+        for digit in 0..size {
+            f(Location {
+                alphabet,
+                digit,
+            });
+        }
+    }
+}
+
+fn vertex(location: Location) -> String {
+    Vertex::from_number(location.alphabet, location.digit).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(engine: &mut GoGameEngine, moves: &[(u8, u8)]) {
+        for (alphabet, digit) in moves.iter() {
+            engine.make_move(Location { alphabet: *alphabet, digit: *digit }).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_liberties() {
+        let mut engine = GoGameEngine::new(3, 0.0);
+        play(&mut engine, &[(0, 0), (1, 0), (2, 2)]);
+
+        assert_eq!(analyze(&engine, "tgoban-liberties").unwrap(), "\"\" \"\" 2\n\"\" \"\" \"\"\n1 2 \"\"");
+    }
+
+    #[test]
+    fn test_ladders() {
+        /* The black stone at A1 in atari is chased along the edge, the white one at B1 with two liberties is not */
+        let mut engine = GoGameEngine::new(9, 0.0);
+        play(&mut engine, &[(0, 0), (1, 0), (4, 4), (4, 5)]);
+
+        assert_eq!(analyze(&engine, "tgoban-ladders").unwrap(), "A1 captured B1 escapes");
+    }
+
+    #[test]
+    fn test_influence_and_legal_moves() {
+        let mut engine = GoGameEngine::new(5, 0.0);
+        play(&mut engine, &[(2, 2)]);
+
+        let influence = analyze(&engine, "tgoban-influence").unwrap();
+        assert!(influence.starts_with("INFLUENCE "));
+        assert!(influence.contains("C3 1.00"));
+        assert!(influence.contains("\nTERRITORY b "));
+
+        let legal_moves = analyze(&engine, "tgoban-legal_moves").unwrap();
+        assert!(legal_moves.starts_with("MARK A1 A2 "));
+        assert!(!legal_moves.contains("C3"));
+        assert_eq!(legal_moves.split(' ').count(), 25);
+
+        assert_eq!(analyze(&engine, "gogui-analyze_commands").unwrap().lines().count(), ANALYZE_COMMANDS.len());
+        assert!(analyze(&engine, "tgoban-unknown").is_none());
+    }
+}
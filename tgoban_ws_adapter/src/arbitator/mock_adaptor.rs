@@ -0,0 +1,311 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tgoban_gtp::{Command, CommandResult, Id, ResponseError, Vertex, failure_response, preprocess};
+
+use super::adaptor::Adaptor;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MockReply {
+    /// The move of genmove, reg_genmove or kgs-genmove_cleanup.
+    Move(Vertex),
+    Resign,
+    /// The success response carrying the text.
+    Success(String),
+    Failure(String),
+    /// The text is written as the whole response, such as the malformed output.
+    Raw(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockRule {
+    /// The time to wait before replying.
+    pub delay: Duration,
+    pub reply: MockReply,
+}
+
+/// The response of the mock engine to one input line.
+pub struct MockResponse {
+    pub delay: Duration,
+    /// The response ended by the empty line.
+    pub text: String,
+    pub is_quit: bool,
+}
+
+/// The GTP engine answering by the script, every command succeeds unless scripted.
+///
+/// The rules of a command are used once in order, the command is answered by the default
+/// after its rules run out: genmove passes, name is `Mock` and the others reply nothing.
+#[derive(Clone, Default)]
+pub struct MockEngine {
+    rules: HashMap<String, VecDeque<MockRule>>,
+    received: Vec<String>,
+}
+
+impl MockEngine {
+    pub fn new() -> MockEngine {
+        MockEngine::default()
+    }
+
+    /// Adds the reply to the rules of the command name.
+    pub fn script(&mut self, command: &str, delay: Duration, reply: MockReply) {
+        self.rules.entry(command.to_string()).or_default().push_back(MockRule {
+            delay,
+            reply,
+        });
+    }
+
+    /// Parses the script of one rule a line, `<command> [delay <ms>] <reply>`, where the reply is
+    /// `move <vertex>`, `resign`, `ok [text]`, `fail <message>` or `raw <text>`.
+    ///
+    /// `\n` in the text is the newline, and `#` starts the comment.
+    pub fn parse_script(script: &str) -> Result<MockEngine, String> {
+        let mut engine = MockEngine::new();
+
+        for (number, line) in script.lines().enumerate() {
+            let line = preprocess(line);
+            let mut tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+
+            if tokens.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("Invalid rule at line {}: {}", number + 1, line.trim());
+
+            let mut delay = Duration::from_millis(0);
+            if tokens.get(1) == Some(&"delay") {
+                let millis: u64 = tokens.get(2).and_then(|millis| millis.parse().ok()).ok_or_else(invalid)?;
+
+                delay = Duration::from_millis(millis);
+                tokens.drain(1..3);
+            }
+
+            let text = tokens.get(2..).unwrap_or(&[]).join(" ").replace("\\n", "\n");
+            let reply = match (tokens.get(1), tokens.len()) {
+                (Some(&"move"), 3) => MockReply::Move(tokens[2].parse().map_err(|_| invalid())?),
+                (Some(&"resign"), 2) => MockReply::Resign,
+                (Some(&"ok"), _) => MockReply::Success(text),
+                (Some(&"fail"), length) if length > 2 => MockReply::Failure(text),
+                (Some(&"raw"), length) if length > 2 => MockReply::Raw(text),
+                _ => return Err(invalid()),
+            };
+
+            engine.script(tokens[0], delay, reply);
+        }
+
+        Ok(engine)
+    }
+
+    /// The input lines received, comments removed.
+    pub fn received(&self) -> &[String] {
+        &self.received
+    }
+
+    /// Answers the input line, None for the empty line which is ignored by GTP.
+    pub fn respond(&mut self, line: &str) -> Option<MockResponse> {
+        let line = preprocess(line);
+        let mut tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+
+        if tokens.is_empty() {
+            return None;
+        }
+
+        self.received.push(line.trim().to_string());
+
+        let id: Option<Id> = tokens[0].parse().ok().filter(|id| *id >= 0);
+        if id.is_some() {
+            tokens.remove(0);
+        }
+
+        let name = match tokens.first() {
+            Some(name) => name.to_string(),
+            None => return Some(respond_now(failure_response(id, "syntax error"), false)),
+        };
+
+        let rule = self.rules.get_mut(&name).and_then(|rules| rules.pop_front());
+        let is_quit = name == "quit";
+
+        let rule = match rule {
+            Some(rule) => rule,
+            None => return Some(respond_now(format!("={} {}", id_text(id), self.default_reply(&name)), is_quit)),
+        };
+
+        let text = match rule.reply {
+            MockReply::Move(vertex) => format!("={} {}", id_text(id), vertex.to_string()),
+            MockReply::Resign => format!("={} resign", id_text(id)),
+            MockReply::Success(text) => format!("={} {}", id_text(id), text),
+            MockReply::Failure(message) => failure_response(id, &message),
+            MockReply::Raw(text) => text,
+        };
+
+        Some(MockResponse {
+            delay: rule.delay,
+            text: format!("{}\n\n", text.trim_end_matches('\n')),
+            is_quit,
+        })
+    }
+
+    fn default_reply(&self, name: &str) -> String {
+        match name {
+            "protocol_version" => "2".to_string(),
+            "name" => "Mock".to_string(),
+            "version" => "1.0".to_string(),
+            "known_command" => "true".to_string(),
+            "list_commands" => {
+                let mut names: Vec<&str> = vec!["protocol_version", "name", "version", "known_command", "list_commands", "quit"];
+                names.extend(self.rules.keys().map(|name| name.as_str()).filter(|name| !names.contains(name)).collect::<Vec<&str>>());
+                names.join("\n")
+            },
+            "genmove" | "reg_genmove" | "kgs-genmove_cleanup" => "pass".to_string(),
+            "final_score" => "0".to_string(),
+            _ => "".to_string(),
+        }
+    }
+}
+
+fn id_text(id: Option<Id>) -> String {
+    match id {
+        Some(id) => id.to_string(),
+        None => "".to_string(),
+    }
+}
+
+fn respond_now(text: String, is_quit: bool) -> MockResponse {
+    MockResponse {
+        delay: Duration::from_millis(0),
+        text: format!("{}\n\n", text),
+        is_quit,
+    }
+}
+
+/// The mock engine in the process, the responses are parsed as the ones from a pipe.
+///
+/// The response of the command whose future is dropped is left as in the pipe, it is read before
+/// the later ones.
+pub struct MockAdaptor {
+    engine: MockEngine,
+    /// The responses not read yet, with the time each one is written by the engine.
+    pending: VecDeque<(Instant, String)>,
+}
+
+impl MockAdaptor {
+    pub fn new(engine: MockEngine) -> MockAdaptor {
+        MockAdaptor {
+            engine,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn engine(&self) -> &MockEngine {
+        &self.engine
+    }
+
+    /// Waits for the response written first and reads it.
+    async fn next_response(&mut self) -> Result<String, ResponseError> {
+        let written = match self.pending.front() {
+            Some((written, _)) => *written,
+            None => return Err(ResponseError::Empty),
+        };
+
+        let now = Instant::now();
+        if written > now {
+            tokio::time::delay_for(written - now).await;
+        }
+
+        let (_, text) = self.pending.pop_front().unwrap();
+
+        /* The reader of the pipe drops the empty line ending the response */
+        Ok(text.trim_end_matches('\n').to_string())
+    }
+}
+
+impl Adaptor for MockAdaptor {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+        let future = async move {
+            let response = match self.engine.respond(&command.to_string()) {
+                Some(response) => response,
+                None => return Err(ResponseError::Empty),
+            };
+
+            /* The engine answers the commands in order, the delay starts after the previous response */
+            let start = match self.pending.back() {
+                Some((written, _)) => (*written).max(Instant::now()),
+                None => Instant::now(),
+            };
+            self.pending.push_back((start + response.delay, response.text));
+
+            command.parse_result(&self.next_response().await?)
+        };
+
+        Box::new(Box::pin(future))
+    }
+
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        Box::new(Box::pin(self.next_response()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgoban_gtp::{Color, GenMoveResult};
+
+    #[test]
+    fn test_parse_script() {
+        let engine = MockEngine::parse_script("# moves\ngenmove move D4\ngenmove delay 20 resign\nplay fail illegal move\nname raw garbage\\nmore\n").unwrap();
+
+        assert_eq!(engine.rules["genmove"][1], MockRule {
+            delay: Duration::from_millis(20),
+            reply: MockReply::Resign,
+        });
+        assert_eq!(engine.rules["play"][0].reply, MockReply::Failure("illegal move".to_string()));
+        assert_eq!(engine.rules["name"][0].reply, MockReply::Raw("garbage\nmore".to_string()));
+
+        assert!(MockEngine::parse_script("genmove move").is_err());
+        assert!(MockEngine::parse_script("genmove delay x resign").is_err());
+        assert!(MockEngine::parse_script("genmove jump").is_err());
+    }
+
+    #[test]
+    fn test_respond() {
+        let mut engine = MockEngine::new();
+        engine.script("genmove", Duration::from_millis(0), MockReply::Move(Vertex::Coordinate('Q', 16)));
+
+        assert_eq!(engine.respond("3 genmove b # comment").unwrap().text, "=3 Q16\n\n");
+        assert_eq!(engine.respond("genmove w").unwrap().text, "= pass\n\n");
+        assert!(engine.respond("  ").is_none());
+        assert!(engine.respond("quit").unwrap().is_quit);
+        assert_eq!(engine.received(), &["3 genmove b", "genmove w", "quit"]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_adaptor() {
+        let mut engine = MockEngine::new();
+        engine.script("genmove", Duration::from_millis(30), MockReply::Move(Vertex::Coordinate('D', 4)));
+        engine.script("genmove", Duration::from_millis(0), MockReply::Resign);
+        engine.script("play", Duration::from_millis(0), MockReply::Failure("illegal move".to_string()));
+        engine.script("name", Duration::from_millis(0), MockReply::Raw("garbage".to_string()));
+
+        let mut adaptor = MockAdaptor::new(engine);
+
+        let start = std::time::Instant::now();
+        match adaptor.send_command(Command::GenMove(Some(1), Color::Black)).await {
+            Ok(CommandResult::GenMove(Some(1), GenMoveResult::Move(vertex))) => assert_eq!(vertex, Vertex::Coordinate('D', 4)),
+            _ => assert!(false, "The scripted move is not replied"),
+        };
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        match adaptor.send_command(Command::GenMove(None, Color::White)).await {
+            Ok(CommandResult::GenMove(None, GenMoveResult::Resign)) => (),
+            _ => assert!(false, "The resignation is not replied"),
+        };
+
+        assert_eq!(adaptor.send_command("play b d4".parse().unwrap()).await.err(), Some(ResponseError::Failure(None, "illegal move".to_string())));
+        assert_eq!(adaptor.send_command(Command::Name(None)).await.err(), Some(ResponseError::Malformed("garbage".to_string())));
+
+        /* The rules run out */
+        assert!(adaptor.send_command("play b d4".parse().unwrap()).await.is_ok());
+        assert_eq!(adaptor.engine().received().len(), 5);
+    }
+}
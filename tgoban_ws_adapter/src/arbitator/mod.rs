@@ -3,3 +3,10 @@ pub mod arbitator;
 pub mod ws_adaptor;
 pub mod async_adaptor;
 pub mod command_adaptor;
+pub mod analysis_engine;
+pub mod bot_adaptor;
+pub mod gogui;
+pub mod mock_adaptor;
+pub mod transcript;
+pub mod client;
+pub mod referee;
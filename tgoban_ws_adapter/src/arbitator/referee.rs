@@ -0,0 +1,234 @@
+use go_game_engine::{GameResult, GameStatus, GoGameEngine, Location, MoveError, Player};
+use tgoban_gtp::{Color, Vertex};
+
+use super::arbitator::GameSettings;
+
+/// Why the move of a player is not accepted.
+#[derive(Debug)]
+pub enum Violation {
+    /// The player moves in the turn of the opponent.
+    OutOfTurn(Color),
+    OffBoard(Vertex),
+    Illegal(Vertex, MoveError),
+    /// The game is already over.
+    GameOver,
+}
+
+/// The GTP error message of the violation.
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::OutOfTurn(color) => write!(f, "not the turn of {}", color.to_string()),
+            Violation::OffBoard(vertex) => write!(f, "illegal move {} off the board", vertex.to_string()),
+            Violation::Illegal(vertex, MoveError::Exist(_)) => write!(f, "illegal move {} on the stone", vertex.to_string()),
+            Violation::Illegal(vertex, MoveError::NoLiberty(_)) => write!(f, "illegal move {} without liberties", vertex.to_string()),
+            Violation::Illegal(vertex, MoveError::Ko(_)) => write!(f, "illegal move {} retaking the ko", vertex.to_string()),
+            Violation::Illegal(vertex, MoveError::NoMove) => write!(f, "illegal move {}", vertex.to_string()),
+            Violation::GameOver => write!(f, "game over"),
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// The authoritative board of the game between two players, the moves are checked before they
+/// are forwarded to the opponent.
+pub struct Referee {
+    engine: GoGameEngine,
+    settings: GameSettings,
+}
+
+impl Referee {
+    pub fn new(settings: &GameSettings) -> Referee {
+        Referee {
+            engine: GoGameEngine::new(settings.board_size, settings.komi),
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    pub fn engine(&self) -> &GoGameEngine {
+        &self.engine
+    }
+
+    /// The record of the game, the names of the players are kept in it.
+    pub fn engine_mut(&mut self) -> &mut GoGameEngine {
+        &mut self.engine
+    }
+
+    pub fn into_engine(self) -> GoGameEngine {
+        self.engine
+    }
+
+    /// The player to move.
+    pub fn player(&self) -> Player {
+        self.engine.player()
+    }
+
+    /// The result after the game is over.
+    pub fn result(&self) -> Option<GameResult> {
+        self.engine.result()
+    }
+
+    /// Whether both players pass in a row and the game waits for `score`.
+    pub fn is_counting(&self) -> bool {
+        self.engine.get_status() == GameStatus::Scoring && self.engine.result().is_none()
+    }
+
+    /// Plays the move of the color, the game is counted after two passes in a row.
+    pub fn play(&mut self, color: Color, vertex: Vertex) -> Result<(), Violation> {
+        if self.engine.result().is_some() {
+            return Err(Violation::GameOver);
+        }
+
+        if color != to_color(self.engine.player()) {
+            return Err(Violation::OutOfTurn(color));
+        }
+
+        if !vertex.is_on_board(self.engine.size()) {
+            return Err(Violation::OffBoard(vertex));
+        }
+
+        match vertex.to_number() {
+            Ok((alphabet, digit)) => {
+                self.engine.make_move(Location { alphabet, digit }).map_err(|error| Violation::Illegal(vertex, error))?;
+            },
+            Err(_) => self.engine.pass(),
+        };
+
+        Ok(())
+    }
+
+    /// Ends the game, the player resigns.
+    pub fn resign(&mut self, player: Player) -> GameResult {
+        let result = GameResult::Resign(player.switch());
+        self.engine.set_result(result);
+
+        result
+    }
+
+    /// Ends the game, the player loses by the forfeit.
+    pub fn forfeit(&mut self, player: Player) -> GameResult {
+        let result = GameResult::Forfeit(player.switch());
+        self.engine.set_result(result);
+
+        result
+    }
+
+    /// Ends the game without the result, such as by the move limit.
+    pub fn void(&mut self) -> GameResult {
+        self.engine.set_result(GameResult::Void);
+
+        GameResult::Void
+    }
+
+    /// Counts the territories after two passes with the dead stones removed, see
+    /// `GoGameEngine::get_score`. The territories are counted whatever the rules of the settings,
+    /// the rules are only sent to the players.
+    pub fn score(&mut self, dead: &[Location]) -> Option<GameResult> {
+        if !self.is_counting() {
+            return None;
+        }
+
+        let size = self.engine.size();
+        for location in dead.iter() {
+            /* The whole chain is toggled by one of its stones, the locations off the board are ignored */
+            if location.alphabet < size && location.digit < size && self.engine.is_alive(*location) {
+                self.engine.toggle(*location);
+            }
+        }
+
+        let (black, white) = self.engine.get_score();
+
+        let result = match black - white {
+            diff if diff > 0.0 => GameResult::Score(Player::Black, diff),
+            diff if diff < 0.0 => GameResult::Score(Player::White, -diff),
+            _ => GameResult::Draw,
+        };
+
+        self.engine.set_result(result);
+
+        Some(result)
+    }
+
+    /// Ends the game without the result since the players disagree on the dead stones, the dead
+    /// stones of both are written in the comment of the record.
+    pub fn dispute(&mut self, black: &[Vertex], white: &[Vertex]) -> GameResult {
+        let join = |vertices: &[Vertex]| vertices.iter().map(|vertex| vertex.to_string()).collect::<Vec<String>>().join(" ");
+
+        self.engine.set_comment(Some(format!("Dead stones disputed\nBlack: {}\nWhite: {}", join(black), join(white))));
+
+        self.void()
+    }
+}
+
+pub fn to_color(player: Player) -> Color {
+    match player {
+        Player::Black => Color::Black,
+        Player::White => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn referee(board_size: u8) -> Referee {
+        Referee::new(&GameSettings {
+            board_size,
+            komi: 6.5,
+            rules: None,
+            max_moves: None,
+        })
+    }
+
+    #[test]
+    fn test_violations() {
+        let mut referee = referee(9);
+
+        assert!(matches!(referee.play(Color::White, Vertex::Coordinate('D', 4)), Err(Violation::OutOfTurn(Color::White))));
+        assert!(matches!(referee.play(Color::Black, Vertex::Coordinate('K', 10)), Err(Violation::OffBoard(_))));
+        assert!(referee.play(Color::Black, Vertex::Coordinate('D', 4)).is_ok());
+
+        let violation = referee.play(Color::White, Vertex::Coordinate('D', 4)).unwrap_err();
+        assert_eq!(violation.to_string(), "illegal move D4 on the stone");
+        assert_eq!(referee.player(), Player::White);
+
+        assert_eq!(referee.forfeit(Player::White), GameResult::Forfeit(Player::Black));
+        assert!(matches!(referee.play(Color::White, Vertex::Pass), Err(Violation::GameOver)));
+    }
+
+    #[test]
+    fn test_two_passes() {
+        let mut referee = referee(9);
+
+        referee.play(Color::Black, Vertex::Coordinate('E', 5)).unwrap();
+        referee.play(Color::White, Vertex::Pass).unwrap();
+        assert_eq!(referee.result(), None);
+
+        referee.play(Color::Black, Vertex::Pass).unwrap();
+        assert!(referee.is_counting());
+        assert_eq!(referee.result(), None);
+
+        assert_eq!(referee.score(&[]), Some(GameResult::Score(Player::Black, 73.5)));
+        assert_eq!(referee.score(&[]), None);
+    }
+
+    #[test]
+    fn test_dead_stones() {
+        let mut referee = referee(9);
+
+        for vertex in [Vertex::Coordinate('E', 5), Vertex::Coordinate('C', 3), Vertex::Coordinate('C', 4), Vertex::Coordinate('C', 2)].iter() {
+            referee.play(to_color(referee.player()), *vertex).unwrap();
+        }
+        referee.play(Color::Black, Vertex::Pass).unwrap();
+        referee.play(Color::White, Vertex::Pass).unwrap();
+
+        let dead = [Location { alphabet: 2, digit: 2 }, Location { alphabet: 24, digit: 24 }, Location { alphabet: 2, digit: 1 }];
+        assert_eq!(referee.score(&dead), Some(GameResult::Score(Player::Black, 74.5)));
+        assert!(!referee.engine().is_alive(Location { alphabet: 2, digit: 1 }));
+    }
+}
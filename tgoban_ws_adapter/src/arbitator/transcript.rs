@@ -0,0 +1,329 @@
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use tgoban_gtp::{Command, CommandResult, ResponseError, failure_response};
+
+use super::adaptor::Adaptor;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// The command sent to the adaptor.
+    Command,
+    /// The response of the adaptor, the failure response included.
+    Response,
+    /// The adaptor fails without a response, such as the closed connection.
+    Error,
+}
+
+impl Direction {
+    fn name(&self) -> &'static str {
+        match self {
+            Direction::Command => "command",
+            Direction::Response => "response",
+            Direction::Error => "error",
+        }
+    }
+}
+
+/// One line of the transcript.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// The time since the recording is started.
+    pub time: Duration,
+    pub direction: Direction,
+    pub text: String,
+}
+
+impl Entry {
+    pub fn to_json(&self) -> String {
+        json!({
+            "time": self.time.as_secs_f64(),
+            "direction": self.direction.name(),
+            "text": self.text,
+        }).to_string()
+    }
+
+    pub fn from_json(line: &str) -> Option<Entry> {
+        let entry: Value = serde_json::from_str(line).ok()?;
+
+        let direction = match entry["direction"].as_str()? {
+            "command" => Direction::Command,
+            "response" => Direction::Response,
+            "error" => Direction::Error,
+            _ => return None,
+        };
+
+        Some(Entry {
+            time: Duration::from_secs_f64(entry["time"].as_f64()?),
+            direction,
+            text: entry["text"].as_str()?.to_string(),
+        })
+    }
+}
+
+/// Reads the transcript written by `RecordingAdaptor`, one JSON entry a line.
+pub fn read_transcript(reader: impl BufRead) -> std::io::Result<Vec<Entry>> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Entry::from_json(&line) {
+            Some(entry) => entries.push(entry),
+            None => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid transcript entry: {}", line))),
+        };
+    }
+
+    Ok(entries)
+}
+
+/// Passes the commands to the adaptor and writes every command and response to the transcript.
+pub struct RecordingAdaptor<A: Adaptor + Unpin + Send, W: Write + Send> {
+    adaptor: A,
+    writer: W,
+    start: Instant,
+}
+
+impl<A: Adaptor + Unpin + Send, W: Write + Send> RecordingAdaptor<A, W> {
+    pub fn new(adaptor: A, writer: W) -> RecordingAdaptor<A, W> {
+        RecordingAdaptor {
+            adaptor,
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn into_inner(self) -> (A, W) {
+        (self.adaptor, self.writer)
+    }
+
+    /// The transcript is flushed at every entry to be kept even if the program crashes.
+    fn record(&mut self, direction: Direction, text: String) {
+        let entry = Entry {
+            time: self.start.elapsed(),
+            direction,
+            text,
+        };
+
+        /* The recording never breaks the game */
+        let _ = writeln!(self.writer, "{}", entry.to_json());
+        let _ = self.writer.flush();
+    }
+}
+
+impl<A: Adaptor + Unpin + Send> RecordingAdaptor<A, BufWriter<File>> {
+    /// Records to the file, the file is truncated.
+    pub fn create(adaptor: A, path: &str) -> std::io::Result<RecordingAdaptor<A, BufWriter<File>>> {
+        Ok(RecordingAdaptor::new(adaptor, BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<A: Adaptor + Unpin + Send, W: Write + Send> Adaptor for RecordingAdaptor<A, W> {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+        let future = async move {
+            let id = command.id();
+
+            self.record(Direction::Command, command.to_string());
+
+            let result = self.adaptor.send_command(command).await;
+
+            match &result {
+                Ok(command_result) => self.record(Direction::Response, command_result.to_string().trim_end_matches('\n').to_string()),
+                Err(ResponseError::Failure(_, message)) => self.record(Direction::Response, failure_response(id, message)),
+                Err(error) => self.record(Direction::Error, error.to_string()),
+            };
+
+            result
+        };
+
+        Box::new(Box::pin(future))
+    }
+
+    fn knows_command(&self, name: &str) -> bool {
+        self.adaptor.knows_command(name)
+    }
+
+    fn read_response<'a>(&'a mut self) -> Box<dyn Future<Output = Result<String, ResponseError>> + Unpin + Send + 'a> {
+        let future = async move {
+            let result = self.adaptor.read_response().await;
+
+            match &result {
+                Ok(response) => self.record(Direction::Response, response.clone()),
+                Err(error) => self.record(Direction::Error, error.to_string()),
+            };
+
+            result
+        };
+
+        Box::new(Box::pin(future))
+    }
+}
+
+/// The command which is not the one of the transcript.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The index of the command in the transcript.
+    pub index: usize,
+    /// None if the transcript is ended.
+    pub expected: Option<String>,
+    pub actual: String,
+}
+
+/// Answers the commands by the responses of the transcript in order.
+///
+/// The recorded response is replied even if the command diverges from the transcript, the
+/// divergences are kept to be checked.
+pub struct ReplayAdaptor {
+    entries: Vec<Entry>,
+    position: usize,
+    commands: usize,
+    divergences: Vec<Divergence>,
+}
+
+impl ReplayAdaptor {
+    pub fn new(entries: Vec<Entry>) -> ReplayAdaptor {
+        ReplayAdaptor {
+            entries,
+            position: 0,
+            commands: 0,
+            divergences: Vec::new(),
+        }
+    }
+
+    pub fn open(path: &str) -> std::io::Result<ReplayAdaptor> {
+        Ok(ReplayAdaptor::new(read_transcript(BufReader::new(File::open(path)?))?))
+    }
+
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+
+    /// Whether all the commands of the transcript are replayed.
+    pub fn is_finished(&self) -> bool {
+        self.entries[self.position..].iter().all(|entry| entry.direction != Direction::Command)
+    }
+
+    fn next_entry(&mut self, direction: Direction) -> Option<Entry> {
+        while self.position < self.entries.len() {
+            let entry = &self.entries[self.position];
+
+            /* The command has no response if the transcript is cut */
+            if direction != Direction::Command && entry.direction == Direction::Command {
+                return None;
+            }
+
+            self.position += 1;
+
+            if (entry.direction == Direction::Command) == (direction == Direction::Command) {
+                return Some(entry.clone());
+            }
+        }
+
+        None
+    }
+}
+
+impl Adaptor for ReplayAdaptor {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
+        let future = async move {
+            let actual = command.to_string();
+            let index = self.commands;
+
+            self.commands += 1;
+
+            let expected = match self.next_entry(Direction::Command) {
+                Some(entry) => entry.text,
+                None => {
+                    self.divergences.push(Divergence {
+                        index,
+                        expected: None,
+                        actual,
+                    });
+                    return Err(ResponseError::Closed);
+                },
+            };
+
+            if expected != actual {
+                self.divergences.push(Divergence {
+                    index,
+                    expected: Some(expected),
+                    actual,
+                });
+            }
+
+            match self.next_entry(Direction::Response) {
+                Some(Entry { direction: Direction::Response, text, .. }) => command.parse_result(&text),
+                Some(Entry { text, .. }) if text == ResponseError::Closed.to_string() => Err(ResponseError::Closed),
+                Some(Entry { text, .. }) => Err(ResponseError::Malformed(text)),
+                None => Err(ResponseError::Closed),
+            }
+        };
+
+        Box::new(Box::pin(future))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tgoban_bot::{Level, new_bot};
+    use tgoban_gtp::{Color, GenMoveResult};
+    use super::super::bot_adaptor::BotAdaptor;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let adaptor = BotAdaptor::new(new_bot(Level::Greedy, 3), 9, 6.5);
+        let mut recording = RecordingAdaptor::new(adaptor, Vec::new());
+
+        let commands = ["boardsize 9", "play b e5", "play w e5", "2 genmove w", "showboard"];
+        for command in commands.iter() {
+            let _ = recording.send_command(command.parse().unwrap()).await;
+        }
+
+        let (_, transcript) = recording.into_inner();
+        let entries = read_transcript(transcript.as_slice()).unwrap();
+
+        assert_eq!(entries.len(), 10);
+        assert_eq!(entries[2], Entry { time: entries[2].time, direction: Direction::Command, text: "play b e5".to_string() });
+        assert_eq!(entries[5].text, "? illegal move");
+        assert!(entries[9].text.contains('\n'));
+        assert!(entries.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+        let recorded_move = entries[7].text.clone();
+        let mut replay = ReplayAdaptor::new(entries);
+
+        replay.send_command("boardsize 9".parse().unwrap()).await.unwrap();
+        replay.send_command("play b e5".parse().unwrap()).await.unwrap();
+
+        match replay.send_command("play w e5".parse().unwrap()).await {
+            Err(ResponseError::Failure(None, message)) => assert_eq!(message, "illegal move"),
+            _ => assert!(false, "The failure is not replayed"),
+        };
+
+        match replay.send_command(Command::GenMove(Some(2), Color::White)).await {
+            Ok(CommandResult::GenMove(Some(2), GenMoveResult::Move(vertex))) => assert_eq!(format!("=2 {}", vertex.to_string()), recorded_move),
+            _ => assert!(false, "The move is not replayed"),
+        };
+        assert!(replay.divergences().is_empty());
+
+        /* The response is served even if the command diverges */
+        assert!(replay.send_command("undo".parse().unwrap()).await.is_err());
+        assert_eq!(replay.divergences(), &[Divergence {
+            index: 4,
+            expected: Some("showboard".to_string()),
+            actual: "undo".to_string(),
+        }]);
+        assert!(replay.is_finished());
+
+        assert_eq!(replay.send_command("quit".parse().unwrap()).await.err(), Some(ResponseError::Closed));
+        assert_eq!(replay.divergences().len(), 2);
+    }
+}
@@ -10,9 +10,12 @@ use tokio_tungstenite::WebSocketStream;
 
 use go_game_engine::{Location};
 
-use tgoban_gtp::{Command, CommandResult};
+use tgoban_gtp::{Command, CommandResult, ResponseError};
 use super::adaptor::Adaptor;
 
+/// The commands answered by the web frontend, the response of genmove is the move of the human.
+pub const FRONTEND_COMMANDS: [&str; 4] = ["boardsize", "komi", "play", "genmove"];
+
 pub struct WsAdaptor {
     ws_stream: WebSocketStream<TcpStream>,
 }
@@ -26,19 +29,18 @@ impl WsAdaptor {
 }
 
 impl Adaptor for WsAdaptor {
-    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ()>> + Unpin + Send + 'a> {
+    fn send_command<'a>(&'a mut self, command: Command) -> Box<dyn Future<Output = Result<CommandResult, ResponseError>> + Unpin + Send + 'a> {
         let future = async move {
             let res = self.ws_stream.send(Message::Text(command.to_string())).await;
 
             loop {
                 let message = self.ws_stream.next().await;
 
-                if let None = message {
-                    return Err(());
+                let message = match message {
+                    Some(Ok(message)) => message,
+                    _ => return Err(ResponseError::Closed),
                 };
 
-                let message = message.unwrap().unwrap();
-
                 match message {
                     Message::Text(text) => {
                         return command.parse_result(&text);
@@ -47,17 +49,21 @@ impl Adaptor for WsAdaptor {
                         self.ws_stream.send(Message::Pong(payload)).await;
                     },
                     Message::Close(close) => {
-                        return Err(());
+                        return Err(ResponseError::Closed);
                     }
                     _ => {
-                        return Err(());
+                        return Err(ResponseError::Malformed(message.to_string()));
                     }
                 };
             }
 
-            return Err(());
+            return Err(ResponseError::Closed);
         };
 
         Box::new(Box::pin(future))
     }
+
+    fn knows_command(&self, name: &str) -> bool {
+        FRONTEND_COMMANDS.contains(&name)
+    }
 }
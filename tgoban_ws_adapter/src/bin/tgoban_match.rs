@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use tgoban_ws_adapter::{BOARD_SIZE, KOMI_DEFAULT};
+use tgoban_ws_adapter::arbitator::arbitator::GameSettings;
+use tgoban_ws_adapter::config::EngineConfig;
+use tgoban_ws_adapter::match_runner::{MatchConfig, run_match};
+
+const USAGE: &str = "Usage: tgoban_match [--games N] [--parallel N] [--max-moves N] [--board-size N] [--komi KOMI] [--rules RULES] [--sgf-dir DIR] \"ENGINE1 [ARGS...]\" \"ENGINE2 [ARGS...]\"";
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<MatchConfig, String> {
+    let mut games: usize = 2;
+    let mut parallel: usize = 1;
+    let mut sgf_dir: Option<PathBuf> = None;
+    let mut game = GameSettings {
+        board_size: BOARD_SIZE,
+        komi: KOMI_DEFAULT,
+        rules: None,
+        max_moves: None,
+    };
+    let mut engines: Vec<EngineConfig> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            let mut words = arg.split_ascii_whitespace().map(|word| word.to_string());
+
+            engines.push(EngineConfig {
+                program: words.next().ok_or_else(|| "The engine is empty".to_string())?,
+                args: words.collect(),
+                working_dir: None,
+            });
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+
+        let is_valid = match arg.as_str() {
+            "--games" => value.parse().map(|value| games = value).is_ok(),
+            "--parallel" => value.parse().map(|value| parallel = value).is_ok() && parallel > 0,
+            "--max-moves" => value.parse().map(|value| game.max_moves = Some(value)).is_ok(),
+            "--board-size" => value.parse().map(|value| game.board_size = value).is_ok() && game.board_size >= 1 && game.board_size as usize <= go_game_engine::BOARD_SIZE_MAX,
+            "--komi" => value.parse().map(|value| game.komi = value).is_ok() && game.komi.is_finite(),
+            "--rules" => {
+                game.rules = Some(value.clone());
+                true
+            },
+            "--sgf-dir" => {
+                sgf_dir = Some(PathBuf::from(&value));
+                true
+            },
+            _ => return Err(format!("Unknown option {}", arg)),
+        };
+
+        if !is_valid {
+            return Err(format!("Invalid value {} of {}", value, arg));
+        }
+    }
+
+    let engines = match engines.as_slice() {
+        [first, second] => [first.clone(), second.clone()],
+        _ => return Err("Two engines are needed".to_string()),
+    };
+
+    Ok(MatchConfig {
+        engines,
+        games,
+        parallel,
+        game,
+        sgf_dir,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match parse_options(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    if let Some(sgf_dir) = &config.sgf_dir {
+        if let Err(error) = std::fs::create_dir_all(sgf_dir) {
+            eprintln!("Cannot create {}: {}", sgf_dir.to_string_lossy(), error);
+            std::process::exit(1);
+        }
+    }
+
+    let results = run_match(config, |game| {
+        match &game.end {
+            Ok(end) => eprintln!("Game {}: {} ({:?}), engine {} as black", game.number, game.result, end, game.black + 1),
+            Err(error) => eprintln!("Game {}: {}", game.number, error),
+        };
+    }).await;
+
+    println!("{}", results.table());
+}
@@ -0,0 +1,45 @@
+use std::io::{BufRead, Write};
+
+use tgoban_ws_adapter::arbitator::mock_adaptor::MockEngine;
+
+const USAGE: &str = "Usage: tgoban_mock_engine [SCRIPT]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let script = match args.as_slice() {
+        [] => Ok("".to_string()),
+        [path] => std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error)),
+        _ => Err("Too many arguments".to_string()),
+    };
+
+    let mut engine = match script.and_then(|script| MockEngine::parse_script(&script)) {
+        Ok(engine) => engine,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        let response = match engine.respond(&line) {
+            Some(response) => response,
+            None => continue,
+        };
+
+        std::thread::sleep(response.delay);
+
+        if stdout.write_all(response.text.as_bytes()).and_then(|_| stdout.flush()).is_err() || response.is_quit {
+            break;
+        }
+    }
+}
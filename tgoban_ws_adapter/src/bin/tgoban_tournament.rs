@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use tgoban_ws_adapter::tournament::{Standings, TournamentConfig, read_results, run_tournament};
+
+const USAGE: &str = "Usage: tgoban_tournament [--report] CONFIG.json [RESULTS.jsonl]";
+
+struct Options {
+    /// Only the crosstable of the results is printed.
+    is_report: bool,
+    config: PathBuf,
+    results: PathBuf,
+}
+
+fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut is_report = false;
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--report" => is_report = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => paths.push(PathBuf::from(arg)),
+        };
+    }
+
+    let (config, results) = match paths.as_slice() {
+        [config] => (config.clone(), config.with_extension("jsonl")),
+        [config, results] => (config.clone(), results.clone()),
+        [] => return Err("The config is not given".to_string()),
+        _ => return Err("Too many arguments".to_string()),
+    };
+
+    Ok(Options {
+        is_report,
+        config,
+        results,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let config = std::fs::read_to_string(&options.config)
+        .map_err(|error| error.to_string())
+        .and_then(|json| TournamentConfig::from_json(&json))
+        .unwrap_or_else(|error| {
+            eprintln!("{}: {}", options.config.to_string_lossy(), error);
+            std::process::exit(1);
+        });
+
+    if let Some(sgf_dir) = &config.sgf_dir {
+        if let Err(error) = std::fs::create_dir_all(sgf_dir) {
+            eprintln!("Cannot create {}: {}", sgf_dir.to_string_lossy(), error);
+            std::process::exit(1);
+        }
+    }
+
+    let names = config.names();
+    let total = config.schedule().len();
+
+    let results = match options.is_report {
+        true => read_results(&options.results).map(|(games, cut)| {
+            if let Some(line) = cut {
+                eprintln!("Ignoring the cut result {}", line);
+            }
+            games
+        }),
+        false => run_tournament(config, &options.results, move |pairing, game| {
+            match game {
+                Ok(game) => eprintln!("Game {}/{}: {} - {} {}", game.number, total, game.black, game.white, game.result),
+                Err(error) => eprintln!("Game {}/{}: {}", pairing.number, total, error),
+            };
+        }).await,
+    };
+
+    match results {
+        Ok(results) => println!("{}", Standings::new(names, &results).crosstable()),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        },
+    };
+}
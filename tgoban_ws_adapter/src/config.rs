@@ -0,0 +1,261 @@
+use std::path::PathBuf;
+
+use serde_derive::Deserialize;
+
+use tgoban_gtp::Color;
+
+use crate::{BOARD_SIZE, KOMI_DEFAULT};
+use crate::arbitator::arbitator::GameSettings;
+
+pub const ADDRESS_DEFAULT: &str = "127.0.0.1:8088";
+
+pub const USAGE: &str = "Usage: tgoban_ws_adapter [--config FILE] [--address ADDRESS] [--cwd DIR] [--board-size N] [--komi KOMI] [--rules RULES] [--human black|white] [--sgf-dir DIR] [ENGINE [ARGS...]]";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineConfig {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The working directory of the engine, the one of the server if None.
+    pub working_dir: Option<PathBuf>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> EngineConfig {
+        EngineConfig {
+            program: "gnugo".to_string(),
+            args: ["--mode", "gtp", "--level", "20"].iter().map(|arg| arg.to_string()).collect(),
+            working_dir: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerConfig {
+    /// The address listened for the websocket connections.
+    pub address: String,
+    /// The engine spawned for every connection.
+    pub engine: EngineConfig,
+    pub game: GameSettings,
+    /// The colour played by the websocket client.
+    pub human: Color,
+    /// The directory where the finished games are saved, not saved if None.
+    pub sgf_dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            address: ADDRESS_DEFAULT.to_string(),
+            engine: EngineConfig::default(),
+            game: GameSettings {
+                board_size: BOARD_SIZE,
+                komi: KOMI_DEFAULT,
+                rules: None,
+                max_moves: None,
+            },
+            human: Color::Black,
+            sgf_dir: None,
+        }
+    }
+}
+
+/// The settings given by the config file or the arguments, the ones not given are left as they are.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Overrides {
+    address: Option<String>,
+    engine: Option<EngineOverrides>,
+    board_size: Option<u8>,
+    komi: Option<f64>,
+    rules: Option<String>,
+    human: Option<String>,
+    sgf_dir: Option<PathBuf>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EngineOverrides {
+    program: Option<String>,
+    args: Option<Vec<String>>,
+    working_dir: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    /// Parses the JSON config file, such as
+    /// `{"address": "0.0.0.0:8088", "engine": {"program": "katago", "args": ["gtp"]}, "komi": 7.5}`.
+    pub fn from_json(json: &str) -> Result<ServerConfig, String> {
+        let overrides: Overrides = serde_json::from_str(json).map_err(|error| format!("Invalid config: {}", error))?;
+
+        ServerConfig::default().apply(overrides)
+    }
+
+    /// Parses the arguments without the program name, the arguments override the config file.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<ServerConfig, String> {
+        let mut config_file: Option<String> = None;
+        let mut overrides = Overrides::default();
+        let mut engine = EngineOverrides::default();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                engine.program = Some(arg);
+                engine.args = Some(args.by_ref().collect());
+                break;
+            }
+
+            let value = match args.next() {
+                Some(value) => value,
+                None => return Err(format!("{} needs a value", arg)),
+            };
+
+            let is_valid = match arg.as_str() {
+                "--config" => {
+                    config_file = Some(value.clone());
+                    true
+                },
+                "--address" => {
+                    overrides.address = Some(value.clone());
+                    true
+                },
+                "--cwd" => {
+                    engine.working_dir = Some(PathBuf::from(&value));
+                    true
+                },
+                "--board-size" => value.parse().map(|size| overrides.board_size = Some(size)).is_ok(),
+                "--komi" => value.parse().map(|komi| overrides.komi = Some(komi)).is_ok(),
+                "--rules" => {
+                    overrides.rules = Some(value.clone());
+                    true
+                },
+                "--human" => {
+                    overrides.human = Some(value.clone());
+                    true
+                },
+                "--sgf-dir" => {
+                    overrides.sgf_dir = Some(PathBuf::from(&value));
+                    true
+                },
+                _ => return Err(format!("Unknown option {}", arg)),
+            };
+
+            if !is_valid {
+                return Err(format!("Invalid value {} of {}", value, arg));
+            }
+        }
+
+        overrides.engine = Some(engine);
+
+        let config = match config_file {
+            Some(path) => {
+                let json = std::fs::read_to_string(&path).map_err(|error| format!("{}: {}", path, error))?;
+                ServerConfig::from_json(&json).map_err(|error| format!("{}: {}", path, error))?
+            },
+            None => ServerConfig::default(),
+        };
+
+        config.apply(overrides)
+    }
+
+    fn apply(mut self, overrides: Overrides) -> Result<ServerConfig, String> {
+        if let Some(address) = overrides.address {
+            self.address = address;
+        }
+
+        if let Some(engine) = overrides.engine {
+            if let Some(program) = engine.program {
+                /* The arguments of the default engine don't suit another one */
+                self.engine.program = program;
+                self.engine.args = engine.args.unwrap_or_default();
+            } else if let Some(args) = engine.args {
+                self.engine.args = args;
+            }
+
+            if engine.working_dir.is_some() {
+                self.engine.working_dir = engine.working_dir;
+            }
+        }
+
+        if let Some(size) = overrides.board_size {
+            if size < 1 || size as usize > go_game_engine::BOARD_SIZE_MAX {
+                return Err(format!("Unacceptable board size {}", size));
+            }
+
+            self.game.board_size = size;
+        }
+
+        if let Some(komi) = overrides.komi {
+            if !komi.is_finite() {
+                return Err(format!("Unacceptable komi {}", komi));
+            }
+
+            self.game.komi = komi;
+        }
+
+        if overrides.rules.is_some() {
+            self.game.rules = overrides.rules;
+        }
+
+        if let Some(human) = overrides.human {
+            self.human = human.parse().map_err(|_| format!("Unknown colour {}", human))?;
+        }
+
+        if overrides.sgf_dir.is_some() {
+            self.sgf_dir = overrides.sgf_dir;
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> {
+        line.split_ascii_whitespace().map(|arg| arg.to_string()).collect::<Vec<String>>().into_iter()
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(ServerConfig::from_args(args("")), Ok(ServerConfig::default()));
+
+        let config = ServerConfig::from_args(args("--address 0.0.0.0:9000 --board-size 9 --komi 7.5 --human white --sgf-dir games katago gtp -model m.bin.gz")).unwrap();
+        assert_eq!(config.address, "0.0.0.0:9000");
+        assert_eq!(config.game.board_size, 9);
+        assert_eq!(config.game.komi, 7.5);
+        assert_eq!(config.human, Color::White);
+        assert_eq!(config.sgf_dir, Some(PathBuf::from("games")));
+        assert_eq!(config.engine.program, "katago");
+        assert_eq!(config.engine.args, ["gtp", "-model", "m.bin.gz"]);
+
+        assert!(ServerConfig::from_args(args("--board-size 30")).is_err());
+        assert!(ServerConfig::from_args(args("--komi x")).is_err());
+        assert!(ServerConfig::from_args(args("--human red")).is_err());
+        assert!(ServerConfig::from_args(args("--level 3")).is_err());
+        assert!(ServerConfig::from_args(args("--address")).is_err());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let config = ServerConfig::from_json(r#"{"engine": {"args": ["--mode", "gtp"], "working_dir": "/opt/gnugo"}, "rules": "japanese"}"#).unwrap();
+        assert_eq!(config.engine.program, "gnugo");
+        assert_eq!(config.engine.args, ["--mode", "gtp"]);
+        assert_eq!(config.engine.working_dir, Some(PathBuf::from("/opt/gnugo")));
+        assert_eq!(config.game.rules, Some("japanese".to_string()));
+        assert_eq!(config.game.komi, KOMI_DEFAULT);
+
+        assert!(ServerConfig::from_json(r#"{"port": 8088}"#).is_err());
+        assert!(ServerConfig::from_json(r#"{"board_size": 0}"#).is_err());
+    }
+
+    #[test]
+    fn test_config_file() {
+        let path = std::env::temp_dir().join(format!("tgoban_ws_adapter_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"address": "0.0.0.0:9000", "komi": 0.5}"#).unwrap();
+
+        let config = ServerConfig::from_args(args(&format!("--komi 7.5 --config {}", path.to_string_lossy()))).unwrap();
+        assert_eq!(config.address, "0.0.0.0:9000");
+        assert_eq!(config.game.komi, 7.5);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod arbitator;
+pub mod config;
+pub mod match_runner;
+pub mod tournament;
+
+pub const BOARD_SIZE: u8 = 19;
+pub const KOMI_DEFAULT: f64 = 6.5;
@@ -1,32 +1,59 @@
 //mod command_adapter;
-mod arbitator;
-
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use tokio::net::{TcpListener, TcpStream};
 
 use tokio_tungstenite::WebSocketStream;
 
-pub const BOARD_SIZE: u8 = 19;
-pub const KOMI_DEFAULT: f64 = 6.5;
-
+use tgoban_gtp::Color;
+use tgoban_ws_adapter::arbitator;
+use tgoban_ws_adapter::config::{ServerConfig, USAGE};
 
-async fn handle_connection(raw_stream: TcpStream, _addr: SocketAddr) {
+async fn handle_connection(raw_stream: TcpStream, addr: SocketAddr, config: Arc<ServerConfig>) {
     let ws_stream: WebSocketStream<TcpStream> = tokio_tungstenite::accept_async(raw_stream)
         .await
         .expect("Error during the websocket handshake occurred");
 
     let ws_adaptor = arbitator::ws_adaptor::WsAdaptor::new(ws_stream);
-    let command_adaptor = arbitator::command_adaptor::spawn_command();
-
-    arbitator::arbitator::run(ws_adaptor, command_adaptor).await;
+    let command_adaptor = match arbitator::command_adaptor::spawn_command(&config.engine) {
+        Ok(command_adaptor) => command_adaptor,
+        Err(error) => {
+            eprintln!("Cannot run {} for {}: {}", config.engine.program, addr, error);
+            return;
+        },
+    };
+
+    let game = match config.human {
+        Color::Black => arbitator::arbitator::run(ws_adaptor, command_adaptor, &config.game).await,
+        Color::White => arbitator::arbitator::run(command_adaptor, ws_adaptor, &config.game).await,
+    };
+
+    eprintln!("Game with {} ends: {} ({:?})", addr, game.result(), game.end);
+
+    if let Some(sgf_dir) = &config.sgf_dir {
+        if let Err(error) = game.save(sgf_dir) {
+            eprintln!("Cannot save the game in {}: {}", sgf_dir.to_string_lossy(), error);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    let mut server = TcpListener::bind("127.0.0.1:8088").await.expect("Failed to bind");
+    let config = match ServerConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => Arc::new(config),
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let mut server = TcpListener::bind(&config.address).await.unwrap_or_else(|error| {
+        eprintln!("Cannot listen on {}: {}", config.address, error);
+        std::process::exit(1);
+    });
 
     while let Ok((stream, addr)) = server.accept().await {
-        tokio::spawn(handle_connection(stream, addr));
+        tokio::spawn(handle_connection(stream, addr, config.clone()));
     }
 }
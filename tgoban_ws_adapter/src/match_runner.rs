@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use go_game_engine::{GameResult, Player};
+
+use crate::arbitator::arbitator::{run, Game, GameEnd, GameSettings};
+use crate::arbitator::command_adaptor::spawn_command;
+use crate::config::EngineConfig;
+
+/// The z value of the 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// The match between two engines, the engines take black in turn from the first one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchConfig {
+    pub engines: [EngineConfig; 2],
+    pub games: usize,
+    /// The number of games played at the same time.
+    pub parallel: usize,
+    pub game: GameSettings,
+    /// The directory where every game is saved as `game-<number>.sgf`.
+    pub sgf_dir: Option<PathBuf>,
+}
+
+/// The game of the match, the engines are given by their indices in `MatchConfig::engines`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchGame {
+    /// The game number from 1.
+    pub number: usize,
+    pub black: usize,
+    pub result: GameResult,
+    /// How the game ends, or why it is not played.
+    pub end: Result<GameEnd, String>,
+}
+
+impl MatchGame {
+    pub fn white(&self) -> usize {
+        1 - self.black
+    }
+
+    /// The engine winning the game, None for the draw or the void game.
+    pub fn winner(&self) -> Option<usize> {
+        match self.result.winner() {
+            Some(Player::Black) => Some(self.black),
+            Some(Player::White) => Some(self.white()),
+            None => None,
+        }
+    }
+}
+
+pub struct MatchResults {
+    /// The names of the engines, such as the command lines.
+    pub names: [String; 2],
+    /// The games in the order of their numbers.
+    pub games: Vec<MatchGame>,
+}
+
+impl MatchResults {
+    pub fn wins(&self, engine: usize) -> usize {
+        self.games.iter().filter(|game| game.winner() == Some(engine)).count()
+    }
+
+    /// The wins of the engine with the color.
+    pub fn wins_as(&self, engine: usize, player: Player) -> (usize, usize) {
+        let games: Vec<&MatchGame> = self.games.iter()
+            .filter(|game| match player {
+                Player::Black => game.black == engine,
+                Player::White => game.white() == engine,
+            })
+            .collect();
+
+        (games.iter().filter(|game| game.winner() == Some(engine)).count(), games.len())
+    }
+
+    pub fn draws(&self) -> usize {
+        self.games.iter().filter(|game| game.result == GameResult::Draw).count()
+    }
+
+    /// The games without the result, by the move limit or the failure to start.
+    pub fn voids(&self) -> usize {
+        self.games.iter().filter(|game| game.result == GameResult::Void).count()
+    }
+
+    /// The games with the result.
+    pub fn decided(&self) -> usize {
+        self.games.len() - self.voids()
+    }
+
+    /// The rate of the points of the engine in the decided games, the draw is half a point.
+    pub fn win_rate(&self, engine: usize) -> Option<f64> {
+        match self.decided() {
+            0 => None,
+            decided => Some((self.wins(engine) as f64 + self.draws() as f64 / 2.0) / decided as f64),
+        }
+    }
+
+    /// The 95% Wilson score interval of the win rate.
+    pub fn confidence_interval(&self, engine: usize) -> Option<(f64, f64)> {
+        let rate = self.win_rate(engine)?;
+        let n = self.decided() as f64;
+
+        let center = (rate + Z_95 * Z_95 / (2.0 * n)) / (1.0 + Z_95 * Z_95 / n);
+        let margin = Z_95 / (1.0 + Z_95 * Z_95 / n) * (rate * (1.0 - rate) / n + Z_95 * Z_95 / (4.0 * n * n)).sqrt();
+
+        Some(((center - margin).max(0.0), (center + margin).min(1.0)))
+    }
+
+    /// The table of the results, one row an engine.
+    pub fn table(&self) -> String {
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max("Engine".len());
+        let mut lines = vec![format!("{:width$}  {:>5}  {:>7}  {:>7}  {:>8}  {}", "Engine", "Wins", "Black", "White", "Win rate", "95% CI", width = width)];
+
+        for (engine, name) in self.names.iter().enumerate() {
+            let (black_wins, black_games) = self.wins_as(engine, Player::Black);
+            let (white_wins, white_games) = self.wins_as(engine, Player::White);
+
+            let (rate, interval) = match (self.win_rate(engine), self.confidence_interval(engine)) {
+                (Some(rate), Some((low, high))) => (format!("{:.1}%", rate * 100.0), format!("[{:.1}%, {:.1}%]", low * 100.0, high * 100.0)),
+                _ => ("-".to_string(), "-".to_string()),
+            };
+
+            lines.push(format!("{:width$}  {:>5}  {:>7}  {:>7}  {:>8}  {}",
+                name,
+                self.wins(engine),
+                format!("{}/{}", black_wins, black_games),
+                format!("{}/{}", white_wins, white_games),
+                rate,
+                interval,
+                width = width,
+            ));
+        }
+
+        lines.push(format!("Games {}, draws {}, void {}", self.games.len(), self.draws(), self.voids()));
+        lines.join("\n")
+    }
+}
+
+/// Plays the games of the match, the report is called after every game.
+pub async fn run_match(config: MatchConfig, report: impl Fn(&MatchGame) + Send + Sync + 'static) -> MatchResults {
+    let config = Arc::new(config);
+    let report = Arc::new(report);
+    let next = Arc::new(AtomicUsize::new(0));
+
+    let workers: Vec<_> = (0..config.parallel.max(1))
+        .map(|_| {
+            let config = config.clone();
+            let report = report.clone();
+            let next = next.clone();
+
+            tokio::spawn(async move {
+                let mut games: Vec<MatchGame> = Vec::new();
+
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= config.games {
+                        break;
+                    }
+
+                    let game = play(&config, index).await;
+                    report(&game);
+                    games.push(game);
+                }
+
+                games
+            })
+        })
+        .collect();
+
+    let mut games: Vec<MatchGame> = Vec::new();
+    for worker in workers {
+        games.extend(worker.await.expect("The match worker panics"));
+    }
+    games.sort_by_key(|game| game.number);
+
+    MatchResults {
+        names: [command_line(&config.engines[0]), command_line(&config.engines[1])],
+        games,
+    }
+}
+
+/// Plays the game of the index, the engines take black in turn.
+async fn play(config: &MatchConfig, index: usize) -> MatchGame {
+    let black = index % 2;
+    let number = index + 1;
+    let sgf = config.sgf_dir.as_ref().map(|sgf_dir| sgf_dir.join(format!("game-{:03}.sgf", number)));
+
+    match play_game(&config.engines[black], &config.engines[1 - black], &config.game, sgf.as_deref()).await {
+        Ok(game) => MatchGame {
+            number,
+            black,
+            result: game.result(),
+            end: Ok(game.end),
+        },
+        Err(error) => MatchGame {
+            number,
+            black,
+            result: GameResult::Void,
+            end: Err(error),
+        },
+    }
+}
+
+/// Spawns the engines and plays the game between them, the game is saved as the SGF file if the
+/// path is given.
+pub async fn play_game(black: &EngineConfig, white: &EngineConfig, settings: &GameSettings, sgf: Option<&Path>) -> Result<Game, String> {
+    let black_adaptor = spawn_command(black).map_err(|error| format!("Cannot run {}: {}", black.program, error))?;
+    let white_adaptor = spawn_command(white).map_err(|error| format!("Cannot run {}: {}", white.program, error))?;
+
+    let game = run(black_adaptor, white_adaptor, settings).await;
+
+    if let Some(path) = sgf {
+        if let Err(error) = std::fs::write(path, game.record.to_sgf()) {
+            eprintln!("Cannot write {}: {}", path.to_string_lossy(), error);
+        }
+    }
+
+    Ok(game)
+}
+
+fn command_line(engine: &EngineConfig) -> String {
+    let mut words = vec![engine.program.clone()];
+    words.extend(engine.args.iter().cloned());
+
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(number: usize, black: usize, result: GameResult) -> MatchGame {
+        MatchGame {
+            number,
+            black,
+            result,
+            end: Ok(GameEnd::Scored),
+        }
+    }
+
+    #[test]
+    fn test_results() {
+        let results = MatchResults {
+            names: ["gnugo --level 1".to_string(), "gnugo".to_string()],
+            games: vec![
+                game(1, 0, GameResult::Resign(Player::White)),
+                game(2, 1, GameResult::Score(Player::Black, 3.5)),
+                game(3, 0, GameResult::Score(Player::Black, 0.5)),
+                game(4, 1, GameResult::Draw),
+                game(5, 0, GameResult::Void),
+            ],
+        };
+
+        assert_eq!(results.wins(0), 1);
+        assert_eq!(results.wins(1), 2);
+        assert_eq!(results.wins_as(1, Player::White), (1, 3));
+        assert_eq!(results.decided(), 4);
+        assert_eq!(results.win_rate(0), Some(0.375));
+
+        let (low, high) = results.confidence_interval(1).unwrap();
+        assert!((low - 0.2194).abs() < 0.0001 && (high - 0.9081).abs() < 0.0001);
+
+        assert_eq!(results.table(), [
+            "Engine            Wins    Black    White  Win rate  95% CI",
+            "gnugo --level 1      1      1/3      0/2     37.5%  [9.2%, 78.1%]",
+            "gnugo                2      1/2      1/3     62.5%  [21.9%, 90.8%]",
+            "Games 5, draws 1, void 1",
+        ].join("\n"));
+    }
+}
@@ -0,0 +1,598 @@
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+
+use go_game_engine::{GameResult, Player};
+
+use crate::{BOARD_SIZE, KOMI_DEFAULT};
+use crate::arbitator::arbitator::GameSettings;
+use crate::config::EngineConfig;
+use crate::match_runner::play_game;
+
+/// The virtual draws added between every two engines which meet, so that the engine winning
+/// all its games has the finite rating.
+const PRIOR_DRAWS: f64 = 2.0;
+
+/// The z value of the 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentEngine {
+    /// The unique name of the engine in the tournament.
+    pub name: String,
+    pub engine: EngineConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    /// Every engine meets every other one.
+    RoundRobin,
+    /// The engine of the index meets every other one, the others don't meet each other.
+    Gauntlet(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentConfig {
+    pub engines: Vec<TournamentEngine>,
+    pub format: Format,
+    /// The games between two engines which meet, they take black in turn.
+    pub games_per_pair: usize,
+    /// The number of games played at the same time.
+    pub parallel: usize,
+    pub game: GameSettings,
+    /// The directory where every game is saved as `game-<number>.sgf`.
+    pub sgf_dir: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TournamentFile {
+    engines: Vec<EngineFile>,
+    /// The name of the engine meeting the others, round robin if None.
+    gauntlet: Option<String>,
+    games_per_pair: Option<usize>,
+    parallel: Option<usize>,
+    board_size: Option<u8>,
+    komi: Option<f64>,
+    rules: Option<String>,
+    max_moves: Option<u32>,
+    sgf_dir: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EngineFile {
+    name: String,
+    program: String,
+    args: Option<Vec<String>>,
+    working_dir: Option<PathBuf>,
+}
+
+/// The game in the schedule, the engines are given by their indices in `TournamentConfig::engines`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Pairing {
+    /// The game number from 1.
+    pub number: usize,
+    pub black: usize,
+    pub white: usize,
+}
+
+impl TournamentConfig {
+    /// Parses the JSON config, such as
+    /// `{"engines": [{"name": "gnugo-10", "program": "gnugo", "args": ["--mode", "gtp"]}, ...], "gauntlet": "gnugo-10", "games_per_pair": 4}`.
+    pub fn from_json(json: &str) -> Result<TournamentConfig, String> {
+        let file: TournamentFile = serde_json::from_str(json).map_err(|error| format!("Invalid config: {}", error))?;
+
+        let engines: Vec<TournamentEngine> = file.engines.into_iter()
+            .map(|engine| TournamentEngine {
+                name: engine.name,
+                engine: EngineConfig {
+                    program: engine.program,
+                    args: engine.args.unwrap_or_default(),
+                    working_dir: engine.working_dir,
+                },
+            })
+            .collect();
+
+        if engines.len() < 2 {
+            return Err("Two engines at least are needed".to_string());
+        }
+
+        let mut names: HashSet<&str> = HashSet::new();
+        for engine in engines.iter() {
+            if !names.insert(&engine.name) {
+                return Err(format!("Duplicate engine {}", engine.name));
+            }
+        }
+
+        let format = match &file.gauntlet {
+            Some(name) => match engines.iter().position(|engine| &engine.name == name) {
+                Some(index) => Format::Gauntlet(index),
+                None => return Err(format!("Unknown engine {}", name)),
+            },
+            None => Format::RoundRobin,
+        };
+
+        let board_size = file.board_size.unwrap_or(BOARD_SIZE);
+        if board_size < 1 || board_size as usize > go_game_engine::BOARD_SIZE_MAX {
+            return Err(format!("Unacceptable board size {}", board_size));
+        }
+
+        let komi = file.komi.unwrap_or(KOMI_DEFAULT);
+        if !komi.is_finite() {
+            return Err(format!("Unacceptable komi {}", komi));
+        }
+
+        Ok(TournamentConfig {
+            engines,
+            format,
+            games_per_pair: file.games_per_pair.unwrap_or(2),
+            parallel: file.parallel.unwrap_or(1).max(1),
+            game: GameSettings {
+                board_size,
+                komi,
+                rules: file.rules,
+                max_moves: file.max_moves,
+            },
+            sgf_dir: file.sgf_dir,
+        })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.engines.iter().map(|engine| engine.name.clone()).collect()
+    }
+
+    /// The games of the tournament, a round of all the pairs after another, so the results of an
+    /// unfinished tournament are still balanced.
+    pub fn schedule(&self) -> Vec<Pairing> {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+
+        for first in 0..self.engines.len() {
+            for second in (first + 1)..self.engines.len() {
+                match self.format {
+                    Format::Gauntlet(challenger) if first != challenger && second != challenger => {},
+                    _ => pairs.push((first, second)),
+                };
+            }
+        }
+
+        let mut pairings: Vec<Pairing> = Vec::new();
+        for round in 0..self.games_per_pair {
+            for (first, second) in pairs.iter() {
+                let (black, white) = match round % 2 {
+                    0 => (*first, *second),
+                    _ => (*second, *first),
+                };
+
+                pairings.push(Pairing {
+                    number: pairings.len() + 1,
+                    black,
+                    white,
+                });
+            }
+        }
+
+        pairings
+    }
+}
+
+/// The finished game of the tournament, one JSON line in the results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentGame {
+    pub number: usize,
+    pub black: String,
+    pub white: String,
+    pub result: GameResult,
+}
+
+impl TournamentGame {
+    pub fn to_json(&self) -> String {
+        json!({
+            "game": self.number,
+            "black": self.black,
+            "white": self.white,
+            "result": self.result.to_string(),
+        }).to_string()
+    }
+
+    pub fn from_json(line: &str) -> Option<TournamentGame> {
+        let game: Value = serde_json::from_str(line).ok()?;
+
+        Some(TournamentGame {
+            number: game["game"].as_u64()? as usize,
+            black: game["black"].as_str()?.to_string(),
+            white: game["white"].as_str()?.to_string(),
+            result: game["result"].as_str()?.parse().ok()?,
+        })
+    }
+
+    /// The points of the engine in the game, None if the engine doesn't play it or the game is void.
+    fn points(&self, name: &str) -> Option<f64> {
+        let player = match name {
+            name if name == self.black => Player::Black,
+            name if name == self.white => Player::White,
+            _ => return None,
+        };
+
+        match self.result {
+            GameResult::Void => None,
+            GameResult::Draw => Some(0.5),
+            result if result.winner() == Some(player) => Some(1.0),
+            _ => Some(0.0),
+        }
+    }
+}
+
+/// Reads the results written by `run_tournament`, missing for the tournament not started.
+///
+/// The last line cut by the interruption is returned apart from the games, the file is left as it is.
+pub fn read_results(path: &Path) -> Result<(Vec<TournamentGame>, Option<String>), String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), None)),
+        Err(error) => return Err(format!("{}: {}", path.to_string_lossy(), error)),
+    };
+
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut games: Vec<TournamentGame> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        match TournamentGame::from_json(line) {
+            Some(game) => games.push(game),
+            None if index + 1 == lines.len() => return Ok((games, Some(line.to_string()))),
+            None => return Err(format!("{}: invalid result {}", path.to_string_lossy(), line)),
+        };
+    }
+
+    Ok((games, None))
+}
+
+/// Plays the games of the schedule not in the results yet, every finished game is appended to
+/// the results at once. The report is called after every game, with the error if the game can't
+/// be played; such a game is played again when the tournament is resumed.
+pub async fn run_tournament(config: TournamentConfig, results: &Path, report: impl Fn(&Pairing, &Result<TournamentGame, String>) + Send + Sync + 'static) -> Result<Vec<TournamentGame>, String> {
+    let schedule = config.schedule();
+    let (mut games, cut) = read_results(results)?;
+
+    for game in games.iter() {
+        let is_scheduled = schedule.iter().any(|pairing| {
+            pairing.number == game.number
+                && config.engines[pairing.black].name == game.black
+                && config.engines[pairing.white].name == game.white
+        });
+
+        if !is_scheduled {
+            return Err(format!("Game {} in the results is not in the schedule", game.number));
+        }
+    }
+
+    let pending: Vec<Pairing> = schedule.into_iter()
+        .filter(|pairing| games.iter().all(|game| game.number != pairing.number))
+        .collect();
+
+    /* The line cut by the interruption is dropped before the games are appended after it */
+    if cut.is_some() {
+        let kept: String = games.iter().map(|game| format!("{}\n", game.to_json())).collect();
+        std::fs::write(results, kept).map_err(|error| format!("{}: {}", results.to_string_lossy(), error))?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(results)
+        .map_err(|error| format!("{}: {}", results.to_string_lossy(), error))?;
+
+    let config = Arc::new(config);
+    let pending = Arc::new(pending);
+    let file = Arc::new(Mutex::new(file));
+    let report = Arc::new(report);
+    let next = Arc::new(AtomicUsize::new(0));
+
+    let workers: Vec<_> = (0..config.parallel)
+        .map(|_| {
+            let (config, pending, file, report, next) = (config.clone(), pending.clone(), file.clone(), report.clone(), next.clone());
+
+            tokio::spawn(async move {
+                let mut games: Vec<TournamentGame> = Vec::new();
+
+                while let Some(pairing) = pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let game = play(&config, pairing).await;
+
+                    if let Ok(game) = &game {
+                        let mut file = file.lock().unwrap();
+
+                        if let Err(error) = writeln!(file, "{}", game.to_json()).and_then(|_| file.flush()) {
+                            eprintln!("Cannot write the result of game {}: {}", game.number, error);
+                        }
+
+                        games.push(game.clone());
+                    }
+
+                    report(pairing, &game);
+                }
+
+                games
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        games.extend(worker.await.expect("The tournament worker panics"));
+    }
+    games.sort_by_key(|game| game.number);
+
+    Ok(games)
+}
+
+async fn play(config: &TournamentConfig, pairing: &Pairing) -> Result<TournamentGame, String> {
+    let (black, white) = (&config.engines[pairing.black], &config.engines[pairing.white]);
+    let sgf = config.sgf_dir.as_ref().map(|sgf_dir| sgf_dir.join(format!("game-{:03}.sgf", pairing.number)));
+
+    let game = play_game(&black.engine, &white.engine, &config.game, sgf.as_deref()).await?;
+
+    Ok(TournamentGame {
+        number: pairing.number,
+        black: black.name.clone(),
+        white: white.name.clone(),
+        result: game.result(),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rating {
+    pub name: String,
+    /// The Elo rating, the average of the engines is 0.
+    pub elo: f64,
+    /// The half width of the 95% confidence interval, None without games.
+    pub error: Option<f64>,
+    /// The games with the result.
+    pub games: usize,
+    /// The points, the draw is half a point.
+    pub score: f64,
+}
+
+/// The results of the engines in the tournament.
+pub struct Standings {
+    names: Vec<String>,
+    /// The games and the points between every two engines, the void games excluded.
+    games: Vec<Vec<usize>>,
+    points: Vec<Vec<f64>>,
+}
+
+impl Standings {
+    pub fn new(names: Vec<String>, results: &[TournamentGame]) -> Standings {
+        let count = names.len();
+        let mut games = vec![vec![0; count]; count];
+        let mut points = vec![vec![0.0; count]; count];
+
+        for result in results.iter() {
+            let black = names.iter().position(|name| *name == result.black);
+            let white = names.iter().position(|name| *name == result.white);
+
+            if let (Some(black), Some(white), Some(black_points)) = (black, white, result.points(&result.black)) {
+                games[black][white] += 1;
+                games[white][black] += 1;
+                points[black][white] += black_points;
+                points[white][black] += 1.0 - black_points;
+            }
+        }
+
+        Standings {
+            names,
+            games,
+            points,
+        }
+    }
+
+    /// The Bradley-Terry ratings by the maximum likelihood with the prior draws, from the best.
+    pub fn ratings(&self) -> Vec<Rating> {
+        let count = self.names.len();
+        let prior = |first: usize, second: usize| match self.games[first][second] {
+            0 => 0.0,
+            _ => PRIOR_DRAWS,
+        };
+
+        /* The engines without games keep the strength 1, the average of the others */
+        let playing: Vec<usize> = (0..count).filter(|first| self.games[*first].iter().sum::<usize>() > 0).collect();
+
+        /* The minorization-maximization iterations of the strengths */
+        let mut strengths = vec![1.0; count];
+        for _ in 0..1000 {
+            let mut next = strengths.clone();
+
+            for first in 0..count {
+                let mut points = 0.0;
+                let mut games = 0.0;
+
+                for second in (0..count).filter(|second| *second != first) {
+                    let meetings = self.games[first][second] as f64 + prior(first, second);
+
+                    points += self.points[first][second] + prior(first, second) / 2.0;
+                    games += meetings / (strengths[first] + strengths[second]);
+                }
+
+                if games > 0.0 {
+                    next[first] = points / games;
+                }
+            }
+
+            if !playing.is_empty() {
+                let mean = playing.iter().map(|first| next[*first].ln()).sum::<f64>() / playing.len() as f64;
+                playing.iter().for_each(|first| next[*first] /= mean.exp());
+            }
+
+            let change = next.iter().zip(strengths.iter()).map(|(next, strength)| (next - strength).abs()).fold(0.0, f64::max);
+            strengths = next;
+
+            if change < 1e-9 {
+                break;
+            }
+        }
+
+        let elo_scale = 400.0 / std::f64::consts::LN_10;
+
+        let mut ratings: Vec<Rating> = (0..count)
+            .map(|first| {
+                /* The Fisher information of the log strength */
+                let information: f64 = (0..count)
+                    .filter(|second| *second != first)
+                    .map(|second| {
+                        let expected = strengths[first] / (strengths[first] + strengths[second]);
+                        (self.games[first][second] as f64 + prior(first, second)) * expected * (1.0 - expected)
+                    })
+                    .sum();
+
+                Rating {
+                    name: self.names[first].clone(),
+                    elo: elo_scale * strengths[first].ln(),
+                    error: match information > 0.0 {
+                        true => Some(Z_95 * elo_scale / information.sqrt()),
+                        false => None,
+                    },
+                    games: self.games[first].iter().sum(),
+                    score: self.points[first].iter().sum(),
+                }
+            })
+            .collect();
+
+        ratings.sort_by(|first, second| second.elo.partial_cmp(&first.elo).unwrap_or(std::cmp::Ordering::Equal));
+        ratings
+    }
+
+    /// The ratings and the points against every engine, the columns are numbered by the ranks.
+    pub fn crosstable(&self) -> String {
+        let ratings = self.ratings();
+        let order: Vec<usize> = ratings.iter()
+            .map(|rating| self.names.iter().position(|name| *name == rating.name).unwrap())
+            .collect();
+
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max("Engine".len());
+        let cell = |first: usize, second: usize| match self.games[first][second] {
+            _ if first == second => "-".to_string(),
+            0 => "".to_string(),
+            games => format!("{}/{}", self.points[first][second], games),
+        };
+
+        let mut header = format!("{:>3}  {:width$}  {:>6}  {:>5}  {:>5}  {:>6}", "#", "Engine", "Elo", "+/-", "Games", "Score", width = width);
+        for rank in 1..=order.len() {
+            header.push_str(&format!("  {:>7}", rank));
+        }
+
+        let mut lines = vec![header];
+        for (rank, (rating, first)) in ratings.iter().zip(order.iter()).enumerate() {
+            let mut line = format!("{:>3}  {:width$}  {:>6.0}  {:>5}  {:>5}  {:>6}",
+                rank + 1,
+                rating.name,
+                rating.elo,
+                rating.error.map(|error| format!("{:.0}", error)).unwrap_or_else(|| "-".to_string()),
+                rating.games,
+                rating.score,
+                width = width,
+            );
+
+            for second in order.iter() {
+                line.push_str(&format!("  {:>7}", cell(*first, *second)));
+            }
+
+            lines.push(line.trim_end().to_string());
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "engines": [
+            {"name": "a", "program": "gnugo", "args": ["--mode", "gtp", "--level", "1"]},
+            {"name": "b", "program": "gnugo"},
+            {"name": "c", "program": "leelaz", "working_dir": "/opt/leela"}
+        ],
+        "games_per_pair": 2,
+        "board_size": 9
+    }"#;
+
+    fn game(number: usize, black: &str, white: &str, result: &str) -> TournamentGame {
+        TournamentGame {
+            number,
+            black: black.to_string(),
+            white: white.to_string(),
+            result: result.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut config = TournamentConfig::from_json(CONFIG).unwrap();
+        assert_eq!(config.game.board_size, 9);
+        assert_eq!(config.engines[2].engine.working_dir, Some(PathBuf::from("/opt/leela")));
+
+        let schedule = config.schedule();
+        assert_eq!(schedule.len(), 6);
+        assert_eq!(schedule[0], Pairing { number: 1, black: 0, white: 1 });
+        assert_eq!(schedule[3], Pairing { number: 4, black: 1, white: 0 });
+
+        config.format = Format::Gauntlet(2);
+        let schedule = config.schedule();
+        assert_eq!(schedule.len(), 4);
+        assert!(schedule.iter().all(|pairing| pairing.black == 2 || pairing.white == 2));
+
+        assert!(TournamentConfig::from_json(r#"{"engines": [{"name": "a", "program": "gnugo"}]}"#).is_err());
+        assert!(TournamentConfig::from_json(r#"{"engines": [{"name": "a", "program": "gnugo"}, {"name": "a", "program": "gnugo"}]}"#).is_err());
+        assert!(TournamentConfig::from_json(r#"{"engines": [{"name": "a", "program": "gnugo"}, {"name": "b", "program": "gnugo"}], "gauntlet": "c"}"#).is_err());
+    }
+
+    #[test]
+    fn test_read_results() {
+        let path = std::env::temp_dir().join(format!("tgoban_tournament_{}.jsonl", std::process::id()));
+        let first = game(1, "a", "b", "B+R");
+        let text = format!("{}\n{{\"game\": 2, \"bla", first.to_json());
+        std::fs::write(&path, &text).unwrap();
+
+        assert_eq!(read_results(&path), Ok((vec![first.clone()], Some("{\"game\": 2, \"bla".to_string()))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+
+        std::fs::write(&path, format!("garbage\n{}\n", first.to_json())).unwrap();
+        assert!(read_results(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_results(&path), Ok((Vec::new(), None)));
+    }
+
+    #[test]
+    fn test_ratings() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let results = vec![
+            game(1, "a", "b", "B+R"),
+            game(2, "b", "a", "W+3.5"),
+            game(3, "a", "b", "B+0.5"),
+            game(4, "b", "a", "B+R"),
+            game(5, "a", "b", "Void"),
+        ];
+
+        let ratings = Standings::new(names.clone(), &results).ratings();
+        assert_eq!(ratings[0].name, "a");
+        assert_eq!((ratings[0].games, ratings[0].score), (4, 3.0));
+
+        /* 4 points of 6 games with the prior draws, the strength is double */
+        assert!((ratings[0].elo - 60.2).abs() < 0.1 && (ratings[1].elo + 60.2).abs() < 0.1);
+        assert!(ratings[0].error.unwrap() > 100.0);
+
+        assert_eq!(Standings::new(names, &results).crosstable(), [
+            "  #  Engine     Elo    +/-  Games   Score        1        2",
+            "  1  a           60    295      4       3        -      3/4",
+            "  2  b          -60    295      4       1      1/4        -",
+        ].join("\n"));
+
+        /* The engine not playing yet doesn't move the others */
+        let ratings = Standings::new(vec!["a".to_string(), "b".to_string(), "c".to_string()], &results).ratings();
+        assert!((ratings[0].elo - 60.2).abs() < 0.1);
+        assert_eq!((ratings[1].name.as_str(), ratings[1].elo, ratings[1].error), ("c", 0.0, None));
+    }
+}
@@ -0,0 +1,51 @@
+use go_game_engine::{GameResult, GoGameEngine, Player};
+
+use tgoban_ws_adapter::arbitator::arbitator::{GameEnd, GameSettings};
+use tgoban_ws_adapter::config::EngineConfig;
+use tgoban_ws_adapter::match_runner::{MatchConfig, run_match};
+
+fn mock_engine(script: &std::path::Path) -> EngineConfig {
+    EngineConfig {
+        program: env!("CARGO_BIN_EXE_tgoban_mock_engine").to_string(),
+        args: vec![script.to_string_lossy().to_string()],
+        working_dir: None,
+    }
+}
+
+/// The first engine always resigns at once, the second one always passes.
+#[tokio::test]
+async fn test_run_match() {
+    let dir = std::env::temp_dir().join(format!("tgoban_match_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let resign = dir.join("resign.gtp");
+    let pass = dir.join("pass.gtp");
+    std::fs::write(&resign, "genmove resign\n").unwrap();
+    std::fs::write(&pass, "").unwrap();
+
+    let results = run_match(MatchConfig {
+        engines: [mock_engine(&resign), mock_engine(&pass)],
+        games: 4,
+        parallel: 2,
+        game: GameSettings {
+            board_size: 9,
+            komi: 6.5,
+            rules: None,
+            max_moves: Some(10),
+        },
+        sgf_dir: Some(dir.clone()),
+    }, |_| {}).await;
+
+    assert_eq!(results.games.iter().map(|game| game.number).collect::<Vec<usize>>(), [1, 2, 3, 4]);
+    assert_eq!(results.games[0].end, Ok(GameEnd::Resigned(Player::Black)));
+    assert_eq!(results.games[1].result, GameResult::Resign(Player::Black));
+    assert_eq!(results.wins(1), 4);
+    assert_eq!(results.wins_as(1, Player::White), (2, 2));
+    assert_eq!(results.win_rate(0), Some(0.0));
+
+    let record = GoGameEngine::from_sgf(&std::fs::read_to_string(dir.join("game-002.sgf")).unwrap()).unwrap();
+    assert_eq!(record.steps(), 1);
+    assert_eq!(record.player_name(Player::Black), Some("Mock 1.0"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use tgoban_gtp::{Color, Command, CommandResult, GenMoveResult, ResponseError, Vertex};
+use tgoban_ws_adapter::arbitator::adaptor::Adaptor;
+use tgoban_ws_adapter::arbitator::command_adaptor::spawn_command;
+use tgoban_ws_adapter::config::EngineConfig;
+
+/// The mock engine binary driven through the pipe as gnugo is.
+#[tokio::test]
+async fn test_command_adaptor() {
+    let path = std::env::temp_dir().join(format!("tgoban_mock_engine_{}.gtp", std::process::id()));
+    let mut script = std::fs::File::create(&path).unwrap();
+    writeln!(script, "genmove delay 50 move Q16\ngenmove resign\nplay fail illegal move\nfinal_score raw garbage").unwrap();
+
+    /* The script is found in the working directory of the engine */
+    let mut adaptor = spawn_command(&EngineConfig {
+        program: env!("CARGO_BIN_EXE_tgoban_mock_engine").to_string(),
+        args: vec![path.file_name().unwrap().to_string_lossy().to_string()],
+        working_dir: Some(std::env::temp_dir()),
+    }).unwrap();
+
+    match adaptor.send_command(Command::Name(Some(1))).await {
+        Ok(CommandResult::Name(Some(1), name)) => assert_eq!(name, "Mock"),
+        _ => assert!(false, "The name is not replied"),
+    };
+
+    match adaptor.send_command(Command::GenMove(None, Color::Black)).await {
+        Ok(CommandResult::GenMove(None, GenMoveResult::Move(vertex))) => assert_eq!(vertex, Vertex::Coordinate('Q', 16)),
+        _ => assert!(false, "The scripted move is not replied"),
+    };
+
+    match adaptor.send_command(Command::GenMove(None, Color::White)).await {
+        Ok(CommandResult::GenMove(None, GenMoveResult::Resign)) => (),
+        _ => assert!(false, "The resignation is not replied"),
+    };
+
+    assert_eq!(adaptor.send_command("play b d4".parse().unwrap()).await.err(), Some(ResponseError::Failure(None, "illegal move".to_string())));
+    assert_eq!(adaptor.send_command(Command::FinalScore(None)).await.err(), Some(ResponseError::Malformed("garbage".to_string())));
+
+    assert!(adaptor.send_command(Command::Quit(None)).await.is_ok());
+    assert_eq!(adaptor.send_command(Command::Name(None)).await.err(), Some(ResponseError::Closed));
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use go_game_engine::{GameResult, Player};
+
+use tgoban_ws_adapter::tournament::{Standings, TournamentConfig, read_results, run_tournament};
+
+/// The engine `resign` always resigns at once, the engines `pass` and `move` never do.
+fn config(dir: &std::path::Path) -> TournamentConfig {
+    let program = env!("CARGO_BIN_EXE_tgoban_mock_engine");
+
+    for (name, script) in [("resign", "genmove resign\n"), ("pass", ""), ("move", "genmove move E5\n")].iter() {
+        std::fs::write(dir.join(format!("{}.gtp", name)), script).unwrap();
+    }
+
+    let engine = |name: &str| format!(r#"{{"name": "{}", "program": "{}", "args": ["{}"]}}"#, name, program, dir.join(format!("{}.gtp", name)).to_string_lossy());
+
+    TournamentConfig::from_json(&format!(
+        r#"{{"engines": [{}, {}, {}], "games_per_pair": 2, "parallel": 2, "board_size": 9, "max_moves": 20}}"#,
+        engine("resign"), engine("pass"), engine("move"),
+    )).unwrap()
+}
+
+#[tokio::test]
+async fn test_resume() {
+    let dir = std::env::temp_dir().join(format!("tgoban_tournament_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let results = dir.join("results.jsonl");
+
+    let played = Arc::new(AtomicUsize::new(0));
+    let counter = played.clone();
+    let games = run_tournament(config(&dir), &results, move |_, game| {
+        assert!(game.is_ok());
+        counter.fetch_add(1, Ordering::SeqCst);
+    }).await.unwrap();
+
+    assert_eq!(played.load(Ordering::SeqCst), 6);
+    assert_eq!(games.iter().map(|game| game.number).collect::<Vec<usize>>(), [1, 2, 3, 4, 5, 6]);
+    assert_eq!(games[0].result, GameResult::Resign(Player::White));
+
+    /* The tournament is interrupted before the last two games are written */
+    let lines: Vec<String> = std::fs::read_to_string(&results).unwrap().lines().map(|line| line.to_string()).collect();
+    let kept: Vec<&String> = lines.iter().filter(|line| !line.contains("\"game\":5") && !line.contains("\"game\":6")).collect();
+    std::fs::write(&results, format!("{}\n{{\"game\":5", kept.iter().map(|line| line.as_str()).collect::<Vec<&str>>().join("\n"))).unwrap();
+
+    /* The report leaves the cut line to the resumed tournament */
+    assert_eq!(read_results(&results).unwrap().1, Some("{\"game\":5".to_string()));
+    assert!(std::fs::read_to_string(&results).unwrap().ends_with("{\"game\":5"));
+
+    let played = Arc::new(AtomicUsize::new(0));
+    let counter = played.clone();
+    let resumed = run_tournament(config(&dir), &results, move |pairing, _| {
+        assert!(pairing.number >= 5);
+        counter.fetch_add(1, Ordering::SeqCst);
+    }).await.unwrap();
+
+    assert_eq!(played.load(Ordering::SeqCst), 2);
+    assert_eq!(resumed, games);
+    let (written, cut) = read_results(&results).unwrap();
+    assert_eq!(written.len(), 6);
+    assert_eq!(cut, None);
+
+    let ratings = Standings::new(config(&dir).names(), &resumed).ratings();
+    assert_eq!(ratings.last().unwrap().name, "resign");
+    assert_eq!(ratings.last().unwrap().score, 0.0);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}